@@ -0,0 +1,205 @@
+app-name = حرارة المعالج
+
+## Popup
+
+unit = الوحدة
+celsius = مئوية
+fahrenheit = فهرنهايت
+kelvin = كلفن
+
+refresh-interval = فترة التحديث (ملي ثانية)
+refresh-interval-too-short = تم رفعها إلى الحد الأدنى { $min } ملي ثانية
+refresh-interval-too-long = تم تقييدها بالحد الأقصى { $max } ملي ثانية
+refresh-interval-invalid = يجب أن يكون عددًا صحيحًا بالملي ثانية
+refresh-now = تحديث الآن
+pin-popup = إبقاء مفتوحة
+unpin-popup = إغلاق
+display-interval = فترة العرض (ملي ثانية)
+display-interval-too-short = لا يمكن أن تكون أقصر من فترة التحديث، لم يتم الحفظ
+slower-refresh-on-battery = تحديث أبطأ عند العمل على البطارية
+battery-refresh-multiplier = مضاعف التحديث على البطارية
+
+warning-threshold = حد التحذير ({ $unit })
+critical-threshold = الحد الحرج ({ $unit })
+thresholds-out-of-order = يجب أن يكون حد التحذير أقل من الحد الحرج، لم يُحفظ
+sensor-thresholds = الحدود لـ { $sensor }
+hide-below = إخفاء النص تحت ({ $unit })
+temperature-offset = إزاحة المعايرة (°C)
+notify-critical = التنبيه عند بلوغ الحرارة الحرجة
+flash-on-critical = وميض اللوحة عند الحرارة الحرجة
+disable-animations = تعطيل الحركات
+alert-sound = صوت عند درجة الحرارة الحرجة
+test-sound = اختبار
+sound-failed = فشل الصوت: { $error }
+sound-not-found = لا يوجد صوت باسم { $sound }
+quiet-hours = ساعات الهدوء
+alerts-muted-until = التنبيهات مكتومة حتى { $time }
+persist-history = الاحتفاظ بالسجل بعد إعادة التشغيل
+graph-range = مدة الرسم البياني
+graph-range-5m = 5 دقائق
+graph-range-30m = 30 دقيقة
+graph-range-2h = ساعتان
+graph-fixed-scale = مقياس ثابت للرسم البياني
+graph-min = الحد الأدنى للرسم البياني ({ $unit })
+graph-max = الحد الأقصى للرسم البياني ({ $unit })
+log-to-file = تسجيل القراءات في ملف
+log-file = ملف السجل
+log-max-size = حد حجم السجل (ميغابايت)
+log-failed = توقف التسجيل
+critical-action = أمر عند بلوغ الحرارة الحرجة
+critical-action-delay = التشغيل بعد (ثانية)
+critical-action-failed = فشل الأمر: { $error }
+metrics-enabled = مقاييس Prometheus على 127.0.0.1
+metrics-port = منفذ المقاييس
+mqtt-enabled = النشر عبر MQTT
+mqtt-target = النشر إلى { $topic } على { $broker }
+mqtt-failed = توقف النشر عبر MQTT
+mqtt-broker = وسيط MQTT
+mqtt-topic = موضوع MQTT
+mqtt-credentials-file = ملف بيانات اعتماد MQTT
+mqtt-interval-multiplier = نشر كل قراءة رقم N
+socket-enabled = توفير المستشعرات عبر مقبس يونكس
+max-plausible = تجاهل القراءات فوق ({ $unit })
+monitor-command = الأمر عند النقر الأوسط
+monitor-launch-failed = تعذّر تشغيل مراقب النظام: { $error }
+no-system-monitor = لم يتم تثبيت cosmic-monitor أو gnome-system-monitor
+
+show-load = عرض حمل المعالج في اللوحة
+load-without-sensors = عرض حمل المعالج عند عدم وجود مستشعرات
+show-fan = عرض سرعة المروحة في اللوحة
+show-power = قراءة طاقة الحزمة
+power-in-panel = عرض الطاقة في اللوحة
+show-throttling = إظهار الخنق الحراري
+show-hot-core = إظهار اسم أسخن نواة في اللوحة
+show-both-units = عرض الوحدتين معًا
+show-icon = إظهار الأيقونة
+threshold-indication = إظهار الحدود بواسطة
+indication-color = لون النص
+indication-icon = الأيقونة
+indication-both = كلاهما
+panel-style = تعرض اللوحة
+panel-style-text = درجة الحرارة
+panel-style-icon-only = الأيقونة فقط
+# Tooltip of the icon-only panel, "CPU: 63°C"
+panel-tooltip = { $sensor }: { $temp }
+
+sensor = المستشعر
+scroll-to-switch = مرّر فوق عنصر اللوحة للتبديل بين المستشعرات
+ignore-sensor = تجاهل
+ignored-sensors = المستشعرات المتجاهلة
+unignore-sensor = استخدام مجددًا
+sensor-backend = قراءة المستشعرات عبر
+automatic = تلقائي
+sensor-error = خطأ في المستشعر
+sensor-access-denied = تعذرت قراءة المستشعرات: { $error } في { $path }
+no-sensors-in-vm = لا توجد مستشعرات حرارة متاحة (تم اكتشاف آلة افتراضية)
+config-write-failed = لم يتم حفظ الإعدادات ({ $count } محاولات)
+config-unavailable = مجلد الإعدادات غير متاح
+cpu-temperature = حرارة المعالج
+source = المصدر: { $source }
+source-component = { $label }
+source-component-chip = { $label } ({ $chip })
+cpu-alternative = أيضًا على هذه الحزمة: { $sensor }
+selected-sensor-missing = { $sensor } غير موجود، يتم استخدام الاكتشاف التلقائي حتى يعود
+source-cores = { $aggregation } لـ { $count } من مستشعرات الأنوية
+source-thermal-zone = مناطق الحرارة في النواة
+no-cpu-sensor = لم يتم العثور على مستشعر لحرارة المعالج، تحقق من أن الأمر `sensors` من lm-sensors يعرض واحدًا
+
+read-error = لا توجد قيمة من { $source }
+no-reading-for = لا توجد قراءة منذ { $seconds } ث
+no-reading-for-sensor = لا توجد قراءة منذ { $seconds } ث — آخر مستشعر: { $sensor }
+
+combine-cores = دمج الأنوية
+maximum = الأعلى
+average = المتوسط
+median = الوسيط
+prefer-tctl = تفضيل Tctl على Tdie
+hottest-ccd = استخدام أسخن CCD
+
+display = العرض
+display-cpu = المعالج
+display-gpu = معالج الرسوميات
+display-both = المعالج / معالج الرسوميات
+display-hottest = أسخن مستشعر
+display-headroom = الهامش حتى الحد الحرج
+headroom-no-critical = لا يبلغ المستشعر عن حد حرج ولم يتم تعيين حد، يتم عرض درجة الحرارة بدلاً من ذلك
+
+decimal-places = المنازل العشرية
+rounding-step = التقريب إلى
+rounding-off = إيقاف
+
+smoothing = التنعيم
+smoothing-off = إيقاف
+smoothing-light = خفيف
+smoothing-medium = متوسط
+smoothing-strong = قوي
+smoothed-raw = منعّم / خام
+
+fixed-width = عرض ثابت
+panel-format = تنسيق اللوحة
+panel-prefix = بادئة اللوحة
+panel-suffix = لاحقة اللوحة
+panel-format-preview = معاينة: { $text }
+
+cpu = المعالج
+cpu-load = حمل المعالج
+fans = المراوح
+fan-speed = { $rpm } دورة/دقيقة
+package-power = طاقة الحزمة
+throttling = الخنق الحراري
+throttling-badge = خنق
+throttling-active = نشط، { $count } أحداث في هذه الجلسة
+throttling-inactive = لا، { $count } أحداث في هذه الجلسة
+both-units = الوحدتان
+sensor-max = الأقصى { $temp }
+sensor-critical = الحرج { $temp }
+gpu-sensor = مستشعر معالج الرسوميات
+hottest-sensor = أسخن مستشعر
+not-found = غير موجود
+show-drive-temps = إظهار حرارة الأقراص
+drives = الأقراص
+all-sensors = جميع المستشعرات
+diagnostics = التشخيص
+copy-diagnostics = نسخ التشخيص
+diagnostics-version = الإصدار
+diagnostics-components = المكونات المكتشفة
+diagnostics-sensor = مستشعر المعالج
+diagnostics-priority = الأولوية { $priority }
+diagnostics-read-time = مدة آخر قراءة
+diagnostics-read-times = القراءات في الدقيقة الأخيرة
+diagnostics-tick-delay = تأخر التحديثات في الدقيقة الأخيرة
+diagnostics-timing-value = المتوسط { $avg } / الأقصى { $max }
+diagnostics-last-error = آخر خطأ
+diagnostics-calibration = المعايرة
+diagnostics-calibration-value = { $raw } مقروءة، { $adjusted } معروضة
+diagnostics-none = لا شيء
+cores = الأنوية
+hottest-core = الأسخن: { $core } ({ $temp })
+
+peak = الذروة: { $temp }
+reset = إعادة تعيين
+copy-readings = نسخ القراءات
+open-settings = كل الإعدادات…
+menu-toggle-unit = العرض بـ { $unit }
+menu-reset-peak = إعادة تعيين الذروة
+menu-pause-updates = إيقاف التحديثات مؤقتًا
+menu-resume-updates = استئناف التحديثات
+copied = تم النسخ
+stats-last-minutes = آخر { $minutes } دقيقة
+stats-values = الأدنى { $min } · المتوسط { $avg } · الأقصى { $max }
+
+## Notifications
+
+critical-notification-summary = حرارة المعالج حرجة
+critical-notification-body = بلغت حرارة المعالج { $temp }
+quiet-hours-summary = ارتفعت حرارة المعالج خلال ساعات الهدوء
+quiet-hours-summary-body = بلغت حرارة المعالج { $temp } أثناء كتم التنبيهات
+
+## Settings window
+
+settings-title = إعدادات حرارة المعالج
+settings-panel = اللوحة
+settings-sensors = المستشعرات
+settings-refreshing = التحديث
+settings-alerts = التنبيهات
+settings-other = أخرى
@@ -0,0 +1,205 @@
+app-name = CPU-Temperatur
+
+## Popup
+
+unit = Einheit
+celsius = Celsius
+fahrenheit = Fahrenheit
+kelvin = Kelvin
+
+refresh-interval = Aktualisierungsintervall (ms)
+refresh-interval-too-short = Auf das Minimum von { $min } ms angehoben
+refresh-interval-too-long = Auf das Maximum von { $max } ms begrenzt
+refresh-interval-invalid = Muss eine ganze Zahl in Millisekunden sein
+refresh-now = Jetzt aktualisieren
+pin-popup = Geöffnet lassen
+unpin-popup = Schließen
+display-interval = Anzeigeintervall (ms)
+display-interval-too-short = Darf nicht kürzer als das Aktualisierungsintervall sein, nicht gespeichert
+slower-refresh-on-battery = Im Akkubetrieb seltener aktualisieren
+battery-refresh-multiplier = Faktor im Akkubetrieb
+
+warning-threshold = Warnschwelle ({ $unit })
+critical-threshold = Kritische Schwelle ({ $unit })
+thresholds-out-of-order = Die Warnschwelle muss unter der kritischen liegen, nicht gespeichert
+sensor-thresholds = Schwellenwerte für { $sensor }
+hide-below = Text ausblenden unter ({ $unit })
+temperature-offset = Kalibrierungsversatz (°C)
+notify-critical = Bei kritischer Temperatur benachrichtigen
+flash-on-critical = Panel bei kritischer Temperatur blinken lassen
+disable-animations = Animationen deaktivieren
+alert-sound = Ton bei kritischer Temperatur
+test-sound = Testen
+sound-failed = Ton fehlgeschlagen: { $error }
+sound-not-found = Kein Ton namens { $sound }
+quiet-hours = Ruhezeit
+alerts-muted-until = Warnungen stumm bis { $time }
+persist-history = Verlauf über Neustarts behalten
+graph-range = Zeitraum des Verlaufs
+graph-range-5m = 5 Min.
+graph-range-30m = 30 Min.
+graph-range-2h = 2 Std.
+graph-fixed-scale = Feste Skala des Verlaufs
+graph-min = Minimum des Verlaufs ({ $unit })
+graph-max = Maximum des Verlaufs ({ $unit })
+log-to-file = Messwerte in Datei protokollieren
+log-file = Protokolldatei
+log-max-size = Protokollgröße (MB)
+log-failed = Protokollierung angehalten
+critical-action = Befehl bei kritischer Temperatur
+critical-action-delay = Ausführen nach (s)
+critical-action-failed = Befehl fehlgeschlagen: { $error }
+metrics-enabled = Prometheus-Metriken auf 127.0.0.1
+metrics-port = Metrik-Port
+mqtt-enabled = Über MQTT veröffentlichen
+mqtt-target = Veröffentlicht auf { $topic } bei { $broker }
+mqtt-failed = MQTT-Veröffentlichung gestoppt
+mqtt-broker = MQTT-Broker
+mqtt-topic = MQTT-Topic
+mqtt-credentials-file = MQTT-Zugangsdaten-Datei
+mqtt-interval-multiplier = Jede n-te Messung veröffentlichen
+socket-enabled = Sensoren über einen Unix-Socket bereitstellen
+max-plausible = Messwerte ignorieren über ({ $unit })
+monitor-command = Befehl bei Mittelklick
+monitor-launch-failed = Die Systemüberwachung konnte nicht gestartet werden: { $error }
+no-system-monitor = weder cosmic-monitor noch gnome-system-monitor ist installiert
+
+show-load = CPU-Auslastung im Panel anzeigen
+load-without-sensors = CPU-Auslastung ohne Sensoren anzeigen
+show-fan = Lüfterdrehzahl im Panel anzeigen
+show-power = Package-Leistung lesen
+power-in-panel = Leistung im Panel anzeigen
+show-throttling = Drosselung anzeigen
+show-hot-core = Heißesten Kern im Panel nennen
+show-both-units = Beide Einheiten anzeigen
+show-icon = Symbol anzeigen
+threshold-indication = Schwellenwerte anzeigen durch
+indication-color = Textfarbe
+indication-icon = Symbol
+indication-both = Beides
+panel-style = Panel zeigt
+panel-style-text = Temperatur
+panel-style-icon-only = Nur Symbol
+# Tooltip of the icon-only panel, "CPU: 63°C"
+panel-tooltip = { $sensor }: { $temp }
+
+sensor = Sensor
+scroll-to-switch = Über dem Panel-Element scrollen, um den Sensor zu wechseln
+ignore-sensor = Ignorieren
+ignored-sensors = Ignorierte Sensoren
+unignore-sensor = Wieder verwenden
+sensor-backend = Sensoren lesen über
+automatic = Automatisch
+sensor-error = Sensorfehler
+sensor-access-denied = Sensoren können nicht gelesen werden: { $error } bei { $path }
+no-sensors-in-vm = Keine Temperatursensoren verfügbar (virtuelle Maschine erkannt)
+config-write-failed = Einstellungen nicht gespeichert ({ $count } Versuche)
+config-unavailable = Konfigurationsverzeichnis nicht verfügbar
+cpu-temperature = CPU-Temperatur
+source = Quelle: { $source }
+source-component = { $label }
+source-component-chip = { $label } ({ $chip })
+cpu-alternative = Ebenfalls auf diesem Chip: { $sensor }
+selected-sensor-missing = { $sensor } ist nicht mehr vorhanden, automatische Erkennung wird verwendet, bis er zurück ist
+source-cores = { $aggregation } von { $count } Kernsensoren
+source-thermal-zone = Thermal Zones des Kernels
+no-cpu-sensor = Kein CPU-Temperatursensor gefunden, prüfe, ob `sensors` aus lm-sensors einen anzeigt
+
+read-error = Kein Wert von { $source }
+no-reading-for = Seit { $seconds } s kein Messwert
+no-reading-for-sensor = Seit { $seconds } s kein Messwert — letzter Sensor: { $sensor }
+
+combine-cores = Kerne zusammenfassen
+maximum = Maximum
+average = Durchschnitt
+median = Median
+prefer-tctl = Tctl statt Tdie bevorzugen
+hottest-ccd = Heißesten CCD verwenden
+
+display = Anzeige
+display-cpu = CPU
+display-gpu = GPU
+display-both = CPU / GPU
+display-hottest = Heißester Sensor
+display-headroom = Abstand zur kritischen Grenze
+headroom-no-critical = Der Sensor meldet keine kritische Grenze und keine ist eingestellt, die Temperatur wird stattdessen angezeigt
+
+decimal-places = Nachkommastellen
+rounding-step = Runden auf
+rounding-off = Aus
+
+smoothing = Glättung
+smoothing-off = Aus
+smoothing-light = Leicht
+smoothing-medium = Mittel
+smoothing-strong = Stark
+smoothed-raw = Geglättet / Roh
+
+fixed-width = Feste Breite
+panel-format = Format im Panel
+panel-prefix = Panel-Präfix
+panel-suffix = Panel-Suffix
+panel-format-preview = Vorschau: { $text }
+
+cpu = CPU
+cpu-load = CPU-Auslastung
+fans = Lüfter
+fan-speed = { $rpm } U/min
+package-power = Package-Leistung
+throttling = Drosselung
+throttling-badge = GEDROSSELT
+throttling-active = Aktiv, { $count } Ereignisse in dieser Sitzung
+throttling-inactive = Nein, { $count } Ereignisse in dieser Sitzung
+both-units = Beide Einheiten
+sensor-max = max. { $temp }
+sensor-critical = krit. { $temp }
+gpu-sensor = GPU-Sensor
+hottest-sensor = Heißester Sensor
+not-found = Nicht gefunden
+show-drive-temps = Laufwerkstemperaturen anzeigen
+drives = Laufwerke
+all-sensors = Alle Sensoren
+diagnostics = Diagnose
+copy-diagnostics = Diagnose kopieren
+diagnostics-version = Version
+diagnostics-components = Gefundene Komponenten
+diagnostics-sensor = CPU-Sensor
+diagnostics-priority = Priorität { $priority }
+diagnostics-read-time = Dauer des letzten Lesens
+diagnostics-read-times = Lesevorgänge der letzten Minute
+diagnostics-tick-delay = Verzögerung der Takte der letzten Minute
+diagnostics-timing-value = { $avg } Ø / { $max } max.
+diagnostics-last-error = Letzter Fehler
+diagnostics-calibration = Kalibrierung
+diagnostics-calibration-value = { $raw } gelesen, { $adjusted } angezeigt
+diagnostics-none = Keiner
+cores = Kerne
+hottest-core = Am heißesten: { $core } ({ $temp })
+
+peak = Höchstwert: { $temp }
+reset = Zurücksetzen
+copy-readings = Messwerte kopieren
+open-settings = Alle Einstellungen …
+menu-toggle-unit = In { $unit } anzeigen
+menu-reset-peak = Höchstwert zurücksetzen
+menu-pause-updates = Aktualisierung anhalten
+menu-resume-updates = Aktualisierung fortsetzen
+copied = Kopiert
+stats-last-minutes = Letzte { $minutes } min
+stats-values = min. { $min } · Ø { $avg } · max. { $max }
+
+## Notifications
+
+critical-notification-summary = CPU-Temperatur kritisch
+critical-notification-body = CPU-Temperatur hat { $temp } erreicht
+quiet-hours-summary = CPU war während der Ruhezeit heiß
+quiet-hours-summary-body = CPU-Temperatur erreichte { $temp }, während Warnungen stumm waren
+
+## Settings window
+
+settings-title = CPU-Temperatur-Einstellungen
+settings-panel = Leiste
+settings-sensors = Sensoren
+settings-refreshing = Aktualisierung
+settings-alerts = Warnungen
+settings-other = Sonstiges
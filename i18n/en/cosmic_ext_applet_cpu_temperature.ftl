@@ -0,0 +1,207 @@
+app-name = CPU Temperature
+
+## Popup
+
+unit = Unit
+celsius = Celsius
+fahrenheit = Fahrenheit
+kelvin = Kelvin
+
+refresh-interval = Refresh Interval (ms)
+refresh-interval-too-short = Raised to the minimum of { $min } ms
+refresh-interval-too-long = Limited to the maximum of { $max } ms
+refresh-interval-invalid = Must be a whole number of milliseconds
+refresh-now = Refresh Now
+pin-popup = Keep Open
+unpin-popup = Close
+display-interval = Display Interval (ms)
+display-interval-too-short = Can't be shorter than the refresh interval, not saved
+slower-refresh-on-battery = Slower Refresh on Battery
+battery-refresh-multiplier = Battery Refresh Multiplier
+
+warning-threshold = Warning Threshold ({ $unit })
+critical-threshold = Critical Threshold ({ $unit })
+thresholds-out-of-order = The warning threshold has to be below the critical one, not saved
+sensor-thresholds = Thresholds for { $sensor }
+hide-below = Hide Text Below ({ $unit })
+temperature-offset = Calibration offset (°C)
+notify-critical = Notify on Critical Temperature
+flash-on-critical = Flash Panel on Critical Temperature
+disable-animations = Disable Animations
+alert-sound = Sound on Critical Temperature
+test-sound = Test
+sound-failed = Sound failed: { $error }
+sound-not-found = No sound named { $sound }
+quiet-hours = Quiet Hours
+alerts-muted-until = Alerts muted until { $time }
+persist-history = Keep History Across Restarts
+graph-range = History Graph Range
+graph-range-5m = 5 min
+graph-range-30m = 30 min
+graph-range-2h = 2 h
+graph-fixed-scale = Fixed Graph Scale
+graph-min = Graph Minimum ({ $unit })
+graph-max = Graph Maximum ({ $unit })
+log-to-file = Log Readings to File
+log-file = Log File
+log-max-size = Log Size Limit (MB)
+log-failed = Logging stopped
+critical-action = Command on Critical Temperature
+critical-action-delay = Run After (s)
+critical-action-failed = Command failed: { $error }
+metrics-enabled = Prometheus Metrics on 127.0.0.1
+metrics-port = Metrics Port
+mqtt-enabled = Publish over MQTT
+mqtt-target = Publishing to { $topic } on { $broker }
+mqtt-failed = MQTT publishing stopped
+mqtt-broker = MQTT Broker
+mqtt-topic = MQTT Topic
+mqtt-credentials-file = MQTT Credentials File
+mqtt-interval-multiplier = Publish Every Nth Sample
+socket-enabled = Serve Sensors on a Unix Socket
+max-plausible = Ignore Readings Above ({ $unit })
+monitor-command = Middle-Click Command
+monitor-launch-failed = Couldn't start the system monitor: { $error }
+no-system-monitor = none of cosmic-monitor or gnome-system-monitor is installed
+
+show-load = Show CPU Load in Panel
+load-without-sensors = Show CPU Load Without Sensors
+show-fan = Show Fan Speed in Panel
+show-power = Read Package Power
+power-in-panel = Show Power in Panel
+show-throttling = Show Throttling
+show-hot-core = Name the hottest core in the panel
+show-both-units = Show Both Units
+show-icon = Show Icon
+threshold-indication = Show Thresholds With
+indication-color = Text Color
+indication-icon = Icon
+indication-both = Both
+panel-style = Panel Shows
+panel-style-text = Temperature
+panel-style-icon-only = Icon Only
+# Tooltip of the icon-only panel, "CPU: 63°C"
+panel-tooltip = { $sensor }: { $temp }
+
+sensor = Sensor
+scroll-to-switch = Scroll over the panel item to switch sensors
+ignore-sensor = Ignore
+ignored-sensors = Ignored Sensors
+unignore-sensor = Use Again
+sensor-backend = Read Sensors With
+automatic = Automatic
+sensor-error = Sensor Error
+sensor-access-denied = Cannot read sensors: { $error } on { $path }
+no-sensors-in-vm = No temperature sensors available (virtual machine detected)
+config-write-failed = Settings Not Saved ({ $count } tries)
+config-unavailable = Config directory unavailable
+cpu-temperature = CPU Temperature
+source = Source: { $source }
+source-component = { $label }
+source-component-chip = { $label } ({ $chip })
+cpu-alternative = Also on this package: { $sensor }
+selected-sensor-missing = { $sensor } is gone, using automatic detection until it is back
+source-cores = { $aggregation } of { $count } core sensors
+source-thermal-zone = kernel thermal zones
+no-cpu-sensor = No CPU temperature sensor detected, check that `sensors` from lm-sensors lists one
+
+# $source is a list of sensor labels or a path
+read-error = No value from { $source }
+no-reading-for = No reading for { $seconds } s
+no-reading-for-sensor = No reading for { $seconds } s — last sensor: { $sensor }
+
+combine-cores = Combine Cores
+maximum = Maximum
+average = Average
+median = Median
+prefer-tctl = Prefer Tctl over Tdie
+hottest-ccd = Use Hottest CCD
+
+display = Display
+display-cpu = CPU
+display-gpu = GPU
+display-both = CPU / GPU
+display-hottest = Hottest Sensor
+display-headroom = Headroom to Critical
+headroom-no-critical = The sensor reports no critical limit and none is set, showing the temperature instead
+
+decimal-places = Decimal Places
+rounding-step = Round To
+rounding-off = Off
+
+smoothing = Smoothing
+smoothing-off = Off
+smoothing-light = Light
+smoothing-medium = Medium
+smoothing-strong = Strong
+smoothed-raw = Smoothed / Raw
+
+fixed-width = Fixed Width
+panel-format = Panel Format
+panel-prefix = Panel Prefix
+panel-suffix = Panel Suffix
+panel-format-preview = Preview: { $text }
+
+cpu = CPU
+cpu-load = CPU Load
+fans = Fans
+fan-speed = { $rpm } rpm
+package-power = Package Power
+throttling = Throttling
+throttling-badge = THROTTLING
+throttling-active = Active, { $count } events this session
+throttling-inactive = No, { $count } events this session
+both-units = Both Units
+# Driver limits shown after the current temperature
+sensor-max = max { $temp }
+sensor-critical = crit { $temp }
+gpu-sensor = GPU Sensor
+hottest-sensor = Hottest Sensor
+not-found = Not found
+show-drive-temps = Show Drive Temperatures
+drives = Drives
+all-sensors = All Sensors
+diagnostics = Diagnostics
+copy-diagnostics = Copy Diagnostics
+diagnostics-version = Version
+diagnostics-components = Components Found
+diagnostics-sensor = CPU Sensor
+diagnostics-priority = priority { $priority }
+diagnostics-read-time = Last Read Took
+diagnostics-read-times = Reads in the Last Minute
+diagnostics-tick-delay = Tick Delay in the Last Minute
+diagnostics-timing-value = { $avg } avg / { $max } max
+diagnostics-last-error = Last Error
+diagnostics-calibration = Calibration
+diagnostics-calibration-value = { $raw } read, { $adjusted } shown
+diagnostics-none = None
+cores = Cores
+hottest-core = Hottest: { $core } ({ $temp })
+
+peak = Peak: { $temp }
+reset = Reset
+copy-readings = Copy Readings
+open-settings = All Settings…
+menu-toggle-unit = Show in { $unit }
+menu-reset-peak = Reset peak
+menu-pause-updates = Pause updates
+menu-resume-updates = Resume updates
+copied = Copied
+stats-last-minutes = Last { $minutes } min
+stats-values = min { $min } · avg { $avg } · max { $max }
+
+## Notifications
+
+critical-notification-summary = CPU temperature critical
+critical-notification-body = CPU temperature reached { $temp }
+quiet-hours-summary = CPU got hot during quiet hours
+quiet-hours-summary-body = CPU temperature reached { $temp } while alerts were muted
+
+## Settings window
+
+settings-title = CPU Temperature Settings
+settings-panel = Panel
+settings-sensors = Sensors
+settings-refreshing = Refreshing
+settings-alerts = Alerts
+settings-other = Other
@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant};
+
+// Degrees (Celsius) the temperature has to drop below the threshold before the
+// alert re-arms, so a CPU hovering around the threshold doesn't alert every tick
+pub(crate) const ALERT_HYSTERESIS_CELSIUS: f32 = 5.0;
+// Readings in a row at or above the threshold before the panel starts flashing, a single
+// spike shouldn't do it
+const FLASH_AFTER_READINGS: u32 = 3;
+
+#[derive(Debug, Default)]
+pub(crate) struct ThresholdAlert {
+    triggered: bool,
+}
+
+impl ThresholdAlert {
+    // Returns true only on the update where the threshold is crossed upwards
+    pub(crate) fn update(&mut self, celsius: f32, threshold_celsius: f32) -> bool {
+        if self.triggered {
+            if celsius < threshold_celsius - ALERT_HYSTERESIS_CELSIUS {
+                self.triggered = false;
+            }
+
+            return false;
+        }
+
+        if celsius >= threshold_celsius {
+            self.triggered = true;
+            return true;
+        }
+
+        false
+    }
+}
+
+// Like `ThresholdAlert`, but only fires once the temperature stayed at or above the
+// threshold for the whole delay
+#[derive(Debug, Default)]
+pub(crate) struct SustainedAlert {
+    // Start of the current stretch at or above the threshold
+    above_since: Option<Instant>,
+    fired: bool,
+}
+
+impl SustainedAlert {
+    // Returns true only once per stretch, until the temperature dropped below the
+    // threshold by the hysteresis
+    pub(crate) fn update(
+        &mut self,
+        celsius: f32,
+        threshold_celsius: f32,
+        delay: Duration,
+        now: Instant,
+    ) -> bool {
+        if celsius < threshold_celsius {
+            self.above_since = None;
+            if celsius < threshold_celsius - ALERT_HYSTERESIS_CELSIUS {
+                self.fired = false;
+            }
+
+            return false;
+        }
+
+        let above_since = *self.above_since.get_or_insert(now);
+        if self.fired || now.duration_since(above_since) < delay {
+            return false;
+        }
+
+        self.fired = true;
+        true
+    }
+}
+
+// Attention state of the panel button. Every reading is one blink step, so it flashes at the
+// refresh rate without a timer of its own. Lasts until the temperature dropped below the
+// threshold by the hysteresis.
+#[derive(Debug, Default)]
+pub(crate) struct FlashAlert {
+    readings_above: u32,
+    flashing: bool,
+    // Which of the two looks is shown, flipped on every reading while flashing
+    highlighted: bool,
+}
+
+impl FlashAlert {
+    pub(crate) fn update(&mut self, celsius: f32, threshold_celsius: f32) {
+        if celsius >= threshold_celsius {
+            self.readings_above = self.readings_above.saturating_add(1);
+        } else {
+            self.readings_above = 0;
+        }
+
+        if self.flashing && celsius < threshold_celsius - ALERT_HYSTERESIS_CELSIUS {
+            self.flashing = false;
+        } else if self.readings_above >= FLASH_AFTER_READINGS {
+            self.flashing = true;
+        }
+        self.highlighted = self.flashing && !self.highlighted;
+    }
+
+    // Without animations the highlight stays on for as long as it would flash
+    pub(crate) fn highlighted(&self, animate: bool) -> bool {
+        if animate {
+            self.highlighted
+        } else {
+            self.flashing
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn looks(alert: &mut FlashAlert, readings: &[f32], animate: bool) -> Vec<bool> {
+        readings
+            .iter()
+            .map(|&celsius| {
+                alert.update(celsius, 90.0);
+                alert.highlighted(animate)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sustained_alert_waits_for_the_delay() {
+        let mut alert = SustainedAlert::default();
+        let start = Instant::now();
+        let delay = Duration::from_secs(30);
+        let at = |seconds| start + Duration::from_secs(seconds);
+
+        assert!(!alert.update(96.0, 95.0, delay, at(0)));
+        assert!(!alert.update(97.0, 95.0, delay, at(29)));
+        assert!(alert.update(96.0, 95.0, delay, at(30)));
+        // Once per stretch
+        assert!(!alert.update(98.0, 95.0, delay, at(60)));
+    }
+
+    #[test]
+    fn sustained_alert_starts_over_below_the_threshold() {
+        let mut alert = SustainedAlert::default();
+        let start = Instant::now();
+        let delay = Duration::from_secs(30);
+        let at = |seconds| start + Duration::from_secs(seconds);
+
+        assert!(!alert.update(96.0, 95.0, delay, at(0)));
+        // A dip restarts the delay
+        assert!(!alert.update(94.0, 95.0, delay, at(20)));
+        assert!(!alert.update(96.0, 95.0, delay, at(25)));
+        assert!(!alert.update(96.0, 95.0, delay, at(50)));
+        assert!(alert.update(96.0, 95.0, delay, at(55)));
+
+        // Not re-armed within the hysteresis
+        assert!(!alert.update(92.0, 95.0, delay, at(60)));
+        assert!(!alert.update(96.0, 95.0, delay, at(100)));
+        // Re-armed below it
+        assert!(!alert.update(85.0, 95.0, delay, at(110)));
+        assert!(!alert.update(96.0, 95.0, delay, at(120)));
+        assert!(alert.update(96.0, 95.0, delay, at(150)));
+    }
+
+    #[test]
+    fn flashes_after_sustained_readings() {
+        let mut alert = FlashAlert::default();
+        assert_eq!(
+            looks(
+                &mut alert,
+                &[91.0, 92.0, 93.0, 93.0, 93.0, 93.0, 80.0],
+                true
+            ),
+            [false, false, true, false, true, false, false]
+        );
+    }
+
+    #[test]
+    fn steady_highlight_without_animations() {
+        let mut alert = FlashAlert::default();
+        assert_eq!(
+            looks(
+                &mut alert,
+                &[91.0, 92.0, 93.0, 93.0, 93.0, 88.0, 84.0],
+                false
+            ),
+            [false, false, true, true, true, true, false]
+        );
+    }
+}
@@ -0,0 +1,818 @@
+// The state behind the popup's inputs and the shown temperature, apart from the iced
+// plumbing in `Window` so the transitions can be tested. Methods that change the config
+// return `true` when it has to be saved, `Window` owns the writing.
+
+use crate::config::{CPUTempAppletConfig, SensorThresholds, TemperatureUnit};
+use crate::locale;
+use crate::refresh_period::{parse_period, PeriodEdit, PeriodInput};
+use crate::sysinfo_utils::TempReading;
+use crate::units;
+
+// Used when neither the config nor the sensor has a limit
+const DEFAULT_WARNING_CELSIUS: f32 = 75.0;
+const DEFAULT_CRITICAL_CELSIUS: f32 = 90.0;
+// How far below the sensor's critical limit the default warning threshold is
+const WARNING_MARGIN_CELSIUS: f32 = 15.0;
+
+// The two period inputs in the popup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeriodField {
+    Sample,
+    Display,
+}
+
+// The temperature inputs in the popup, typed in the display unit except for the offsets
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ThresholdField {
+    Warning,
+    Critical,
+    // Overrides for the sensor that gives the CPU value
+    SensorWarning,
+    SensorCritical,
+    HideBelow,
+    // Calibration, always in Celsius
+    Offset,
+    SensorOffset,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct AppletState {
+    // Last good CPU temperature, kept through read errors
+    pub(crate) temp: Option<f32>,
+    // Moving average of `temp` for the panel, equal to it without smoothing
+    pub(crate) smoothed_temp: Option<f32>,
+    // Set on resume, the next reading starts the average over
+    pub(crate) restart_smoothing: bool,
+    // Highest CPU temperature (Celsius) since start or the last reset
+    pub(crate) peak: Option<f32>,
+    // Limits reported by the CPU sensor's driver
+    pub(crate) sensor_max: Option<f32>,
+    pub(crate) sensor_critical: Option<f32>,
+    pub(crate) period_edit: PeriodEdit,
+    pub(crate) display_period_edit: PeriodEdit,
+    // Shown under the period inputs, `Valid` never ends up here
+    pub(crate) period_error: Option<PeriodInput>,
+    pub(crate) display_period_error: Option<PeriodInput>,
+    // Shown in the display unit, the config stores Celsius
+    pub(crate) warning_string: String,
+    pub(crate) critical_string: String,
+    pub(crate) sensor_warning_string: String,
+    pub(crate) sensor_critical_string: String,
+    pub(crate) hide_below_string: String,
+    pub(crate) offset_string: String,
+    pub(crate) sensor_offset_string: String,
+}
+
+// Exponential moving average, `factor` is the weight of the new value
+pub(crate) fn smooth(previous: Option<f32>, value: f32, factor: f32) -> f32 {
+    match previous {
+        Some(previous) => previous + factor.clamp(0.0, 1.0) * (value - previous),
+        None => value,
+    }
+}
+
+// Empty means not overridden, the placeholder shows the one in use then
+pub(crate) fn format_threshold(celsius: Option<f32>, unit: TemperatureUnit) -> String {
+    celsius
+        .and_then(|celsius| units::format_number(celsius, unit, 0))
+        .unwrap_or_default()
+}
+
+// Empty without an offset, it's in Celsius in every unit
+pub(crate) fn format_offset(offset_celsius: Option<f32>) -> String {
+    offset_celsius
+        .filter(|offset| *offset != 0.0)
+        .and_then(|offset| units::format_number(offset, TemperatureUnit::Celsius, 1))
+        .unwrap_or_default()
+}
+
+// `Ok(None)` for an empty input, which goes back to the default
+fn parse_threshold(input: &str) -> Result<Option<f32>, ()> {
+    match input.trim() {
+        "" => Ok(None),
+        value => locale::parse_decimal(value).map(Some).ok_or(()),
+    }
+}
+
+// A temperature typed in the display unit, in Celsius
+pub(crate) fn parse_celsius(input: &str, unit: TemperatureUnit) -> Result<Option<f32>, ()> {
+    parse_threshold(input).map(|value| value.map(|value| units::from_unit(value, unit)))
+}
+
+// Warning and critical threshold as typed, saved together so both can be raised in either
+// order. `None` while the warning isn't below the critical threshold. An input that doesn't
+// parse, or still shows the saved value rounded, keeps the saved value.
+pub(crate) fn threshold_pair(
+    warning_input: &str,
+    critical_input: &str,
+    unit: TemperatureUnit,
+    saved: (Option<f32>, Option<f32>),
+) -> Option<(Option<f32>, Option<f32>)> {
+    let typed = |input: &str, saved: Option<f32>| {
+        if input == format_threshold(saved, unit) {
+            saved
+        } else {
+            parse_celsius(input, unit).unwrap_or(saved)
+        }
+    };
+    match (
+        typed(warning_input, saved.0),
+        typed(critical_input, saved.1),
+    ) {
+        (Some(warning), Some(critical)) if warning >= critical => None,
+        pair => Some(pair),
+    }
+}
+
+// A display period typed shorter than the sample period isn't saved
+pub(crate) fn is_display_period_too_short(input: &str, config: &CPUTempAppletConfig) -> bool {
+    parse_period(input)
+        .period()
+        .is_some_and(|period| period < config.sample_period_ms)
+}
+
+impl AppletState {
+    pub(crate) fn new(config: &CPUTempAppletConfig) -> Self {
+        let mut state = Self {
+            period_edit: PeriodEdit::new(config.sample_period_ms),
+            display_period_edit: PeriodEdit::new(config.display_period_ms),
+            ..Default::default()
+        };
+        state.update_threshold_strings(config, None);
+        state
+    }
+
+    // Takes the CPU value of a reading, `display` is `false` for samples the display period
+    // skips. Returns the temperature if the reading had one.
+    pub(crate) fn apply_reading(
+        &mut self,
+        reading: &TempReading,
+        display: bool,
+        smoothing: Option<f32>,
+    ) -> Option<f32> {
+        match reading {
+            TempReading::Value(temp) => {
+                if display {
+                    self.temp = Some(temp.celsius);
+                    // The average from before a suspend is too old to build on
+                    let previous = if std::mem::take(&mut self.restart_smoothing) {
+                        None
+                    } else {
+                        self.smoothed_temp
+                    };
+                    self.smoothed_temp = Some(match smoothing {
+                        Some(factor) => smooth(previous, temp.celsius, factor),
+                        None => temp.celsius,
+                    });
+                }
+                self.sensor_max = temp.max;
+                self.sensor_critical = temp.critical;
+                self.peak = Some(
+                    self.peak
+                        .map_or(temp.celsius, |peak| peak.max(temp.celsius)),
+                );
+                Some(temp.celsius)
+            }
+            TempReading::NoSensors => {
+                self.temp = None;
+                self.smoothed_temp = None;
+                self.sensor_max = None;
+                self.sensor_critical = None;
+                None
+            }
+            // Keep showing the last good value, the EC on some laptops times out now and then
+            TempReading::ReadError(_) => None,
+        }
+    }
+
+    pub(crate) fn reset_peak(&mut self) {
+        self.peak = self.temp;
+    }
+
+    pub(crate) fn set_unit(
+        &mut self,
+        config: &mut CPUTempAppletConfig,
+        unit: TemperatureUnit,
+        cpu_label: Option<&str>,
+    ) -> bool {
+        if config.unit == unit {
+            return false;
+        }
+
+        config.unit = unit;
+        self.update_threshold_strings(config, cpu_label);
+        true
+    }
+
+    fn sensor_thresholds(
+        config: &CPUTempAppletConfig,
+        cpu_label: Option<&str>,
+    ) -> SensorThresholds {
+        cpu_label
+            .and_then(|label| config.sensor_thresholds.get(label))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    // The config overrides the sensor's own limits
+    pub(crate) fn default_critical_threshold(&self, config: &CPUTempAppletConfig) -> f32 {
+        config
+            .critical_threshold_celsius
+            .or(self.sensor_critical)
+            .unwrap_or(DEFAULT_CRITICAL_CELSIUS)
+    }
+
+    // sysinfo raises a sensor's max whenever it's exceeded, so it can't be used as the
+    // warning threshold. The critical limit is fixed by the driver.
+    pub(crate) fn default_warning_threshold(&self, config: &CPUTempAppletConfig) -> f32 {
+        config.warning_threshold_celsius.unwrap_or_else(|| {
+            self.sensor_critical
+                .map_or(DEFAULT_WARNING_CELSIUS, |critical| {
+                    critical - WARNING_MARGIN_CELSIUS
+                })
+        })
+    }
+
+    // A sensor's own entry wins over the global thresholds
+    pub(crate) fn critical_threshold(
+        &self,
+        config: &CPUTempAppletConfig,
+        label: Option<&str>,
+    ) -> f32 {
+        Self::sensor_thresholds(config, label)
+            .critical_celsius
+            .unwrap_or_else(|| self.default_critical_threshold(config))
+    }
+
+    pub(crate) fn warning_threshold(
+        &self,
+        config: &CPUTempAppletConfig,
+        label: Option<&str>,
+    ) -> f32 {
+        Self::sensor_thresholds(config, label)
+            .warning_celsius
+            .unwrap_or_else(|| self.default_warning_threshold(config))
+    }
+
+    // What the headroom is measured against. Unlike the alerts the sensor's own limit comes
+    // first, it's what the hardware throttles at.
+    pub(crate) fn headroom_critical(
+        &self,
+        config: &CPUTempAppletConfig,
+        cpu_label: Option<&str>,
+    ) -> Option<f32> {
+        Self::sensor_thresholds(config, cpu_label)
+            .critical_celsius
+            .or(self.sensor_critical)
+            .or(config.critical_threshold_celsius)
+    }
+
+    pub(crate) fn update_threshold_strings(
+        &mut self,
+        config: &CPUTempAppletConfig,
+        cpu_label: Option<&str>,
+    ) {
+        self.warning_string = format_threshold(config.warning_threshold_celsius, config.unit);
+        self.critical_string = format_threshold(config.critical_threshold_celsius, config.unit);
+        self.hide_below_string = format_threshold(config.hide_below_celsius, config.unit);
+        self.offset_string = format_offset(Some(config.offset_celsius));
+        self.update_sensor_threshold_strings(config, cpu_label);
+    }
+
+    // Also when another sensor gives the CPU value
+    pub(crate) fn update_sensor_threshold_strings(
+        &mut self,
+        config: &CPUTempAppletConfig,
+        cpu_label: Option<&str>,
+    ) {
+        let thresholds = Self::sensor_thresholds(config, cpu_label);
+        self.sensor_warning_string = format_threshold(thresholds.warning_celsius, config.unit);
+        self.sensor_critical_string = format_threshold(thresholds.critical_celsius, config.unit);
+        self.sensor_offset_string =
+            format_offset(cpu_label.and_then(|label| config.sensor_offsets.get(label).copied()));
+    }
+
+    // The text is kept as typed, invalid input only doesn't get saved
+    pub(crate) fn threshold_input(
+        &mut self,
+        config: &mut CPUTempAppletConfig,
+        field: ThresholdField,
+        input: String,
+        cpu_label: Option<&str>,
+    ) -> bool {
+        let value = parse_threshold(&input);
+        let threshold = parse_celsius(&input, config.unit);
+        *self.threshold_string(field) = input;
+        let saved = match field {
+            ThresholdField::Warning | ThresholdField::Critical => threshold.and_then(|_| {
+                let saved = (
+                    config.warning_threshold_celsius,
+                    config.critical_threshold_celsius,
+                );
+                let (warning, critical) = threshold_pair(
+                    &self.warning_string,
+                    &self.critical_string,
+                    config.unit,
+                    saved,
+                )
+                .ok_or(())?;
+                config.warning_threshold_celsius = warning;
+                config.critical_threshold_celsius = critical;
+                Ok(())
+            }),
+            ThresholdField::HideBelow => {
+                threshold.map(|celsius| config.hide_below_celsius = celsius)
+            }
+            ThresholdField::SensorWarning | ThresholdField::SensorCritical => {
+                threshold.and_then(|_| {
+                    let sensor = Self::sensor_thresholds(config, cpu_label);
+                    let (warning, critical) = threshold_pair(
+                        &self.sensor_warning_string,
+                        &self.sensor_critical_string,
+                        config.unit,
+                        (sensor.warning_celsius, sensor.critical_celsius),
+                    )
+                    .ok_or(())?;
+                    set_sensor_thresholds(config, cpu_label, |thresholds| {
+                        thresholds.warning_celsius = warning;
+                        thresholds.critical_celsius = critical;
+                    })
+                })
+            }
+            ThresholdField::Offset => {
+                value.map(|offset| config.offset_celsius = offset.unwrap_or_default())
+            }
+            // Empty goes back to the global offset
+            ThresholdField::SensorOffset => value.and_then(|offset| {
+                let label = cpu_label.ok_or(())?;
+                match offset {
+                    Some(offset) => config.sensor_offsets.insert(label.to_string(), offset),
+                    None => config.sensor_offsets.remove(label),
+                };
+                Ok(())
+            }),
+        };
+        saved.is_ok()
+    }
+
+    fn threshold_string(&mut self, field: ThresholdField) -> &mut String {
+        match field {
+            ThresholdField::Warning => &mut self.warning_string,
+            ThresholdField::Critical => &mut self.critical_string,
+            ThresholdField::SensorWarning => &mut self.sensor_warning_string,
+            ThresholdField::SensorCritical => &mut self.sensor_critical_string,
+            ThresholdField::HideBelow => &mut self.hide_below_string,
+            ThresholdField::Offset => &mut self.offset_string,
+            ThresholdField::SensorOffset => &mut self.sensor_offset_string,
+        }
+    }
+
+    fn period_edit(&mut self, field: PeriodField) -> &mut PeriodEdit {
+        match field {
+            PeriodField::Sample => &mut self.period_edit,
+            PeriodField::Display => &mut self.display_period_edit,
+        }
+    }
+
+    // Returns the generation to commit once the input stayed unchanged for a while
+    pub(crate) fn period_input(&mut self, field: PeriodField, input: String) -> u64 {
+        let parsed = parse_period(&input);
+        let error = (!matches!(parsed, PeriodInput::Valid(_))).then_some(parsed);
+        match field {
+            PeriodField::Sample => self.period_error = error,
+            PeriodField::Display => self.display_period_error = error,
+        }
+        self.period_edit(field).edit(input)
+    }
+
+    // Enter in the input, saves right away
+    pub(crate) fn submit_period(
+        &mut self,
+        config: &mut CPUTempAppletConfig,
+        field: PeriodField,
+    ) -> bool {
+        // Invalidates the pending debounced commit
+        self.period_edit(field).invalidate();
+        self.commit_period(config, field)
+    }
+
+    // The debounced commit, ignored if the input changed since it was scheduled
+    pub(crate) fn commit_period_if_current(
+        &mut self,
+        config: &mut CPUTempAppletConfig,
+        field: PeriodField,
+        generation: u64,
+    ) -> bool {
+        self.period_edit(field).is_current(generation) && self.commit_period(config, field)
+    }
+
+    pub(crate) fn display_period_too_short(&self, config: &CPUTempAppletConfig) -> bool {
+        is_display_period_too_short(self.display_period_edit.text(), config)
+    }
+
+    // Neither threshold of a pair is saved then
+    pub(crate) fn thresholds_out_of_order(
+        &self,
+        config: &CPUTempAppletConfig,
+        cpu_label: Option<&str>,
+    ) -> bool {
+        let sensor = Self::sensor_thresholds(config, cpu_label);
+        let global = (
+            config.warning_threshold_celsius,
+            config.critical_threshold_celsius,
+        );
+        threshold_pair(
+            &self.warning_string,
+            &self.critical_string,
+            config.unit,
+            global,
+        )
+        .is_none()
+            || threshold_pair(
+                &self.sensor_warning_string,
+                &self.sensor_critical_string,
+                config.unit,
+                (sensor.warning_celsius, sensor.critical_celsius),
+            )
+            .is_none()
+    }
+
+    fn commit_period(&mut self, config: &mut CPUTempAppletConfig, field: PeriodField) -> bool {
+        if field == PeriodField::Display && self.display_period_too_short(config) {
+            return false;
+        }
+
+        let saved = match field {
+            PeriodField::Sample => config.sample_period_ms,
+            PeriodField::Display => config.display_period_ms,
+        };
+        let Some(period) = self.period_edit(field).commit(saved) else {
+            return false;
+        };
+
+        match field {
+            PeriodField::Sample => config.sample_period_ms = period,
+            PeriodField::Display => config.display_period_ms = period,
+        }
+        true
+    }
+
+    // Called with every config reload before it replaces `current`. The period inputs skip
+    // reloads of our own saves and anything arriving while they're edited, these can come
+    // after newer input and would revert it. The rest is only touched if `applied`, a change
+    // that's still waiting to be written is newer than the reload.
+    pub(crate) fn config_changed(
+        &mut self,
+        current: &CPUTempAppletConfig,
+        new: &CPUTempAppletConfig,
+        applied: bool,
+        cpu_label: Option<&str>,
+    ) {
+        if self.period_edit.config_changed(new.sample_period_ms) {
+            self.period_error = None;
+        }
+        if self
+            .display_period_edit
+            .config_changed(new.display_period_ms)
+        {
+            self.display_period_error = None;
+        }
+        if !applied {
+            return;
+        }
+
+        // Only reformat when needed so we don't overwrite what the user is typing
+        let thresholds_changed = current.unit != new.unit
+            || current.warning_threshold_celsius != new.warning_threshold_celsius
+            || current.critical_threshold_celsius != new.critical_threshold_celsius
+            || current.sensor_thresholds != new.sensor_thresholds
+            || current.hide_below_celsius != new.hide_below_celsius
+            || current.offset_celsius != new.offset_celsius
+            || current.sensor_offsets != new.sensor_offsets;
+        if thresholds_changed {
+            self.update_threshold_strings(new, cpu_label);
+        }
+    }
+}
+
+// Empty fields remove the entry, so the config only lists sensors that differ. Fails when
+// the CPU value isn't from a single component.
+fn set_sensor_thresholds(
+    config: &mut CPUTempAppletConfig,
+    cpu_label: Option<&str>,
+    update: impl FnOnce(&mut SensorThresholds),
+) -> Result<(), ()> {
+    let label = cpu_label.ok_or(())?;
+    let mut thresholds = AppletState::sensor_thresholds(config, Some(label));
+    update(&mut thresholds);
+    if thresholds.is_empty() {
+        config.sensor_thresholds.remove(label);
+    } else {
+        config
+            .sensor_thresholds
+            .insert(label.to_string(), thresholds);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::refresh_period::MIN_REFRESH_PERIOD_MS;
+    use crate::sysinfo_utils::SensorTemp;
+
+    fn value(celsius: f32) -> TempReading {
+        TempReading::Value(SensorTemp {
+            celsius,
+            max: None,
+            critical: Some(100.0),
+        })
+    }
+
+    #[test]
+    fn readings_update_the_shown_temperature() {
+        let mut state = AppletState::new(&CPUTempAppletConfig::default());
+
+        assert_eq!(
+            state.apply_reading(&TempReading::NoSensors, true, None),
+            None
+        );
+        assert_eq!(state.temp, None);
+
+        assert_eq!(
+            state.apply_reading(&value(60.0), true, Some(0.5)),
+            Some(60.0)
+        );
+        assert_eq!(
+            state.apply_reading(&value(80.0), true, Some(0.5)),
+            Some(80.0)
+        );
+        assert_eq!(state.temp, Some(80.0));
+        assert_eq!(state.smoothed_temp, Some(70.0));
+        // Skipped by the display period, still counts for the peak
+        assert_eq!(state.apply_reading(&value(90.0), false, None), Some(90.0));
+        assert_eq!(state.temp, Some(80.0));
+        assert_eq!(state.peak, Some(90.0));
+
+        // A read error keeps the last value
+        let error = TempReading::ReadError("Tctl".to_string());
+        assert_eq!(state.apply_reading(&error, true, None), None);
+        assert_eq!(state.temp, Some(80.0));
+        assert_eq!(state.sensor_critical, Some(100.0));
+
+        state.reset_peak();
+        assert_eq!(state.peak, Some(80.0));
+        state.restart_smoothing = true;
+        state.apply_reading(&value(50.0), true, Some(0.5));
+        assert_eq!(state.smoothed_temp, Some(50.0));
+
+        assert_eq!(
+            state.apply_reading(&TempReading::NoSensors, true, None),
+            None
+        );
+        assert_eq!((state.temp, state.smoothed_temp), (None, None));
+        assert_eq!(state.sensor_critical, None);
+    }
+
+    #[test]
+    fn thresholds_fall_back_to_the_sensor_limit() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+        assert_eq!(
+            state.critical_threshold(&config, None),
+            DEFAULT_CRITICAL_CELSIUS
+        );
+        assert_eq!(
+            state.warning_threshold(&config, None),
+            DEFAULT_WARNING_CELSIUS
+        );
+
+        state.apply_reading(&value(50.0), true, None);
+        assert_eq!(state.critical_threshold(&config, None), 100.0);
+        assert_eq!(state.warning_threshold(&config, None), 85.0);
+
+        assert!(state.threshold_input(&mut config, ThresholdField::Critical, "95".into(), None));
+        assert_eq!(state.critical_threshold(&config, None), 95.0);
+        assert_eq!(state.warning_threshold(&config, None), 85.0);
+
+        // Sensor overrides need a single CPU sensor
+        let input = "80".to_string();
+        assert!(!state.threshold_input(&mut config, ThresholdField::SensorWarning, input, None));
+        assert!(config.sensor_thresholds.is_empty());
+        let input = "80".to_string();
+        let label = Some("Tctl");
+        assert!(state.threshold_input(&mut config, ThresholdField::SensorWarning, input, label));
+        assert_eq!(state.warning_threshold(&config, label), 80.0);
+        assert_eq!(state.warning_threshold(&config, None), 85.0);
+        // Emptied again, the entry goes away
+        let input = String::new();
+        assert!(state.threshold_input(&mut config, ThresholdField::SensorWarning, input, label));
+        assert!(config.sensor_thresholds.is_empty());
+    }
+
+    #[test]
+    fn invalid_threshold_input_is_kept_but_not_saved() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+
+        assert!(state.threshold_input(&mut config, ThresholdField::Warning, "70".into(), None));
+        assert!(!state.threshold_input(&mut config, ThresholdField::Warning, "7x".into(), None));
+        assert_eq!(state.warning_string, "7x");
+        assert_eq!(config.warning_threshold_celsius, Some(70.0));
+
+        assert!(state.threshold_input(&mut config, ThresholdField::HideBelow, "".into(), None));
+        assert_eq!(config.hide_below_celsius, None);
+    }
+
+    #[test]
+    fn warning_has_to_stay_below_critical() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+        assert!(state.threshold_input(&mut config, ThresholdField::Warning, "70".into(), None));
+        assert!(state.threshold_input(&mut config, ThresholdField::Critical, "90".into(), None));
+
+        assert!(!state.threshold_input(&mut config, ThresholdField::Warning, "95".into(), None));
+        assert!(state.thresholds_out_of_order(&config, None));
+        assert_eq!(config.warning_threshold_celsius, Some(70.0));
+        // Raising the critical threshold saves the waiting warning along with it
+        assert!(state.threshold_input(&mut config, ThresholdField::Critical, "100".into(), None));
+        assert!(!state.thresholds_out_of_order(&config, None));
+        assert_eq!(config.warning_threshold_celsius, Some(95.0));
+        assert_eq!(config.critical_threshold_celsius, Some(100.0));
+
+        let label = Some("Tctl");
+        let input = "80".to_string();
+        assert!(state.threshold_input(&mut config, ThresholdField::SensorCritical, input, label));
+        let input = "80".to_string();
+        assert!(!state.threshold_input(&mut config, ThresholdField::SensorWarning, input, label));
+        assert_eq!(config.sensor_thresholds["Tctl"].warning_celsius, None);
+    }
+
+    #[test]
+    fn waiting_threshold_keeps_a_rounded_saved_value() {
+        let mut config = CPUTempAppletConfig {
+            critical_threshold_celsius: Some(90.4),
+            ..Default::default()
+        };
+        let mut state = AppletState::new(&config);
+        assert_eq!(state.critical_string, "90");
+
+        assert!(state.threshold_input(&mut config, ThresholdField::Warning, "70".into(), None));
+        assert_eq!(config.critical_threshold_celsius, Some(90.4));
+    }
+
+    #[test]
+    fn fahrenheit_toggle_reformats_the_inputs() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+        assert!(state.threshold_input(&mut config, ThresholdField::Critical, "90".into(), None));
+
+        assert!(state.set_unit(&mut config, TemperatureUnit::Fahrenheit, None));
+        assert_eq!(state.critical_string, "194");
+        assert_eq!(state.warning_string, "");
+        // Typed in Fahrenheit, stored in Celsius
+        assert!(state.threshold_input(&mut config, ThresholdField::Warning, "167".into(), None));
+        assert_eq!(config.warning_threshold_celsius, Some(75.0));
+        // Nothing to save when the unit stays the same
+        assert!(!state.set_unit(&mut config, TemperatureUnit::Fahrenheit, None));
+
+        assert!(state.set_unit(&mut config, TemperatureUnit::Celsius, None));
+        assert_eq!(state.critical_string, "90");
+        assert_eq!(state.warning_string, "75");
+    }
+
+    #[test]
+    fn invalid_period_input_shows_a_hint_and_saves_nothing() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+
+        let generation = state.period_input(PeriodField::Sample, "abc".into());
+        assert_eq!(state.period_error, Some(PeriodInput::Invalid));
+        assert!(!state.commit_period_if_current(&mut config, PeriodField::Sample, generation));
+        assert_eq!(config.sample_period_ms, 1000);
+        assert_eq!(state.period_edit.text(), "abc");
+
+        // Clamped, but saved
+        let generation = state.period_input(PeriodField::Sample, "50".into());
+        assert_eq!(state.period_error, Some(PeriodInput::TooShort));
+        assert!(state.commit_period_if_current(&mut config, PeriodField::Sample, generation));
+        assert_eq!(config.sample_period_ms, MIN_REFRESH_PERIOD_MS);
+
+        state.period_input(PeriodField::Sample, "2000".into());
+        assert_eq!(state.period_error, None);
+    }
+
+    #[test]
+    fn only_the_latest_period_edit_is_committed() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+
+        let first = state.period_input(PeriodField::Sample, "15".into());
+        let second = state.period_input(PeriodField::Sample, "1500".into());
+        assert!(!state.commit_period_if_current(&mut config, PeriodField::Sample, first));
+        assert!(state.commit_period_if_current(&mut config, PeriodField::Sample, second));
+        assert_eq!(config.sample_period_ms, 1500);
+
+        // Enter already saved it, the debounced commit comes too late
+        let generation = state.period_input(PeriodField::Sample, "2000".into());
+        assert!(state.submit_period(&mut config, PeriodField::Sample));
+        assert!(!state.commit_period_if_current(&mut config, PeriodField::Sample, generation));
+        assert_eq!(config.sample_period_ms, 2000);
+    }
+
+    #[test]
+    fn display_period_shorter_than_the_sample_period_is_not_saved() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+
+        config.sample_period_ms = 2000;
+        state.period_input(PeriodField::Display, "1000".into());
+        assert!(state.display_period_too_short(&config));
+        assert!(!state.submit_period(&mut config, PeriodField::Display));
+        assert_eq!(config.display_period_ms, 1000);
+
+        state.period_input(PeriodField::Display, "4000".into());
+        assert!(state.submit_period(&mut config, PeriodField::Display));
+        assert_eq!(config.display_period_ms, 4000);
+    }
+
+    #[test]
+    fn config_reloads_keep_edits_in_progress() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+
+        // Typing, the reload of an unrelated change arrives before the commit
+        let generation = state.period_input(PeriodField::Sample, "150".into());
+        let mut reloaded = config.clone();
+        reloaded.sample_period_ms = 3000;
+        state.config_changed(&config, &reloaded, true, None);
+        config = reloaded;
+        assert_eq!(state.period_edit.text(), "150");
+
+        assert!(state.commit_period_if_current(&mut config, PeriodField::Sample, generation));
+        let saved = config.clone();
+        // A stale reload from before the save, then the save itself
+        let mut stale = saved.clone();
+        stale.sample_period_ms = 3000;
+        state.config_changed(&config, &stale, true, None);
+        assert_eq!(state.period_edit.text(), "150");
+        state.config_changed(&config, &saved, true, None);
+        assert_eq!(state.period_edit.text(), "150");
+
+        // Changed elsewhere while nothing is edited
+        let mut external = saved.clone();
+        external.sample_period_ms = 5000;
+        external.critical_threshold_celsius = Some(85.0);
+        state.config_changed(&config, &external, true, None);
+        assert_eq!(state.period_edit.text(), "5000");
+        assert_eq!(state.critical_string, "85");
+    }
+
+    #[test]
+    fn pending_writes_win_over_reloads() {
+        let mut config = CPUTempAppletConfig::default();
+        let mut state = AppletState::new(&config);
+
+        assert!(state.threshold_input(&mut config, ThresholdField::Warning, "7".into(), None));
+        // The reload from before the input doesn't reformat what's being typed
+        let old = CPUTempAppletConfig::default();
+        state.config_changed(&config, &old, false, None);
+        assert_eq!(state.warning_string, "7");
+
+        // Nor does one that didn't change the thresholds
+        assert!(state.threshold_input(&mut config, ThresholdField::Warning, "70.0".into(), None));
+        let mut other = config.clone();
+        other.show_icon = true;
+        state.config_changed(&config, &other, true, None);
+        assert_eq!(state.warning_string, "70.0");
+    }
+
+    #[test]
+    fn offsets_are_in_celsius_in_every_unit() {
+        let mut config = CPUTempAppletConfig {
+            unit: TemperatureUnit::Fahrenheit,
+            ..Default::default()
+        };
+        let mut state = AppletState::new(&config);
+        assert_eq!(state.offset_string, "");
+
+        assert!(state.threshold_input(&mut config, ThresholdField::Offset, "-7".into(), None));
+        assert_eq!(config.offset_celsius, -7.0);
+        let label = Some("EC");
+        let input = "-3.5".to_string();
+        assert!(!state.threshold_input(&mut config, ThresholdField::SensorOffset, input, None));
+        let input = "-3.5".to_string();
+        assert!(state.threshold_input(&mut config, ThresholdField::SensorOffset, input, label));
+        assert_eq!(config.sensor_offsets.get("EC"), Some(&-3.5));
+
+        state.update_threshold_strings(&config, label);
+        assert_eq!(state.offset_string, "-7.0");
+        assert_eq!(state.sensor_offset_string, "-3.5");
+
+        let input = String::new();
+        assert!(state.threshold_input(&mut config, ThresholdField::SensorOffset, input, label));
+        assert!(config.sensor_offsets.is_empty());
+        assert!(state.threshold_input(&mut config, ThresholdField::Offset, "".into(), None));
+        assert_eq!(config.offset_celsius, 0.0);
+    }
+}
@@ -1,17 +1,500 @@
-use cosmic::cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, CosmicConfigEntry};
+use cosmic::cosmic_config::{
+    self, cosmic_config_derive::CosmicConfigEntry, ConfigGet, ConfigSet, CosmicConfigEntry,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
 
-#[derive(Debug, Clone, CosmicConfigEntry, PartialEq, Eq)]
-#[version = 1]
+use crate::panel_format::DEFAULT_PANEL_FORMAT;
+use crate::sanity;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TemperatureUnit {
+    #[default]
+    Celsius,
+    Fahrenheit,
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    // Same order as the options in the popup
+    pub const ALL: [TemperatureUnit; 3] = [
+        TemperatureUnit::Celsius,
+        TemperatureUnit::Fahrenheit,
+        TemperatureUnit::Kelvin,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Cpu,
+    Gpu,
+    Both,
+    // Hottest of all sensors, whatever it belongs to
+    HottestAny,
+    // CPU distance to its critical limit, "−18°"
+    Headroom,
+}
+
+impl DisplayMode {
+    // Same order as the options in the popup
+    pub const ALL: [DisplayMode; 5] = [
+        DisplayMode::Cpu,
+        DisplayMode::Gpu,
+        DisplayMode::Both,
+        DisplayMode::HottestAny,
+        DisplayMode::Headroom,
+    ];
+
+    pub fn shows_cpu(self) -> bool {
+        matches!(
+            self,
+            DisplayMode::Cpu | DisplayMode::Both | DisplayMode::Headroom
+        )
+    }
+
+    pub fn shows_gpu(self) -> bool {
+        matches!(self, DisplayMode::Gpu | DisplayMode::Both)
+    }
+}
+
+// How per-core temperatures are combined when there is no overall CPU sensor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CoreAggregation {
+    #[default]
+    Max,
+    Average,
+    Median,
+}
+
+impl CoreAggregation {
+    // Same order as the options in the popup
+    pub const ALL: [CoreAggregation; 3] = [
+        CoreAggregation::Max,
+        CoreAggregation::Average,
+        CoreAggregation::Median,
+    ];
+}
+
+// Where the sensor values are read from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SensorBackend {
+    #[default]
+    Sysinfo,
+    // /sys/class/hwmon, read directly
+    Hwmon,
+}
+
+impl SensorBackend {
+    // Same order as the options in the popup
+    pub const ALL: [SensorBackend; 2] = [SensorBackend::Sysinfo, SensorBackend::Hwmon];
+}
+
+// How the panel shows that a threshold was reached
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ThresholdIndication {
+    #[default]
+    Color,
+    // Swaps the thermometer for a warmer one or a flame
+    Icon,
+    Both,
+}
+
+impl ThresholdIndication {
+    // Same order as the options in the popup
+    pub const ALL: [ThresholdIndication; 3] = [
+        ThresholdIndication::Color,
+        ThresholdIndication::Icon,
+        ThresholdIndication::Both,
+    ];
+
+    pub fn colors_text(self) -> bool {
+        matches!(self, ThresholdIndication::Color | ThresholdIndication::Both)
+    }
+
+    pub fn changes_icon(self) -> bool {
+        matches!(self, ThresholdIndication::Icon | ThresholdIndication::Both)
+    }
+}
+
+// What the panel item is made of
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PanelStyle {
+    #[default]
+    Text,
+    // Only the thermometer, the temperature is in its tooltip and the popup
+    IconOnly,
+}
+
+impl PanelStyle {
+    // Same order as the options in the popup
+    pub const ALL: [PanelStyle; 2] = [PanelStyle::Text, PanelStyle::IconOnly];
+}
+
+// Time shown by the history graph in the popup
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphRange {
+    #[default]
+    FiveMinutes,
+    ThirtyMinutes,
+    TwoHours,
+}
+
+impl GraphRange {
+    // Same order as the options in the popup
+    pub const ALL: [GraphRange; 3] = [
+        GraphRange::FiveMinutes,
+        GraphRange::ThirtyMinutes,
+        GraphRange::TwoHours,
+    ];
+
+    pub fn duration(self) -> Duration {
+        let minutes = match self {
+            GraphRange::FiveMinutes => 5,
+            GraphRange::ThirtyMinutes => 30,
+            GraphRange::TwoHours => 120,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+}
+
+// Thresholds of a single sensor, in Celsius. `None` uses the global one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct SensorThresholds {
+    pub warning_celsius: Option<f32>,
+    pub critical_celsius: Option<f32>,
+}
+
+impl SensorThresholds {
+    pub fn is_empty(&self) -> bool {
+        self.warning_celsius.is_none() && self.critical_celsius.is_none()
+    }
+}
+
+pub const MAX_DECIMAL_PLACES: u8 = 2;
+
+const THRESHOLD_KEYS: [&str; 2] = ["warning_threshold_celsius", "critical_threshold_celsius"];
+// Replaced by `sample_period_ms` and `display_period_ms`
+const REFRESH_PERIOD_KEY: &str = "refresh_period_milliseconds";
+
+// Environment variable naming the applet instance, same as the `--instance` argument
+pub const INSTANCE_ENV: &str = "COSMIC_CPU_TEMP_INSTANCE";
+
+// Each named instance gets its own config, so two applets in the panel can show different
+// sensors. Without a name (or with one that has no usable characters) the shared applet ID
+// is used, which is where the settings of existing installs live.
+pub fn instance_config_id(app_id: &str, instance: Option<&str>) -> String {
+    let suffix: String = instance
+        .unwrap_or_default()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .collect();
+
+    if suffix.is_empty() {
+        app_id.to_string()
+    } else {
+        format!("{app_id}.{suffix}")
+    }
+}
+
+#[derive(Debug, Clone, CosmicConfigEntry, PartialEq)]
+#[version = 2]
 pub struct CPUTempAppletConfig {
-    pub fahrenheit: bool,
-    pub refresh_period_milliseconds: u64,
+    pub unit: TemperatureUnit,
+    // How often the sensors are read, every sample feeds the alerts, peak, stats and history
+    pub sample_period_ms: u64,
+    // How often the shown temperature changes, used as `sample_period_ms` when shorter
+    pub display_period_ms: u64,
+    // Component label picked by the user, `None` means automatic detection
+    pub selected_sensor: Option<String>,
+    // Always stored in Celsius, regardless of the display unit.
+    // `None` uses the limits reported by the sensor.
+    pub warning_threshold_celsius: Option<f32>,
+    pub critical_threshold_celsius: Option<f32>,
+    // Overrides of the two above by sensor label, e.g. a lower critical limit for an SSD.
+    // The global ones stay the default for every sensor without an entry.
+    pub sensor_thresholds: BTreeMap<String, SensorThresholds>,
+    // Added to what the CPU sensor reports, for sensors that are known to read high or low.
+    // In Celsius whatever the display unit, by sensor label with the global one as default.
+    pub offset_celsius: f32,
+    pub sensor_offsets: BTreeMap<String, f32>,
+    pub critical_notification: bool,
+    // Shell command to run once the critical threshold was exceeded for the delay
+    pub critical_action: Option<String>,
+    pub critical_action_delay_seconds: u64,
+    pub display_mode: DisplayMode,
+    // 0 to 2
+    pub decimal_places: u8,
+    // The panel value is rounded to a multiple of this, in the display unit and without
+    // decimals. Alerts and the history keep the exact value.
+    pub rounding_step: Option<u8>,
+    pub core_aggregation: CoreAggregation,
+    // Show the hottest AMD CCD instead of Tctl
+    pub hottest_ccd: bool,
+    // Show AMD Tctl (what fan curves use) instead of Tdie
+    pub prefer_tctl: bool,
+    // Thermometer icon next to the temperature in the panel
+    pub show_icon: bool,
+    pub panel_style: PanelStyle,
+    // Pad the value so the panel item keeps its width when the number of digits changes
+    pub fixed_width: bool,
+    // Template for the panel text, see `panel_format` for the placeholders
+    pub panel_format: String,
+    // Shown before and after everything else in the panel, to tell several applets apart
+    pub panel_prefix: String,
+    pub panel_suffix: String,
+    // Multiply the refresh period by `battery_period_multiplier` while on battery
+    pub battery_saver: bool,
+    pub battery_period_multiplier: u32,
+    // Weight of a new reading in the exponential moving average of the panel value,
+    // 0 to 1. `None` shows the raw readings.
+    pub smoothing: Option<f32>,
+    // List NVMe and SATA drive temperatures in the popup
+    pub show_drive_temps: bool,
+    // Launched on middle-click, `None` uses the first installed default system monitor
+    pub monitor_command: Option<String>,
+    pub sensor_backend: SensorBackend,
+    // Show the temperature in a second unit as well, see `units::secondary_unit`
+    pub show_both_units: bool,
+    // Show the CPU utilization next to the temperature in the panel, the popup always has it
+    pub show_load: bool,
+    // Show the CPU utilization instead of "--" in a virtual machine without any sensor
+    pub load_without_sensors: bool,
+    // Show the CPU fan's speed in the panel and every fan in the popup, see `fans.rs`
+    pub show_fan: bool,
+    // Read the package power from RAPL, shown in the popup and with `power_in_panel` in the
+    // panel as well
+    pub show_power: bool,
+    pub power_in_panel: bool,
+    // Badge in the panel while the CPU throttles and the events in the popup, see
+    // `throttle.rs`
+    pub show_throttling: bool,
+    // "Core 11: 88°", names the hottest core in front of the CPU temperature
+    pub show_hot_core: bool,
+    // Labels or simple globs ("pch_*") of sensors that are left out of every reading
+    pub sensor_blacklist: Vec<String>,
+    // Serve the readings for Prometheus on http://127.0.0.1:<metrics_port>/metrics
+    pub metrics_enabled: bool,
+    pub metrics_port: u16,
+    // Publish the CPU temperature to an MQTT broker, see `mqtt.rs`
+    pub mqtt_enabled: bool,
+    // "mqtt://host:port", the port defaults to 1883
+    pub mqtt_broker: String,
+    pub mqtt_topic: String,
+    // Every this many samples
+    pub mqtt_interval_multiplier: u32,
+    // User name on the first line, password on the second
+    pub mqtt_credentials_file: Option<PathBuf>,
+    // Serve every sensor as JSON on a Unix socket in the runtime directory, see `socket.rs`
+    pub socket_enabled: bool,
+    // Blink the panel button while the critical threshold is exceeded
+    pub flash_on_critical: bool,
+    // Steady instead of moving indicators, for desktops without a reduced motion setting
+    pub disable_animations: bool,
+    // Sound file or XDG sound event name played when the critical threshold is crossed
+    pub alert_sound: Option<String>,
+    // Critical notifications and sounds are held back between these, in minutes after
+    // midnight. See `quiet_hours.rs`.
+    pub quiet_hours_start: Option<u16>,
+    pub quiet_hours_end: Option<u16>,
+    // Keep the history graph and peak across restarts, see `persist.rs` for the file
+    pub persist_history: bool,
+    pub graph_range: GraphRange,
+    // The graph's axis goes from min to max instead of following the values, in Celsius
+    pub graph_fixed_scale: bool,
+    pub graph_min_celsius: f32,
+    pub graph_max_celsius: f32,
+    pub threshold_indication: ThresholdIndication,
+    // Only the icon is shown in the panel while the temperature is below this
+    pub hide_below_celsius: Option<f32>,
+    // Sensor values above are dropped as garbage, for hardware that really runs this hot
+    pub max_plausible_celsius: f32,
+    // Append every sample to this file, see `sample_log.rs`. Relative to the state directory.
+    pub log_to_file: Option<PathBuf>,
+    // The log is rotated once it's larger
+    pub log_max_size_mb: u32,
 }
 
 impl Default for CPUTempAppletConfig {
     fn default() -> Self {
         Self {
-            fahrenheit: false,
-            refresh_period_milliseconds: 1000,
+            unit: TemperatureUnit::Celsius,
+            sample_period_ms: 1000,
+            display_period_ms: 1000,
+            selected_sensor: None,
+            warning_threshold_celsius: None,
+            critical_threshold_celsius: None,
+            sensor_thresholds: BTreeMap::new(),
+            offset_celsius: 0.0,
+            sensor_offsets: BTreeMap::new(),
+            critical_notification: false,
+            critical_action: None,
+            critical_action_delay_seconds: 30,
+            display_mode: DisplayMode::Cpu,
+            decimal_places: 0,
+            rounding_step: None,
+            core_aggregation: CoreAggregation::Max,
+            hottest_ccd: false,
+            prefer_tctl: false,
+            show_icon: false,
+            panel_style: PanelStyle::Text,
+            fixed_width: false,
+            panel_format: DEFAULT_PANEL_FORMAT.to_string(),
+            panel_prefix: String::new(),
+            panel_suffix: String::new(),
+            battery_saver: false,
+            battery_period_multiplier: 5,
+            smoothing: None,
+            show_drive_temps: false,
+            monitor_command: None,
+            sensor_backend: SensorBackend::Sysinfo,
+            show_both_units: false,
+            show_load: false,
+            load_without_sensors: false,
+            show_fan: false,
+            show_power: false,
+            power_in_panel: true,
+            show_throttling: false,
+            show_hot_core: false,
+            sensor_blacklist: Vec::new(),
+            metrics_enabled: false,
+            metrics_port: 9101,
+            mqtt_enabled: false,
+            mqtt_broker: "mqtt://localhost:1883".to_string(),
+            mqtt_topic: "cosmic/cpu_temperature".to_string(),
+            mqtt_interval_multiplier: 1,
+            mqtt_credentials_file: None,
+            socket_enabled: false,
+            flash_on_critical: false,
+            disable_animations: false,
+            alert_sound: None,
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            persist_history: false,
+            graph_range: GraphRange::FiveMinutes,
+            graph_fixed_scale: false,
+            graph_min_celsius: 30.0,
+            graph_max_celsius: 100.0,
+            threshold_indication: ThresholdIndication::Color,
+            hide_below_celsius: None,
+            max_plausible_celsius: sanity::DEFAULT_MAX_PLAUSIBLE_CELSIUS,
+            log_to_file: None,
+            log_max_size_mb: 10,
+        }
+    }
+}
+
+impl CPUTempAppletConfig {
+    // Version 1 stored the unit as `fahrenheit: bool`, every other field is read as is.
+    // Does nothing if the current version was already written or there is no version 1.
+    pub fn migrate(id: &str, helper: &cosmic_config::Config) {
+        Self::migrate_thresholds(helper);
+        Self::migrate_refresh_period(helper);
+
+        if helper.get::<TemperatureUnit>("unit").is_ok() {
+            return;
+        }
+
+        let Ok(v1) = cosmic_config::Config::new(id, 1) else {
+            return;
+        };
+        let Ok(fahrenheit) = v1.get::<bool>("fahrenheit") else {
+            return;
+        };
+
+        // Missing fields keep their default
+        let mut config = match Self::get_entry(&v1) {
+            Ok(config) => config,
+            Err((_, config)) => config,
+        };
+        config.unit = if fahrenheit {
+            TemperatureUnit::Fahrenheit
+        } else {
+            TemperatureUnit::Celsius
+        };
+        for (key, threshold) in [
+            (THRESHOLD_KEYS[0], &mut config.warning_threshold_celsius),
+            (THRESHOLD_KEYS[1], &mut config.critical_threshold_celsius),
+        ] {
+            if let Ok(value) = v1.get::<f32>(key) {
+                *threshold = Some(value);
+            }
         }
+
+        if let Ok(period) = v1.get::<u64>(REFRESH_PERIOD_KEY) {
+            config.sample_period_ms = period;
+            config.display_period_ms = period;
+        }
+
+        if let Err(err) = config.write_entry(helper) {
+            tracing::error!(?err, "Error writing migrated config");
+        }
+    }
+
+    // The single refresh period became the sample and display periods, both start out with it
+    fn migrate_refresh_period(helper: &cosmic_config::Config) {
+        if helper.get::<u64>("sample_period_ms").is_ok() {
+            return;
+        }
+
+        if let Ok(period) = helper.get::<u64>(REFRESH_PERIOD_KEY) {
+            for key in ["sample_period_ms", "display_period_ms"] {
+                if let Err(err) = helper.set(key, period) {
+                    tracing::error!(?err, "Error writing migrated refresh period");
+                }
+            }
+        }
+    }
+
+    // Thresholds used to be plain numbers, which don't parse as `Option<f32>`
+    fn migrate_thresholds(helper: &cosmic_config::Config) {
+        for key in THRESHOLD_KEYS {
+            if helper.get::<Option<f32>>(key).is_ok() {
+                continue;
+            }
+
+            if let Ok(value) = helper.get::<f32>(key) {
+                if let Err(err) = helper.set(key, Some(value)) {
+                    tracing::error!(?err, "Error writing migrated threshold");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_instance_uses_the_applet_id() {
+        assert_eq!(
+            instance_config_id("com.example.Applet", None),
+            "com.example.Applet"
+        );
+        assert_eq!(
+            instance_config_id("com.example.Applet", Some("")),
+            "com.example.Applet"
+        );
+        // Nothing usable left
+        assert_eq!(
+            instance_config_id("com.example.Applet", Some("./ ")),
+            "com.example.Applet"
+        );
+    }
+
+    #[test]
+    fn named_instances_get_their_own_id() {
+        assert_eq!(
+            instance_config_id("com.example.Applet", Some("gpu-2_b")),
+            "com.example.Applet.gpu-2_b"
+        );
+        // Path separators and dots would leave the config directory or nest the ID
+        assert_eq!(
+            instance_config_id("com.example.Applet", Some("../work.laptop")),
+            "com.example.Applet.worklaptop"
+        );
     }
 }
@@ -0,0 +1,140 @@
+// Config writes of the applet and the settings window. Changes are written once they stopped
+// for `SAVE_DELAY`, and a failed write is tried again with a backoff.
+
+use cosmic::cosmic_config::{self, CosmicConfigEntry};
+use cosmic::iced::futures::SinkExt;
+use cosmic::iced::Subscription;
+use cosmic::iced_futures::stream;
+use tokio::time::{self, Duration};
+
+use crate::config::CPUTempAppletConfig;
+use crate::fl;
+
+// Config changes within this long are written together, e.g. scrolling through a dropdown
+const SAVE_DELAY: Duration = Duration::from_millis(300);
+// A failed write is tried again after this, doubled with every further failure up to the max
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Default)]
+pub(crate) struct ConfigWriter {
+    config_id: &'static str,
+    // `None` if the config directory couldn't be opened, tried again on every write
+    helper: Option<cosmic_config::Config>,
+    // Bumped by every `save`, only the last scheduled write happens
+    generation: u64,
+    dirty: bool,
+    // Writes that failed in a row and the last error, reset by a successful one
+    failures: u32,
+    error: Option<String>,
+    // The scheduled write is a retry of a failed one, not a new change
+    retry: bool,
+}
+
+impl ConfigWriter {
+    pub(crate) fn new(config_id: &'static str, helper: Option<cosmic_config::Config>) -> Self {
+        Self {
+            config_id,
+            helper,
+            ..Default::default()
+        }
+    }
+
+    // Every config change goes through here, the write happens in `subscription`
+    pub(crate) fn save(&mut self) {
+        self.dirty = true;
+        self.retry = false;
+        self.generation += 1;
+    }
+
+    // A change is waiting to be written. It's newer than what a config reload brings, which
+    // shouldn't replace it.
+    pub(crate) fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    pub(crate) fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    pub(crate) fn error(&self) -> &Option<String> {
+        &self.error
+    }
+
+    // On failure the config stays dirty and the write is tried again after a backoff
+    pub(crate) fn write(&mut self, config: &CPUTempAppletConfig) {
+        if self.helper.is_none() {
+            self.helper =
+                cosmic_config::Config::new(self.config_id, CPUTempAppletConfig::VERSION).ok();
+        }
+        let result = match &self.helper {
+            Some(helper) => config.write_entry(helper).map_err(|err| err.to_string()),
+            None => Err(fl!("config-unavailable")),
+        };
+
+        match result {
+            Ok(()) => {
+                self.dirty = false;
+                self.failures = 0;
+                self.error = None;
+            }
+            Err(err) => {
+                // Logged once per stretch of failures, the retries would repeat it
+                if self.failures == 0 {
+                    tracing::error!(%err, "Error writing config");
+                }
+                self.failures += 1;
+                self.error = Some(err);
+                // Starts the retry subscription
+                self.retry = true;
+                self.generation += 1;
+            }
+        }
+    }
+
+    // For the message of `subscription`, ignored if there were changes since
+    pub(crate) fn write_if_current(&mut self, config: &CPUTempAppletConfig, generation: u64) {
+        if self.dirty && generation == self.generation {
+            self.write(config);
+        }
+    }
+
+    // Right away, when quitting
+    pub(crate) fn flush(&mut self, config: &CPUTempAppletConfig) {
+        if self.dirty {
+            self.write(config);
+        }
+    }
+
+    // Until the next write, backing off while retrying. New changes are written after
+    // `SAVE_DELAY` as usual, they might be what makes it work again.
+    fn delay(&self) -> Duration {
+        if !self.retry {
+            return SAVE_DELAY;
+        }
+
+        let doublings = self.failures.saturating_sub(1).min(16);
+        RETRY_DELAY
+            .saturating_mul(1 << doublings)
+            .min(RETRY_MAX_DELAY)
+    }
+
+    // Restarted by every change, so it only fires once they stopped for a moment. Sends
+    // `write` with the generation to pass to `write_if_current`.
+    pub(crate) fn subscription<M: Send + 'static>(&self, write: fn(u64) -> M) -> Subscription<M> {
+        if !self.dirty {
+            return Subscription::none();
+        }
+
+        let generation = self.generation;
+        let delay = self.delay();
+        Subscription::run_with_id(
+            ("config-save-sub", generation),
+            stream::channel(1, move |mut output| async move {
+                time::sleep(delay).await;
+                let _ = output.send(write(generation)).await;
+                std::future::pending::<()>().await;
+            }),
+        )
+    }
+}
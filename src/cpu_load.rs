@@ -0,0 +1,42 @@
+use sysinfo::System;
+
+// Overall CPU utilization. sysinfo computes it from the difference between two refreshes,
+// so the `System` is kept between ticks and the first sample has no value.
+pub(crate) struct CpuLoad {
+    system: System,
+    // A refresh happened, the next one has something to compare with
+    primed: bool,
+}
+
+impl Default for CpuLoad {
+    fn default() -> Self {
+        Self {
+            system: System::new(),
+            primed: false,
+        }
+    }
+}
+
+impl CpuLoad {
+    // Average of all cores in percent, `None` on the first call
+    pub(crate) fn sample(&mut self) -> Option<f32> {
+        self.system.refresh_cpu_usage();
+        if !std::mem::replace(&mut self.primed, true) {
+            return None;
+        }
+
+        Some(self.system.global_cpu_usage())
+    }
+
+    // (percent, MHz) of every core since the last `sample`, cores without a known clock are
+    // left out. Only meaningful once `sample` returned a value.
+    pub(crate) fn cores(&mut self) -> Vec<(f32, u64)> {
+        self.system.refresh_cpu_frequency();
+        self.system
+            .cpus()
+            .iter()
+            .map(|cpu| (cpu.cpu_usage(), cpu.frequency()))
+            .filter(|(_, mhz)| *mhz > 0)
+            .collect()
+    }
+}
@@ -0,0 +1,22 @@
+use tokio::process::Command;
+
+// Runs the user's command through the shell, so pipes and arguments work as typed.
+// Returns a description of the failure, the command's own output isn't captured.
+pub(crate) async fn run(command: String) -> Result<(), String> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(&command)
+        .status()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, %command, "Failed to run critical action");
+            err.to_string()
+        })?;
+
+    if !status.success() {
+        tracing::error!(%status, %command, "Critical action failed");
+        return Err(status.to_string());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use zbus::object_server::SignalEmitter;
+use zbus::Connection;
+
+const DBUS_NAME: &str = "com.gr3q.CosmicExtAppletCPUTemperature";
+const DBUS_PATH: &str = "/com/gr3q/CosmicExtAppletCPUTemperature";
+
+struct Temperature {
+    // Celsius, NaN while there is no reading
+    celsius: f64,
+    // Empty when the sensor is picked automatically
+    sensor: String,
+}
+
+#[zbus::interface(name = "com.gr3q.CosmicExtAppletCPUTemperature")]
+impl Temperature {
+    #[zbus(property)]
+    fn temperature(&self) -> f64 {
+        self.celsius
+    }
+
+    #[zbus(property)]
+    fn sensor(&self) -> String {
+        self.sensor.clone()
+    }
+
+    #[zbus(signal, name = "TemperatureChanged")]
+    async fn emit_temperature_changed(
+        emitter: &SignalEmitter<'_>,
+        celsius: f64,
+    ) -> zbus::Result<()>;
+}
+
+// Named instances get a name of their own, only one connection can own a name. The instance
+// only has characters that are valid in a bus name, see `instance_config_id`.
+fn bus_name(instance: Option<&str>) -> String {
+    match instance {
+        Some(instance) => format!("{DBUS_NAME}_{instance}"),
+        None => DBUS_NAME.to_string(),
+    }
+}
+
+async fn try_start(instance: Option<&str>) -> zbus::Result<Connection> {
+    let temperature = Temperature {
+        celsius: f64::NAN,
+        sensor: String::new(),
+    };
+
+    zbus::connection::Builder::session()?
+        .name(bus_name(instance))?
+        .serve_at(DBUS_PATH, temperature)?
+        .build()
+        .await
+}
+
+// The applet keeps working without the service, so failures are only logged.
+// The name is released when the returned connection is dropped.
+pub(crate) async fn start(instance: Option<&str>) -> Option<Connection> {
+    match try_start(instance).await {
+        Ok(connection) => Some(connection),
+        Err(err) => {
+            tracing::error!(?err, "Failed to start D-Bus service");
+            None
+        }
+    }
+}
+
+async fn try_update(
+    connection: &Connection,
+    celsius: Option<f32>,
+    sensor: Option<String>,
+) -> zbus::Result<()> {
+    let iface_ref = connection
+        .object_server()
+        .interface::<_, Temperature>(DBUS_PATH)
+        .await?;
+    let mut iface = iface_ref.get_mut().await;
+    let emitter = iface_ref.signal_emitter();
+
+    let sensor = sensor.unwrap_or_default();
+    if iface.sensor != sensor {
+        iface.sensor = sensor;
+        iface.sensor_changed(emitter).await?;
+    }
+
+    let celsius = celsius.map_or(f64::NAN, f64::from);
+    // NaN != NaN, so compare the bits to not emit while there is no reading
+    if iface.celsius.to_bits() != celsius.to_bits() {
+        iface.celsius = celsius;
+        iface.temperature_changed(emitter).await?;
+        Temperature::emit_temperature_changed(emitter, celsius).await?;
+    }
+
+    Ok(())
+}
+
+pub(crate) async fn update(connection: Connection, celsius: Option<f32>, sensor: Option<String>) {
+    if let Err(err) = try_update(&connection, celsius, sensor).await {
+        tracing::error!(?err, "Failed to update D-Bus service");
+    }
+}
@@ -0,0 +1,178 @@
+// Fan speeds from hwmon, next to the temperature they tell whether the cooling responds.
+// sysinfo doesn't expose fans, so they're always read from sysfs whatever the backend.
+
+use std::fs;
+use std::path::Path;
+
+use crate::hwmon::list_chips;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Fan {
+    pub(crate) label: String,
+    // 0 is a stopped fan, not a missing one
+    pub(crate) rpm: u32,
+}
+
+// "fan2_input" -> (2, "input")
+fn parse_fan_file_name(name: &str) -> Option<(u32, &str)> {
+    let (index, kind) = name.strip_prefix("fan")?.split_once('_')?;
+    Some((index.parse().ok()?, kind))
+}
+
+// `fan<index>_label` when the driver provides one, most don't
+fn fan_label(chip: &str, index: u32, label: Option<&str>) -> String {
+    match label.map(str::trim).filter(|label| !label.is_empty()) {
+        Some(label) => label.to_string(),
+        None => format!("{chip} fan{index}"),
+    }
+}
+
+// Fans of a chip from the (file name, contents) pairs of its directory, ordered by index.
+// Inputs that fail to read are left out, unlike temperatures there's no last value to keep.
+fn parse_chip(chip: &str, files: &[(String, String)]) -> Vec<Fan> {
+    let file = |index: u32, kind: &str| {
+        files
+            .iter()
+            .find(|(name, _)| parse_fan_file_name(name) == Some((index, kind)))
+            .map(|(_, contents)| contents.as_str())
+    };
+
+    let mut indexes: Vec<u32> = files
+        .iter()
+        .filter_map(|(name, _)| parse_fan_file_name(name))
+        .filter(|(_, kind)| *kind == "input")
+        .map(|(index, _)| index)
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    let chip = chip.trim();
+    indexes
+        .into_iter()
+        .filter_map(|index| {
+            let rpm = file(index, "input")?.trim().parse().ok()?;
+            Some(Fan {
+                label: fan_label(chip, index, file(index, "label")),
+                rpm,
+            })
+        })
+        .collect()
+}
+
+fn read_chip_files(dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with("fan"))
+        .filter_map(|name| {
+            let contents = fs::read_to_string(dir.join(&name)).ok()?;
+            Some((name, contents))
+        })
+        .collect()
+}
+
+// Every fan of every chip under `root`. Only a handful of small files, so there's no cache
+// like for the temperatures.
+pub(crate) fn read_fans(root: &Path) -> Vec<Fan> {
+    list_chips(root)
+        .into_iter()
+        .flat_map(|dir| {
+            let chip = fs::read_to_string(dir.join("name")).unwrap_or_default();
+            parse_chip(&chip, &read_chip_files(&dir))
+        })
+        .collect()
+}
+
+// The one shown in the panel: labeled as the CPU fan, or the first one when none is
+pub(crate) fn cpu_fan(fans: &[Fan]) -> Option<&Fan> {
+    fans.iter()
+        .find(|fan| fan.label.to_ascii_lowercase().contains("cpu"))
+        .or_else(|| fans.first())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    fn files(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, contents)| (name.to_string(), contents.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_fan_inputs() {
+        let fans = parse_chip(
+            "nct6775\n",
+            &files(&[
+                ("fan2_input", "1450\n"),
+                ("fan2_label", "CPU Fan\n"),
+                ("fan1_input", "0\n"),
+                ("fan1_min", "300\n"),
+                ("fan3_input", "garbage\n"),
+                ("fan4_label", "No input\n"),
+            ]),
+        );
+
+        assert_eq!(
+            fans,
+            vec![
+                Fan {
+                    label: "nct6775 fan1".to_string(),
+                    rpm: 0,
+                },
+                Fan {
+                    label: "CPU Fan".to_string(),
+                    rpm: 1450,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn prefers_the_cpu_fan() {
+        let fan = |label: &str, rpm| Fan {
+            label: label.to_string(),
+            rpm,
+        };
+
+        let fans = [fan("Chassis", 800), fan("cpu_fan", 0), fan("Pump", 2000)];
+        assert_eq!(cpu_fan(&fans), Some(&fans[1]));
+        let fans = [fan("thinkpad fan1", 2400), fan("thinkpad fan2", 2300)];
+        assert_eq!(cpu_fan(&fans), Some(&fans[0]));
+        assert_eq!(cpu_fan(&[]), None);
+    }
+
+    #[test]
+    fn reads_every_chip() {
+        let root = TestDir::new("fans");
+        for (dir, chip, fixture) in [
+            ("hwmon0", "k10temp\n", &[("temp1_input", "54000\n")][..]),
+            (
+                "hwmon1",
+                "thinkpad\n",
+                &[("fan1_input", "2400\n"), ("fan2_input", "0\n")][..],
+            ),
+        ] {
+            root.write(format!("{dir}/name"), chip);
+            for (name, contents) in fixture {
+                root.write(format!("{dir}/{name}"), contents);
+            }
+        }
+
+        let fans = read_fans(&root);
+        assert_eq!(
+            fans.iter()
+                .map(|fan| (fan.label.as_str(), fan.rpm))
+                .collect::<Vec<_>>(),
+            [("thinkpad fan1", 2400), ("thinkpad fan2", 0)]
+        );
+        assert!(read_fans(Path::new("/nonexistent/hwmon")).is_empty());
+    }
+}
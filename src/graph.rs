@@ -0,0 +1,162 @@
+use std::time::{Duration, Instant};
+
+use cosmic::iced::mouse;
+use cosmic::iced::widget::canvas;
+use cosmic::iced::{Point, Rectangle};
+
+use crate::history::TempHistory;
+
+pub(crate) struct HistoryGraph {
+    // (position on the x axis from 0 to 1, value in the display unit)
+    points: Vec<(f32, f32)>,
+    // Ends of the y axis in the display unit
+    pub(crate) min: f32,
+    pub(crate) max: f32,
+    // Warning and critical threshold in the display unit, drawn as guides
+    warning: f32,
+    critical: f32,
+}
+
+impl HistoryGraph {
+    // `scale` fixes the y axis to (min, max), it follows the values otherwise. It and the
+    // thresholds are in Celsius like the history, `convert` turns them into the display unit.
+    // A scale from a hand-edited config can be upside down, or empty and then ignored.
+    pub(crate) fn new(
+        history: &TempHistory,
+        range: Duration,
+        scale: Option<(f32, f32)>,
+        thresholds: (f32, f32),
+        convert: impl Fn(f32) -> f32,
+    ) -> Option<Self> {
+        let now = Instant::now();
+        let window = range.as_secs_f32();
+        let points: Vec<(f32, f32)> = history
+            .samples_within(range, now)
+            .map(|(time, celsius)| {
+                let age = now.duration_since(*time).as_secs_f32();
+                (1.0 - (age / window).min(1.0), convert(*celsius))
+            })
+            .collect();
+
+        let scale = scale.filter(|(min, max)| min.is_finite() && max.is_finite() && min != max);
+        let (min, max) = match scale {
+            Some((a, b)) => (convert(a.min(b)), convert(a.max(b))),
+            None => points.iter().fold(None, |acc, (_, value)| match acc {
+                None => Some((*value, *value)),
+                Some((min, max)) => Some((f32::min(min, *value), f32::max(max, *value))),
+            })?,
+        };
+        if points.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            points,
+            min,
+            max,
+            warning: convert(thresholds.0),
+            critical: convert(thresholds.1),
+        })
+    }
+}
+
+impl<Message> canvas::Program<Message, cosmic::Theme, cosmic::Renderer> for HistoryGraph {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &cosmic::Renderer,
+        theme: &cosmic::Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry<cosmic::Renderer>> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+
+        // Keep a flat line in the middle instead of dividing by zero
+        let (min, max) = if self.max - self.min < 1.0 {
+            (self.min - 1.0, self.max + 1.0)
+        } else {
+            (self.min, self.max)
+        };
+        let to_y = |value: f32| {
+            bounds.height - (value.clamp(min, max) - min) / (max - min) * bounds.height
+        };
+
+        let cosmic = theme.cosmic();
+        for (threshold, color) in [
+            (self.warning, cosmic.warning_color()),
+            (self.critical, cosmic.destructive_color()),
+        ] {
+            // Only where the axis reaches, a guide at the edge would look like a value
+            if threshold <= min || threshold >= max {
+                continue;
+            }
+            let y = to_y(threshold);
+            let guide = canvas::Path::line(Point::new(0.0, y), Point::new(bounds.width, y));
+            frame.stroke(
+                &guide,
+                canvas::Stroke {
+                    line_dash: canvas::LineDash {
+                        segments: &[4.0, 4.0],
+                        offset: 0,
+                    },
+                    ..canvas::Stroke::default()
+                        .with_color(color.into())
+                        .with_width(1.0)
+                },
+            );
+        }
+
+        let path = canvas::Path::new(|builder| {
+            let mut points = self
+                .points
+                .iter()
+                .map(|(x, value)| Point::new(x * bounds.width, to_y(*value)));
+            if let Some(first) = points.next() {
+                builder.move_to(first);
+                for point in points {
+                    builder.line_to(point);
+                }
+            }
+        });
+
+        frame.stroke(
+            &path,
+            canvas::Stroke::default()
+                .with_color(cosmic.accent_color().into())
+                .with_width(2.0),
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HISTORY_WINDOW;
+
+    fn graph(scale: Option<(f32, f32)>) -> HistoryGraph {
+        let mut history = TempHistory::default();
+        history.push(50.0);
+        HistoryGraph::new(&history, HISTORY_WINDOW, scale, (75.0, 90.0), |celsius| {
+            celsius
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn orders_an_upside_down_scale() {
+        let graph = graph(Some((100.0, 30.0)));
+        assert_eq!((graph.min, graph.max), (30.0, 100.0));
+    }
+
+    #[test]
+    fn follows_the_values_without_a_usable_scale() {
+        for scale in [None, Some((70.0, 70.0)), Some((f32::NAN, 100.0))] {
+            let graph = graph(scale);
+            assert_eq!((graph.min, graph.max), (50.0, 50.0));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Wall-clock time covered by every sample, the shortest range of the graph
+pub(crate) const HISTORY_WINDOW: Duration = Duration::from_secs(5 * 60);
+const MAX_SAMPLES: usize = 300;
+// Longer ranges keep one sample per interval, so 2 hours draw about as many points
+const COARSE_INTERVAL: Duration = Duration::from_secs(10);
+// The longest range of the graph
+const COARSE_WINDOW: Duration = Duration::from_secs(2 * 60 * 60);
+
+// Temperatures in Celsius, bounded by both age and sample count so it doesn't grow
+// while the popup is never opened
+#[derive(Debug, Default)]
+pub(crate) struct TempHistory {
+    samples: VecDeque<(Instant, f32)>,
+    // Downsampled for the longer ranges
+    coarse: VecDeque<(Instant, f32)>,
+    // Start and hottest value of the interval that's not in `coarse` yet
+    pending: Option<(Instant, f32)>,
+}
+
+impl TempHistory {
+    // Rebuilds the history from saved samples, oldest first
+    pub(crate) fn restore(samples: impl IntoIterator<Item = (Instant, f32)>) -> Self {
+        let mut history = Self::default();
+        for (time, celsius) in samples {
+            history.push_at(time, celsius);
+        }
+        history
+    }
+
+    pub(crate) fn push(&mut self, celsius: f32) {
+        self.push_at(Instant::now(), celsius);
+    }
+
+    fn push_at(&mut self, now: Instant, celsius: f32) {
+        self.push_coarse(now, celsius);
+
+        // Refresh periods shorter than this are downsampled, so the buffer always covers
+        // the whole window. Longer periods simply end up with fewer samples.
+        let min_interval = HISTORY_WINDOW / MAX_SAMPLES as u32;
+        if let Some((last, _)) = self.samples.back() {
+            // Some leeway for timer jitter
+            if now.duration_since(*last) < min_interval * 9 / 10 {
+                return;
+            }
+        }
+
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > HISTORY_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+
+        if self.samples.len() >= MAX_SAMPLES {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back((now, celsius));
+    }
+
+    // The hottest value of each interval, an average would hide short spikes
+    fn push_coarse(&mut self, now: Instant, celsius: f32) {
+        let (start, hottest) = self.pending.get_or_insert((now, celsius));
+        *hottest = hottest.max(celsius);
+        if now.duration_since(*start) < COARSE_INTERVAL {
+            return;
+        }
+
+        let hottest = *hottest;
+        self.pending = None;
+        while self
+            .coarse
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > COARSE_WINDOW)
+        {
+            self.coarse.pop_front();
+        }
+        self.coarse.push_back((now, hottest));
+    }
+
+    pub(crate) fn samples(&self) -> impl Iterator<Item = &(Instant, f32)> {
+        self.samples.iter()
+    }
+
+    // The samples of the last `range`, downsampled when it's longer than every sample covers
+    pub(crate) fn samples_within(
+        &self,
+        range: Duration,
+        now: Instant,
+    ) -> impl Iterator<Item = &(Instant, f32)> {
+        let samples = if range <= HISTORY_WINDOW {
+            &self.samples
+        } else {
+            &self.coarse
+        };
+        samples
+            .iter()
+            .filter(move |(time, _)| now.duration_since(*time) <= range)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_hottest_value_of_each_interval() {
+        let start = Instant::now();
+        let at = |seconds| start + Duration::from_secs(seconds);
+        let mut history = TempHistory::default();
+        for (seconds, celsius) in [(0, 50.0), (3, 70.0), (6, 55.0), (10, 52.0), (14, 60.0)] {
+            history.push_at(at(seconds), celsius);
+        }
+        // The second interval isn't over yet
+        let coarse: Vec<_> = history.samples_within(COARSE_WINDOW, at(14)).collect();
+        assert_eq!(coarse, [&(at(10), 70.0)]);
+
+        history.push_at(at(24), 40.0);
+        let coarse: Vec<_> = history.samples_within(COARSE_WINDOW, at(24)).collect();
+        assert_eq!(coarse, [&(at(10), 70.0), &(at(24), 60.0)]);
+        // The shortest range has every sample instead
+        assert_eq!(history.samples_within(HISTORY_WINDOW, at(24)).count(), 6);
+    }
+
+    #[test]
+    fn drops_coarse_samples_after_two_hours() {
+        let start = Instant::now();
+        let mut history = TempHistory::default();
+        let end = COARSE_WINDOW.as_secs() + 10 * 60;
+        for seconds in (0..=end).step_by(10) {
+            history.push_at(start + Duration::from_secs(seconds), 50.0);
+        }
+
+        // As of the last interval that ended, the newest samples are still waiting
+        let (oldest, _) = *history.coarse.front().unwrap();
+        let (newest, _) = *history.coarse.back().unwrap();
+        assert!(oldest > start);
+        assert!(newest.duration_since(oldest) <= COARSE_WINDOW);
+        assert_eq!(
+            history.samples_within(COARSE_WINDOW, newest).count(),
+            history.coarse.len()
+        );
+    }
+}
@@ -0,0 +1,468 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::SinkExt;
+
+use crate::sysinfo_utils::{
+    build_reading, is_drive_temp_label, RawSensor, ReadOptions, SensorReading, TempProvider,
+};
+use crate::thermal_zone::parse_millidegrees;
+
+pub(crate) const HWMON_ROOT: &str = "/sys/class/hwmon";
+
+// Labels of the first input of CPU drivers that don't (or didn't always) label it, so the
+// usual CPU detection recognizes them
+const UNLABELED_FIRST_INPUTS: &[(&str, &str)] = &[
+    ("k10temp", "Tctl"),
+    ("zenpower", "Tdie"),
+    ("coretemp", "Package id 0"),
+];
+
+// Chips are only listed again after this long, values are read on every refresh
+const RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+// How often the chip directories are listed to notice plugged in or removed ones (USB
+// sensors, an eGPU). Much cheaper than a rescan, which reads every chip.
+const HOTPLUG_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+// "temp3_input" -> (3, "input")
+fn parse_temp_file_name(name: &str) -> Option<(u32, &str)> {
+    let (index, kind) = name.strip_prefix("temp")?.split_once('_')?;
+    Some((index.parse().ok()?, kind))
+}
+
+// Uses `temp<index>_label` when the driver provides one, like sysinfo does
+fn input_label(chip: &str, index: u32, label: Option<&str>) -> String {
+    if let Some(label) = label.map(str::trim).filter(|label| !label.is_empty()) {
+        return label.to_string();
+    }
+
+    UNLABELED_FIRST_INPUTS
+        .iter()
+        .find(|(name, _)| index == 1 && *name == chip)
+        .map_or_else(
+            || format!("{chip} temp{index}"),
+            |(_, label)| label.to_string(),
+        )
+}
+
+// Temperature inputs of a chip, from its `name` and the (file name, contents) pairs of its
+// directory, ordered by index. Groups without an `_input` file aren't inputs.
+fn parse_chip(chip: &str, files: &[(String, String)]) -> Vec<(u32, RawSensor)> {
+    let file = |index: u32, kind: &str| {
+        files
+            .iter()
+            .find(|(name, _)| parse_temp_file_name(name) == Some((index, kind)))
+            .map(|(_, contents)| contents.as_str())
+    };
+
+    let mut indexes: Vec<u32> = files
+        .iter()
+        .filter_map(|(name, _)| parse_temp_file_name(name))
+        .filter(|(_, kind)| *kind == "input")
+        .map(|(index, _)| index)
+        .collect();
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    let chip = chip.trim();
+    indexes
+        .into_iter()
+        .map(|index| {
+            let sensor = RawSensor {
+                label: input_label(chip, index, file(index, "label")),
+                chip: Some(chip.to_string()),
+                temp: file(index, "input").and_then(parse_millidegrees),
+                max: file(index, "max").and_then(parse_millidegrees),
+                critical: file(index, "crit").and_then(parse_millidegrees),
+            };
+            (index, sensor)
+        })
+        .collect()
+}
+
+// The `temp*` files of a chip directory. Inputs that fail to read (e.g. an EC timeout)
+// are kept with empty contents, so they show up as unread instead of vanishing.
+fn read_chip_files(dir: &Path) -> Vec<(String, String)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with("temp"))
+        .map(|name| {
+            let contents = fs::read_to_string(dir.join(&name)).unwrap_or_default();
+            (name, contents)
+        })
+        .collect()
+}
+
+// Reads `/sys/class/hwmon` directly, without the overhead of sysinfo
+pub(crate) struct HwmonProvider {
+    root: PathBuf,
+    // `_input` file of every sensor, same order as `sensors`
+    inputs: Vec<PathBuf>,
+    sensors: Vec<RawSensor>,
+    // `None` until the first scan happened
+    last_scan: Option<Instant>,
+}
+
+impl Default for HwmonProvider {
+    fn default() -> Self {
+        Self::new(Path::new(HWMON_ROOT))
+    }
+}
+
+impl HwmonProvider {
+    pub(crate) fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            inputs: vec![],
+            sensors: vec![],
+            last_scan: None,
+        }
+    }
+
+    // Lists every chip, this reads the values as well
+    fn scan(&mut self) {
+        self.inputs.clear();
+        self.sensors.clear();
+        self.last_scan = Some(Instant::now());
+
+        for dir in list_chips(&self.root) {
+            let chip = fs::read_to_string(dir.join("name")).unwrap_or_default();
+            for (index, sensor) in parse_chip(&chip, &read_chip_files(&dir)) {
+                self.inputs.push(dir.join(format!("temp{index}_input")));
+                self.sensors.push(sensor);
+            }
+        }
+    }
+
+    fn refresh(&mut self, include_drives: bool) {
+        let scan_due = self
+            .last_scan
+            .map_or(true, |last_scan| last_scan.elapsed() >= RESCAN_INTERVAL);
+        if scan_due {
+            self.scan();
+            return;
+        }
+
+        for (input, sensor) in self.inputs.iter().zip(self.sensors.iter_mut()) {
+            // Reading drive sensors can wake up sleeping disks
+            if include_drives || !is_drive_temp_label(&sensor.label) {
+                sensor.temp = fs::read_to_string(input)
+                    .ok()
+                    .and_then(|contents| parse_millidegrees(&contents));
+            }
+        }
+    }
+}
+
+// Directory of every chip, empty if `root` can't be read
+pub(crate) fn list_chips(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return vec![];
+    };
+    let mut chips: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    // hwmon10 after hwmon9 doesn't matter, only a stable order does
+    chips.sort();
+    chips
+}
+
+// Sends whenever a chip appeared or went away since the last look. Both providers only
+// list the sensors again every `RESCAN_INTERVAL` otherwise.
+pub(crate) async fn watch_chips(mut output: mpsc::Sender<()>) {
+    let root = Path::new(HWMON_ROOT);
+    let mut known = list_chips(root);
+
+    loop {
+        tokio::time::sleep(HOTPLUG_POLL_INTERVAL).await;
+        let chips = list_chips(root);
+        if chips != known {
+            known = chips;
+            // The subscription went away
+            if output.send(()).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+impl TempProvider for HwmonProvider {
+    fn read(&mut self, options: &ReadOptions) -> SensorReading {
+        self.refresh(options.drive_temps || options.hottest_any || options.all_components);
+        build_reading(&self.sensors, options)
+    }
+
+    fn rescan_on_next_read(&mut self) {
+        self.last_scan = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CoreAggregation;
+    use crate::sysinfo_utils::{CpuSensor, SensorTemp, TempReading};
+    use crate::test_dir::TestDir;
+
+    fn files(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, contents)| (name.to_string(), contents.to_string()))
+            .collect()
+    }
+
+    // Captured from a Ryzen 9 5900X
+    const K10TEMP: &[(&str, &str)] = &[
+        ("temp1_input", "61250\n"),
+        ("temp1_label", "Tctl\n"),
+        ("temp3_input", "54500\n"),
+        ("temp3_label", "Tccd1\n"),
+        ("temp4_input", "49750\n"),
+        ("temp4_label", "Tccd2\n"),
+    ];
+
+    // Captured from an i7-8550U
+    const CORETEMP: &[(&str, &str)] = &[
+        ("temp1_crit", "100000\n"),
+        ("temp1_crit_alarm", "0\n"),
+        ("temp1_input", "47000\n"),
+        ("temp1_label", "Package id 0\n"),
+        ("temp1_max", "100000\n"),
+        ("temp2_crit", "100000\n"),
+        ("temp2_input", "45000\n"),
+        ("temp2_label", "Core 0\n"),
+        ("temp2_max", "100000\n"),
+    ];
+
+    // Captured from a Samsung 970 EVO
+    const NVME: &[(&str, &str)] = &[
+        ("temp1_alarm", "0\n"),
+        ("temp1_crit", "84850\n"),
+        ("temp1_input", "38850\n"),
+        ("temp1_label", "Composite\n"),
+        ("temp1_max", "81850\n"),
+        ("temp1_min", "-273150\n"),
+    ];
+
+    // zenpower with the inputs its README lists, on a kernel setup without Tctl. The
+    // voltage, current and power inputs aren't temperatures.
+    const ZENPOWER: &[(&str, &str)] = &[
+        ("temp1_input", "58375\n"),
+        ("temp1_label", "Tdie\n"),
+        ("temp3_input", "55250\n"),
+        ("temp3_label", "Tccd1\n"),
+        ("temp4_input", "51000\n"),
+        ("temp4_label", "Tccd2\n"),
+        ("in1_input", "1331\n"),
+        ("in1_label", "SVI2_Core\n"),
+        ("curr1_input", "14236\n"),
+        ("curr1_label", "SVI2_C_Core\n"),
+        ("power1_input", "18948000\n"),
+        ("power1_label", "SVI2_P_Core\n"),
+    ];
+
+    // k10temp of older kernels, without labels
+    const K10TEMP_UNLABELED: &[(&str, &str)] = &[("temp1_input", "43125\n")];
+
+    #[test]
+    fn parses_temp_file_names() {
+        assert_eq!(parse_temp_file_name("temp1_input"), Some((1, "input")));
+        assert_eq!(
+            parse_temp_file_name("temp12_crit_alarm"),
+            Some((12, "crit_alarm"))
+        );
+        assert_eq!(parse_temp_file_name("temp_input"), None);
+        assert_eq!(parse_temp_file_name("fan1_input"), None);
+        assert_eq!(parse_temp_file_name("name"), None);
+    }
+
+    #[test]
+    fn parses_labeled_inputs() {
+        let labels: Vec<(u32, String, Option<f32>)> = parse_chip("k10temp\n", &files(K10TEMP))
+            .into_iter()
+            .map(|(index, sensor)| (index, sensor.label, sensor.temp))
+            .collect();
+
+        assert_eq!(
+            labels,
+            vec![
+                (1, "Tctl".to_string(), Some(61.25)),
+                (3, "Tccd1".to_string(), Some(54.5)),
+                (4, "Tccd2".to_string(), Some(49.75)),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_limits() {
+        let sensors = parse_chip("nvme\n", &files(NVME));
+
+        assert_eq!(
+            sensors,
+            vec![(
+                1,
+                RawSensor {
+                    label: "Composite".to_string(),
+                    chip: Some("nvme".to_string()),
+                    temp: Some(38.85),
+                    max: Some(81.85),
+                    critical: Some(84.85),
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn labels_unlabeled_inputs() {
+        let sensors = parse_chip("k10temp\n", &files(K10TEMP_UNLABELED));
+        assert_eq!(sensors[0].1.label, "Tctl");
+
+        assert_eq!(input_label("zenpower", 1, None), "Tdie");
+        assert_eq!(input_label("drivetemp", 1, Some("\n")), "drivetemp temp1");
+        assert_eq!(input_label("k10temp", 2, None), "k10temp temp2");
+    }
+
+    #[test]
+    fn keeps_inputs_that_fail_to_read() {
+        let sensors = parse_chip(
+            "coretemp",
+            &files(&[("temp1_input", ""), ("temp1_label", "Package id 0")]),
+        );
+
+        assert_eq!(sensors.len(), 1);
+        assert_eq!(sensors[0].1.temp, None);
+    }
+
+    #[test]
+    fn uses_the_usual_cpu_detection() {
+        let sensors: Vec<RawSensor> = [("coretemp", CORETEMP), ("nvme", NVME)]
+            .iter()
+            .flat_map(|(chip, fixture)| parse_chip(chip, &files(fixture)))
+            .map(|(_, sensor)| sensor)
+            .collect();
+
+        let reading = build_reading(&sensors, &ReadOptions::default());
+        assert_eq!(
+            reading.cpu,
+            TempReading::Value(SensorTemp {
+                celsius: 47.0,
+                max: Some(100.0),
+                critical: Some(100.0),
+            })
+        );
+        assert_eq!(
+            reading.cpu_sensor,
+            Some(CpuSensor::Component {
+                label: "Package id 0".to_string(),
+                chip: Some("coretemp".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn picks_tdie_of_zenpower_without_tctl() {
+        let sensors: Vec<RawSensor> = parse_chip("zenpower\n", &files(ZENPOWER))
+            .into_iter()
+            .map(|(_, sensor)| sensor)
+            .collect();
+        assert_eq!(sensors.len(), 3);
+
+        let reading = build_reading(&sensors, &ReadOptions::default());
+        assert_eq!(
+            reading.cpu,
+            TempReading::Value(SensorTemp {
+                celsius: 58.375,
+                max: None,
+                critical: None,
+            })
+        );
+        assert_eq!(
+            reading.cpu_sensor,
+            Some(CpuSensor::Component {
+                label: "Tdie".to_string(),
+                chip: Some("zenpower".to_string()),
+            })
+        );
+        assert_eq!(
+            reading.ccds,
+            vec![("Tccd1".to_string(), 55.25), ("Tccd2".to_string(), 51.0)]
+        );
+
+        // Without Tdie the CCDs are the cores
+        let ccds_only: Vec<RawSensor> = sensors
+            .into_iter()
+            .filter(|sensor| sensor.label != "Tdie")
+            .collect();
+        let reading = build_reading(&ccds_only, &ReadOptions::default());
+        assert_eq!(
+            reading.cpu,
+            TempReading::Value(SensorTemp {
+                celsius: 55.25,
+                max: None,
+                critical: None,
+            })
+        );
+        assert_eq!(
+            reading.cpu_sensor,
+            Some(CpuSensor::Cores {
+                aggregation: CoreAggregation::Max,
+                count: 2,
+            })
+        );
+    }
+
+    #[test]
+    fn reads_hwmon_directory() {
+        let root = TestDir::new("hwmon");
+        for (dir, chip, fixture) in [("hwmon0", "k10temp\n", K10TEMP), ("hwmon1", "nvme\n", NVME)] {
+            root.write(format!("{dir}/name"), chip);
+            for (name, contents) in fixture.iter() {
+                root.write(format!("{dir}/{name}"), contents);
+            }
+        }
+
+        let options = ReadOptions {
+            drive_temps: true,
+            ..Default::default()
+        };
+        let mut provider = HwmonProvider::new(&root);
+        let reading = provider.read(&options);
+        assert!(
+            matches!(reading.cpu_sensor, Some(CpuSensor::Component { label, .. }) if label == "Tctl")
+        );
+        assert_eq!(reading.ccds.len(), 2);
+        assert_eq!(reading.drives, vec![("Composite".to_string(), 38.85)]);
+        assert_eq!(
+            list_chips(&root),
+            vec![root.join("hwmon0"), root.join("hwmon1")]
+        );
+
+        // Values are read again without a rescan
+        root.write("hwmon0/temp1_input", "70000\n");
+        let reading = provider.read(&options);
+        assert!(matches!(reading.cpu, TempReading::Value(temp) if temp.celsius == 70.0));
+
+        // A chip that went away is gone after a rescan
+        fs::remove_dir_all(root.join("hwmon1")).unwrap();
+        provider.rescan_on_next_read();
+        let reading = provider.read(&options);
+        assert!(reading.drives.is_empty());
+        assert_eq!(list_chips(&root), vec![root.join("hwmon0")]);
+    }
+
+    #[test]
+    fn missing_root_has_no_sensors() {
+        assert!(list_chips(Path::new("/nonexistent/hwmon")).is_empty());
+        let mut provider = HwmonProvider::new(Path::new("/nonexistent/hwmon"));
+
+        assert_eq!(
+            provider.read(&ReadOptions::default()).cpu,
+            TempReading::NoSensors
+        );
+    }
+}
@@ -0,0 +1,212 @@
+// Numbers the way the user's locale writes them. Only the decimal separator of shown
+// temperatures is localized, the digits stay ASCII like in the rest of the panel. Typed
+// numbers are read leniently instead: grouping separators are dropped, "85,5" and "85.5"
+// both work, and so do Arabic-Indic and other common digits.
+
+use once_cell::sync::OnceCell;
+
+const DEFAULT_DECIMAL_SEPARATOR: char = '.';
+
+// Languages writing "85,5", by their ISO 639 code
+const COMMA_LANGUAGES: &[&str] = &[
+    "af", "az", "be", "bg", "bs", "ca", "cs", "da", "de", "el", "es", "et", "eu", "fi", "fr", "gl",
+    "hr", "hu", "hy", "id", "is", "it", "ka", "kk", "lt", "lv", "mk", "nb", "nl", "nn", "no", "pl",
+    "pt", "ro", "ru", "sk", "sl", "sq", "sr", "sv", "tr", "uk", "uz", "vi",
+];
+// Regions using a period although their language doesn't
+const PERIOD_REGIONS: &[(&str, &str)] = &[
+    ("de", "CH"),
+    ("de", "LI"),
+    ("es", "MX"),
+    ("es", "US"),
+    ("it", "CH"),
+];
+// Zeros of the digit blocks that can be typed instead of ASCII ones: Arabic-Indic, Persian,
+// Devanagari, Bengali and full width
+const ZERO_DIGITS: [char; 5] = ['\u{660}', '\u{6F0}', '\u{966}', '\u{9E6}', '\u{FF10}'];
+
+static DECIMAL_SEPARATOR: OnceCell<char> = OnceCell::new();
+
+// "de_DE.UTF-8", "de-AT", "sr_RS@latin" or "C". Anything unknown writes a period.
+pub(crate) fn decimal_separator_for(locale: &str) -> char {
+    let name = locale.split(['.', '@']).next().unwrap_or_default();
+    let mut parts = name.split(['_', '-']);
+    let language = parts.next().unwrap_or_default().to_ascii_lowercase();
+    let region = parts.next().unwrap_or_default().to_ascii_uppercase();
+
+    if PERIOD_REGIONS.contains(&(language.as_str(), region.as_str())) {
+        '.'
+    } else if COMMA_LANGUAGES.contains(&language.as_str()) {
+        ','
+    } else {
+        DEFAULT_DECIMAL_SEPARATOR
+    }
+}
+
+// Picks the separator from the environment the way the C library does. Runs once at startup,
+// numbers formatted before (and in tests) use a period.
+pub(crate) fn init() {
+    let locale = ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .into_iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+    let _ = DECIMAL_SEPARATOR.set(decimal_separator_for(&locale));
+}
+
+fn localize_with(number: String, separator: char) -> String {
+    if separator == '.' {
+        number
+    } else {
+        number.replacen('.', &separator.to_string(), 1)
+    }
+}
+
+// Takes a number formatted with `format!`, so without grouping and at most one period
+pub(crate) fn localize(number: String) -> String {
+    let separator = DECIMAL_SEPARATOR
+        .get()
+        .copied()
+        .unwrap_or(DEFAULT_DECIMAL_SEPARATOR);
+    localize_with(number, separator)
+}
+
+fn ascii_digit(c: char) -> Option<char> {
+    if c.is_ascii_digit() {
+        return Some(c);
+    }
+
+    ZERO_DIGITS.iter().find_map(|&zero| {
+        let offset = u32::from(c).checked_sub(u32::from(zero))?;
+        (offset < 10).then(|| char::from(b'0' + offset as u8))
+    })
+}
+
+fn ascii_digits(text: &str) -> Option<String> {
+    if text.is_empty() {
+        return None;
+    }
+    text.chars().map(ascii_digit).collect()
+}
+
+// Includes the Arabic thousands separator, a period and a comma can group as well
+fn is_group_separator(c: char) -> bool {
+    matches!(
+        c,
+        '.' | ',' | ' ' | '\'' | '\u{A0}' | '\u{202F}' | '\u{2019}' | '\u{66C}'
+    )
+}
+
+// Includes the Arabic decimal separator
+fn is_decimal_mark(c: char) -> bool {
+    matches!(c, '.' | ',' | '\u{66B}')
+}
+
+// "1.000", "1,000", "1 000" and "١٠٠٠" -> "1000". Separators have to be followed by three
+// digits, so "1.5" isn't silently read as 15.
+pub(crate) fn normalize_integer(input: &str) -> Option<String> {
+    let mut groups = input.trim().split(is_group_separator);
+    let mut digits = ascii_digits(groups.next()?)?;
+    for group in groups {
+        if group.chars().count() != 3 {
+            return None;
+        }
+        digits.push_str(&ascii_digits(group)?);
+    }
+    Some(digits)
+}
+
+// "85,5", "85.5", "1.000,5" and "-3,5" in a form `str::parse` accepts. A mark that appears
+// once and last is the decimal separator, every other one groups.
+pub(crate) fn normalize_decimal(input: &str) -> Option<String> {
+    let input = input.trim();
+    let (sign, body) = match input.strip_prefix(['-', '\u{2212}']) {
+        Some(body) => ("-", body),
+        None => ("", input.strip_prefix('+').unwrap_or(input)),
+    };
+
+    let last_mark = body.chars().rfind(|&c| is_decimal_mark(c));
+    let decimal_mark = last_mark.filter(|&mark| body.matches(mark).count() == 1);
+    let (integer, fraction) = match decimal_mark {
+        Some(mark) => body.rsplit_once(mark)?,
+        None => (body, ""),
+    };
+
+    let mut number = format!("{sign}{}", normalize_integer(integer)?);
+    if !fraction.is_empty() {
+        number.push('.');
+        number.push_str(&ascii_digits(fraction)?);
+    }
+    Some(number)
+}
+
+pub(crate) fn parse_decimal(input: &str) -> Option<f32> {
+    normalize_decimal(input)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_the_separator_of_the_locale() {
+        assert_eq!(decimal_separator_for("de_DE.UTF-8"), ',');
+        assert_eq!(decimal_separator_for("de-AT"), ',');
+        assert_eq!(decimal_separator_for("sr_RS@latin"), ',');
+        assert_eq!(decimal_separator_for("de_CH.UTF-8"), '.');
+        assert_eq!(decimal_separator_for("en_US.UTF-8"), '.');
+        assert_eq!(decimal_separator_for("ar_EG.UTF-8"), '.');
+        assert_eq!(decimal_separator_for("C"), '.');
+        assert_eq!(decimal_separator_for(""), '.');
+        assert_eq!(decimal_separator_for("ñ_€.ü@ö"), '.');
+    }
+
+    #[test]
+    fn localizes_the_decimal_separator() {
+        assert_eq!(localize_with("54.5".to_string(), ','), "54,5");
+        assert_eq!(localize_with("54.5".to_string(), '.'), "54.5");
+        assert_eq!(localize_with("-3".to_string(), ','), "-3");
+    }
+
+    #[test]
+    fn reads_grouped_integers() {
+        for input in ["1000", "1.000", "1,000", "1 000", "1\u{202F}000", " 1.000 "] {
+            assert_eq!(
+                normalize_integer(input).as_deref(),
+                Some("1000"),
+                "{input:?}"
+            );
+        }
+        assert_eq!(normalize_integer("1.000.000").as_deref(), Some("1000000"));
+        for input in ["", "abc", "-5", "1.5", "1000.", ".5", "1..000", "1.0000"] {
+            assert_eq!(normalize_integer(input), None, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn reads_both_decimal_separators() {
+        assert_eq!(parse_decimal("85,5"), Some(85.5));
+        assert_eq!(parse_decimal("85.5"), Some(85.5));
+        assert_eq!(parse_decimal("85"), Some(85.0));
+        assert_eq!(parse_decimal("-3,5"), Some(-3.5));
+        assert_eq!(parse_decimal("\u{2212}3.5"), Some(-3.5));
+        assert_eq!(parse_decimal("1.000,5"), Some(1000.5));
+        assert_eq!(parse_decimal("1,000.5"), Some(1000.5));
+        for input in ["", "abc", "8,5,5", "85,x", "inf", "NaN"] {
+            assert_eq!(parse_decimal(input), None, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn reads_non_ascii_digits() {
+        assert_eq!(normalize_integer("١٠٠٠").as_deref(), Some("1000"));
+        assert_eq!(normalize_integer("١٬٠٠٠").as_deref(), Some("1000"));
+        assert_eq!(normalize_integer("۱۰۰۰").as_deref(), Some("1000"));
+        assert_eq!(normalize_integer("१०००").as_deref(), Some("1000"));
+        assert_eq!(parse_decimal("٨٥٫٥"), Some(85.5));
+        assert_eq!(parse_decimal("８５"), Some(85.0));
+        // Other digits are refused, not misread
+        assert_eq!(normalize_integer("๑๐๐๐"), None);
+        assert_eq!(parse_decimal("٨٥٫"), Some(85.0));
+    }
+}
@@ -1,9 +1,51 @@
+mod alert;
+mod applet_state;
 mod config;
+mod config_writer;
+mod cpu_load;
+mod critical_action;
+mod dbus;
+mod fans;
+mod graph;
+mod history;
+mod hwmon;
+mod i18n;
+mod locale;
+mod metrics;
+mod monitor;
+mod motion;
+mod mqtt;
+mod notifications;
+mod panel_format;
+mod persist;
+mod power;
+mod quiet_hours;
+mod rapl;
+mod refresh_period;
+mod sample_log;
+mod sanity;
+mod session;
+mod settings_app;
+mod socket;
+mod sound;
+mod stats;
 mod sysinfo_utils;
+#[cfg(test)]
+mod test_dir;
+mod thermal_zone;
+mod throttle;
+mod timing;
+mod units;
+mod virtualization;
 mod window;
 
+use cosmic::cosmic_config::CosmicConfigEntry;
+
+use crate::config::{instance_config_id, CPUTempAppletConfig, SensorBackend};
+use crate::settings_app::SettingsApp;
+use crate::sysinfo_utils::{new_provider, ReadOptions};
 // Import the applet model (Window)
-use crate::window::Window;
+use crate::window::{Flags, Window};
 
 // The main function returns a cosmic::iced::Result that is returned from
 // the run function that's part of the applet module.
@@ -11,7 +53,67 @@ fn main() -> cosmic::iced::Result {
     tracing_subscriber::fmt::init();
     let _ = tracing_log::LogTracer::init();
 
-    cosmic::applet::run::<Window>(())?;
+    let requested_languages = i18n_embed::DesktopLanguageRequester::requested_languages();
+    i18n::init(&requested_languages);
+    locale::init();
+
+    // `--instance <name>` (or COSMIC_CPU_TEMP_INSTANCE) runs the applet with its own
+    // settings, so it can be added to the panel more than once
+    let instance = arg_value("--instance").or_else(|| std::env::var(config::INSTANCE_ENV).ok());
+
+    // `--list-sensors` prints what `--sensor` accepts, for scripts and bug reports
+    if std::env::args().any(|arg| arg == "--list-sensors") {
+        list_sensors(instance.as_deref());
+        return Ok(());
+    }
+
+    // `--settings` opens every option in a regular window instead of running the applet
+    if std::env::args().any(|arg| arg == settings_app::SETTINGS_ARG) {
+        let settings = cosmic::app::Settings::default().size(cosmic::iced::Size::new(560.0, 720.0));
+        cosmic::app::run::<SettingsApp>(settings, instance)?;
+        return Ok(());
+    }
+
+    // `--sensor <label>` shows that sensor instead of the configured one, without saving it
+    let sensor = arg_value("--sensor");
+    cosmic::applet::run::<Window>(Flags { instance, sensor })?;
 
     Ok(())
 }
+
+// `<name> <value>` or `<name>=<value>`
+fn arg_value(name: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == name {
+            return args.next();
+        }
+        if let Some(value) = arg
+            .strip_prefix(name)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+// One sensor per line, the label followed by the driver when it's known. Uses the backend
+// of the instance, the labels differ between them.
+fn list_sensors(instance: Option<&str>) {
+    let backend = cosmic::cosmic_config::Config::new(
+        &instance_config_id(window::ID, instance),
+        CPUTempAppletConfig::VERSION,
+    )
+    .ok()
+    .and_then(|helper| helper.get::<SensorBackend>("sensor_backend").ok())
+    .unwrap_or_default();
+
+    let reading = new_provider(backend).read(&ReadOptions::default());
+    for label in &reading.labels {
+        match reading.chips.iter().find(|(other, _)| other == label) {
+            Some((_, chip)) => println!("{label}\t{chip}"),
+            None => println!("{label}"),
+        }
+    }
+}
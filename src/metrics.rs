@@ -0,0 +1,148 @@
+use std::fmt::Write;
+use std::net::Ipv4Addr;
+use std::sync::{Arc, Mutex, PoisonError};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time;
+
+// A scrape that doesn't finish in time is dropped, so a stuck client can't block the others
+const REQUEST_TIMEOUT: time::Duration = time::Duration::from_secs(5);
+// Only the request line is looked at
+const MAX_REQUEST_BYTES: usize = 1024;
+
+// Values of the last reading, shared with the server
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Metrics {
+    // `None` while there is no fresh value, the gauge is left out then
+    pub(crate) cpu: Option<f32>,
+    // Label and temperature of every per-core (or CCD) sensor
+    pub(crate) cores: Vec<(String, f32)>,
+}
+
+// Prometheus text format, version 0.0.4
+pub(crate) fn render(metrics: &Metrics) -> String {
+    let mut text = String::new();
+
+    let _ = writeln!(
+        text,
+        "# HELP cpu_temperature_celsius CPU temperature as shown by the applet."
+    );
+    let _ = writeln!(text, "# TYPE cpu_temperature_celsius gauge");
+    if let Some(celsius) = metrics.cpu {
+        let _ = writeln!(text, "cpu_temperature_celsius {celsius}");
+    }
+
+    if !metrics.cores.is_empty() {
+        let _ = writeln!(
+            text,
+            "# HELP cpu_core_temperature_celsius Temperature of each CPU core or CCD sensor."
+        );
+        let _ = writeln!(text, "# TYPE cpu_core_temperature_celsius gauge");
+        for (label, celsius) in &metrics.cores {
+            let _ = writeln!(
+                text,
+                "cpu_core_temperature_celsius{{sensor=\"{}\"}} {celsius}",
+                escape_label_value(label)
+            );
+        }
+    }
+
+    text
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// Serves `/metrics` on the loopback interface until the future is dropped, which happens
+// when the subscription running it goes away (metrics disabled or another port)
+pub(crate) async fn serve(port: u16, metrics: Arc<Mutex<Metrics>>) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::error!(?err, port, "Error starting the metrics server");
+            return;
+        }
+    };
+
+    loop {
+        let mut stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                tracing::error!(?err, "Error accepting a metrics connection");
+                // Out of file descriptors or similar, don't spin on it
+                time::sleep(time::Duration::from_secs(1)).await;
+                continue;
+            }
+        };
+
+        // One scrape at a time is plenty
+        match time::timeout(REQUEST_TIMEOUT, respond(&mut stream, &metrics)).await {
+            Ok(Err(err)) => tracing::error!(?err, "Error answering a metrics request"),
+            Err(_) => tracing::error!("Metrics request timed out"),
+            Ok(Ok(())) => {}
+        }
+    }
+}
+
+async fn respond(stream: &mut TcpStream, metrics: &Mutex<Metrics>) -> std::io::Result<()> {
+    let mut request = [0; MAX_REQUEST_BYTES];
+    let length = stream.read(&mut request).await?;
+    let request = String::from_utf8_lossy(&request[..length]);
+
+    let mut request_line = request.split_whitespace();
+    let method = request_line.next();
+    let path = request_line
+        .next()
+        .map(|target| target.split('?').next().unwrap_or(target));
+
+    let response = if method == Some("GET") && path == Some("/metrics") {
+        let body = render(&metrics.lock().unwrap_or_else(PoisonError::into_inner));
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+    };
+
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_cpu_and_core_gauges() {
+        let metrics = Metrics {
+            cpu: Some(54.5),
+            cores: vec![("Core 0".to_string(), 50.0), ("Core 1".to_string(), 54.5)],
+        };
+
+        let text = render(&metrics);
+        assert!(text.contains("# TYPE cpu_temperature_celsius gauge\n"));
+        assert!(text.contains("\ncpu_temperature_celsius 54.5\n"));
+        assert!(text.contains("\ncpu_core_temperature_celsius{sensor=\"Core 0\"} 50\n"));
+        assert!(text.contains("\ncpu_core_temperature_celsius{sensor=\"Core 1\"} 54.5\n"));
+    }
+
+    #[test]
+    fn leaves_out_missing_values() {
+        let text = render(&Metrics::default());
+
+        assert!(!text.contains("\ncpu_temperature_celsius "));
+        assert!(!text.contains("cpu_core_temperature_celsius"));
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape_label_value(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape_label_value("a\nb"), "a\\nb");
+    }
+}
@@ -0,0 +1,50 @@
+use std::os::unix::process::CommandExt;
+use std::process::{Command, Stdio};
+
+// Tried in order when no command is configured
+const DEFAULT_MONITORS: &[&str] = &["cosmic-monitor", "gnome-system-monitor"];
+
+fn is_installed(program: &str) -> bool {
+    let Some(path) = std::env::var_os("PATH") else {
+        return false;
+    };
+    std::env::split_paths(&path).any(|dir| dir.join(program).is_file())
+}
+
+// First default monitor that is installed
+pub(crate) fn find_default() -> Option<&'static str> {
+    DEFAULT_MONITORS
+        .iter()
+        .copied()
+        .find(|program| is_installed(program))
+}
+
+// Starts the command through the shell (so "alacritty -e btop" works) without waiting for
+// it. It gets its own process group, so it outlives the applet and isn't hit by signals
+// meant for the panel.
+pub(crate) fn launch(command: &str) -> Result<(), String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()
+        .map_err(|err| {
+            tracing::error!(?err, %command, "Failed to launch system monitor");
+            err.to_string()
+        })?;
+
+    // Reaps the process once it exits, otherwise it stays around as a zombie
+    let command = command.to_string();
+    std::thread::spawn(move || match child.wait() {
+        Ok(status) if !status.success() => {
+            tracing::error!(%status, %command, "System monitor exited with an error");
+        }
+        Ok(_) => {}
+        Err(err) => tracing::error!(?err, %command, "Failed to wait for system monitor"),
+    });
+
+    Ok(())
+}
@@ -0,0 +1,102 @@
+// Whether anything in the applet may move, for users who get dizzy from blinking and
+// transitions. Everything that changes the look on its own (the critical flash so far) asks
+// `Motion::animate` and shows a steady variant otherwise.
+
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::{SinkExt, StreamExt};
+
+// Where the desktop portal keeps the setting, GNOME's key is what GTK and COSMIC read
+const INTERFACE_NAMESPACE: &str = "org.gnome.desktop.interface";
+const ENABLE_ANIMATIONS_KEY: &str = "enable-animations";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Motion {
+    // The desktop's accessibility setting
+    pub(crate) reduced_by_desktop: bool,
+    // `disable_animations` in the config, for desktops without the setting
+    pub(crate) disabled_by_config: bool,
+}
+
+impl Motion {
+    pub(crate) fn animate(self) -> bool {
+        !self.reduced_by_desktop && !self.disabled_by_config
+    }
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.portal.Settings",
+    default_service = "org.freedesktop.portal.Desktop",
+    default_path = "/org/freedesktop/portal/desktop"
+)]
+trait PortalSettings {
+    fn read_one(&self, namespace: &str, key: &str) -> zbus::Result<zbus::zvariant::OwnedValue>;
+
+    #[zbus(signal)]
+    fn setting_changed(
+        &self,
+        namespace: &str,
+        key: &str,
+        value: zbus::zvariant::Value<'_>,
+    ) -> zbus::Result<()>;
+}
+
+async fn try_watch(output: &mut mpsc::Sender<bool>) -> zbus::Result<()> {
+    let connection = zbus::Connection::session().await?;
+    let settings = PortalSettingsProxy::new(&connection).await?;
+    let mut changes = settings.receive_setting_changed().await?;
+
+    let enabled = bool::try_from(
+        settings
+            .read_one(INTERFACE_NAMESPACE, ENABLE_ANIMATIONS_KEY)
+            .await?,
+    )?;
+    if output.send(!enabled).await.is_err() {
+        return Ok(());
+    }
+
+    while let Some(change) = changes.next().await {
+        let args = change.args()?;
+        if args.namespace() != &INTERFACE_NAMESPACE || args.key() != &ENABLE_ANIMATIONS_KEY {
+            continue;
+        }
+        let Ok(enabled) = args.value().downcast_ref::<bool>() else {
+            continue;
+        };
+        if output.send(!enabled).await.is_err() {
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+// Sends whether the desktop asks for reduced motion, first the current state and then every
+// change. Without the portal (or the setting) animations stay on until the applet restarts,
+// so failures are only logged.
+pub(crate) async fn watch(mut output: mpsc::Sender<bool>) {
+    if let Err(err) = try_watch(&mut output).await {
+        tracing::warn!(?err, "Failed reading the desktop's animation setting");
+    }
+
+    std::future::pending::<()>().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn either_setting_stops_animations() {
+        assert!(Motion::default().animate());
+        assert!(!Motion {
+            reduced_by_desktop: true,
+            disabled_by_config: false,
+        }
+        .animate());
+        assert!(!Motion {
+            reduced_by_desktop: false,
+            disabled_by_config: true,
+        }
+        .animate());
+    }
+}
@@ -0,0 +1,198 @@
+// Optional publishing of the CPU temperature to an MQTT broker, for home automation
+// dashboards. Every message is a small JSON object on the configured topic:
+//
+// {"celsius":54.2,"sensor":"Package id 0"}
+//
+// The client runs as long as its subscription does, so turning the option off or changing
+// the broker drops the connection. A lost connection is retried with a growing delay, the
+// samples in the meantime are dropped.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::SinkExt;
+use rumqttc::{AsyncClient, Event as MqttEvent, MqttOptions, Packet, QoS};
+use serde::Serialize;
+
+const DEFAULT_PORT: u16 = 1883;
+const URL_SCHEME: &str = "mqtt://";
+const KEEP_ALIVE: Duration = Duration::from_secs(30);
+// Samples waiting to be sent, newer ones are dropped once it's full
+const QUEUE_CAPACITY: usize = 10;
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Serialize)]
+struct Payload<'a> {
+    celsius: f32,
+    sensor: &'a str,
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Publisher {
+    client: AsyncClient,
+    topic: String,
+}
+
+impl Publisher {
+    // Never waits, a sample that doesn't fit in the queue is dropped. Returns false once the
+    // client is gone.
+    pub(crate) fn publish(&self, sensor: &str, celsius: f32) -> bool {
+        // Serializing a string and a number can't fail
+        let payload = serde_json::to_vec(&Payload { celsius, sensor }).unwrap_or_default();
+        match self
+            .client
+            .try_publish(&self.topic, QoS::AtMostOnce, false, payload)
+        {
+            Ok(()) => true,
+            Err(rumqttc::ClientError::TryRequest(_)) => {
+                tracing::debug!("MQTT queue full, dropping a sample");
+                true
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Event {
+    // Where the samples go, replaced whenever the client is started over
+    Ready(Publisher),
+    // The settings can't work (bad broker address, unreadable credentials), retrying
+    // wouldn't help
+    Failed(String),
+}
+
+// "mqtt://broker.lan:1884" -> ("broker.lan", 1884), the scheme and the port are optional
+fn parse_broker(url: &str) -> Result<(String, u16), String> {
+    let address = url.trim();
+    let address = address.strip_prefix(URL_SCHEME).unwrap_or(address);
+    let address = address.trim_end_matches('/');
+
+    let (host, port) = match address.rsplit_once(':') {
+        Some((host, port)) => (
+            host,
+            port.parse::<u16>()
+                .map_err(|_| format!("Invalid port \"{port}\""))?,
+        ),
+        None => (address, DEFAULT_PORT),
+    };
+    if host.is_empty() || host.contains(['/', ' ']) {
+        return Err(format!("Invalid broker \"{url}\""));
+    }
+
+    Ok((host.to_string(), port))
+}
+
+// User name on the first line, password on the second, so neither ends up in the config
+fn parse_credentials(contents: &str) -> Option<(String, String)> {
+    let mut lines = contents.lines();
+    let username = lines.next()?.trim();
+    let password = lines.next().unwrap_or_default().trim_end();
+    (!username.is_empty()).then(|| (username.to_string(), password.to_string()))
+}
+
+async fn options(broker: &str, credentials_file: Option<&Path>) -> Result<MqttOptions, String> {
+    let (host, port) = parse_broker(broker)?;
+    // Unique per process, the broker disconnects the older client if two use the same ID
+    let client_id = format!("cosmic-ext-applet-cpu-temperature-{}", std::process::id());
+    let mut options = MqttOptions::new(client_id, host, port);
+    options.set_keep_alive(KEEP_ALIVE);
+
+    if let Some(path) = credentials_file {
+        let contents = tokio::fs::read_to_string(path)
+            .await
+            .map_err(|err| format!("{}: {err}", path.display()))?;
+        let (username, password) = parse_credentials(&contents)
+            .ok_or_else(|| format!("{}: no user name", path.display()))?;
+        options.set_credentials(username, password);
+    }
+
+    Ok(options)
+}
+
+// Keeps the connection to the broker up until the future is dropped. Only the first failure
+// in a row is logged, a broker that is down for the night shouldn't fill the journal.
+pub(crate) async fn run(
+    broker: String,
+    topic: String,
+    credentials_file: Option<PathBuf>,
+    mut output: mpsc::Sender<Event>,
+) {
+    let options = match options(&broker, credentials_file.as_deref()).await {
+        Ok(options) => options,
+        Err(err) => {
+            tracing::error!(%err, "Invalid MQTT settings");
+            let _ = output.send(Event::Failed(err)).await;
+            // Nothing to connect to until the settings are changed, which runs this again
+            std::future::pending::<()>().await;
+            return;
+        }
+    };
+
+    let (client, mut event_loop) = AsyncClient::new(options, QUEUE_CAPACITY);
+    let _ = output.send(Event::Ready(Publisher { client, topic })).await;
+
+    let mut delay = RETRY_DELAY;
+    let mut failing = false;
+    loop {
+        match event_loop.poll().await {
+            Ok(MqttEvent::Incoming(Packet::ConnAck(_))) => {
+                if failing {
+                    tracing::info!(broker, "Reconnected to the MQTT broker");
+                }
+                failing = false;
+                delay = RETRY_DELAY;
+            }
+            Ok(_) => {}
+            // The next poll connects again
+            Err(err) => {
+                if !failing {
+                    tracing::warn!(?err, broker, "MQTT connection failed, retrying");
+                }
+                failing = true;
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(RETRY_MAX_DELAY);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_broker_addresses() {
+        assert_eq!(
+            parse_broker("mqtt://broker.lan:1884"),
+            Ok(("broker.lan".to_string(), 1884))
+        );
+        assert_eq!(
+            parse_broker("192.168.1.5"),
+            Ok(("192.168.1.5".to_string(), DEFAULT_PORT))
+        );
+        assert_eq!(
+            parse_broker(" mqtt://localhost/ "),
+            Ok(("localhost".to_string(), DEFAULT_PORT))
+        );
+        assert!(parse_broker("mqtt://broker.lan:port").is_err());
+        assert!(parse_broker("mqtt://").is_err());
+        assert!(parse_broker("http://broker.lan").is_err());
+    }
+
+    #[test]
+    fn parses_credentials_file() {
+        assert_eq!(
+            parse_credentials("homeassistant\nsecret pass\n"),
+            Some(("homeassistant".to_string(), "secret pass".to_string()))
+        );
+        assert_eq!(
+            parse_credentials("homeassistant\n"),
+            Some(("homeassistant".to_string(), String::new()))
+        );
+        assert_eq!(parse_credentials("\nsecret"), None);
+        assert_eq!(parse_credentials(""), None);
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+
+use zbus::zvariant::Value;
+
+use crate::fl;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.Notifications",
+    default_service = "org.freedesktop.Notifications",
+    default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+    #[allow(clippy::too_many_arguments)]
+    fn notify(
+        &self,
+        app_name: &str,
+        replaces_id: u32,
+        app_icon: &str,
+        summary: &str,
+        body: &str,
+        actions: &[&str],
+        hints: HashMap<&str, Value<'_>>,
+        expire_timeout: i32,
+    ) -> zbus::Result<u32>;
+}
+
+async fn try_send_critical(summary: &str, body: &str) -> zbus::Result<()> {
+    let connection = zbus::Connection::session().await?;
+    let proxy = NotificationsProxy::new(&connection).await?;
+
+    let mut hints = HashMap::new();
+    // Critical urgency
+    hints.insert("urgency", Value::U8(2));
+
+    proxy
+        .notify(
+            &fl!("app-name"),
+            0,
+            "thermometer-symbolic",
+            summary,
+            body,
+            &[],
+            hints,
+            -1,
+        )
+        .await?;
+
+    Ok(())
+}
+
+// Errors are only logged, a missing notification daemon shouldn't affect the applet
+pub(crate) async fn send_critical(summary: String, body: String) {
+    if let Err(err) = try_send_critical(&summary, &body).await {
+        tracing::error!(?err, "Failed sending notification");
+    }
+}
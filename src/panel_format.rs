@@ -0,0 +1,124 @@
+// The panel provides `{temp}` (the number) and `{unit}` (e.g. "°C").
+// Shows the value followed by the unit symbol, e.g. "45°C"
+pub(crate) const DEFAULT_PANEL_FORMAT: &str = "{temp}{unit}";
+
+// Replaces `{name}` with the matching value. Anything that isn't a known placeholder,
+// including unknown names and unmatched braces, is kept as is.
+pub(crate) fn format(template: &str, values: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let candidate = &rest[start + 1..];
+
+        let value = candidate.find('}').and_then(|end| {
+            let name = &candidate[..end];
+            values
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (*value, end))
+        });
+
+        match value {
+            Some((value, end)) => {
+                output.push_str(value);
+                rest = &candidate[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = candidate;
+            }
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+// For vertical panels too narrow for the text on one line. Every space separated part goes
+// on its own line and numbers are split from their unit, "100°C 34%" becomes
+// "100\n°C\n34\n%". The "/" between two temperatures is dropped, the lines keep them apart.
+pub(crate) fn stack_lines(text: &str) -> String {
+    text.split_whitespace()
+        .filter(|part| *part != "/")
+        .flat_map(|part| {
+            let number_end = part
+                .find(|c: char| !(c.is_ascii_digit() || matches!(c, '.' | ',' | '-')))
+                .unwrap_or(part.len());
+            if number_end == 0 || number_end == part.len() {
+                [part, ""]
+            } else {
+                [&part[..number_end], &part[number_end..]]
+            }
+        })
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALUES: &[(&str, &str)] = &[("temp", "45"), ("unit", "°C")];
+
+    #[test]
+    fn default_format_shows_value_and_unit() {
+        assert_eq!(format(DEFAULT_PANEL_FORMAT, VALUES), "45°C");
+    }
+
+    #[test]
+    fn keeps_surrounding_text() {
+        assert_eq!(format("CPU {temp}°", VALUES), "CPU 45°");
+        assert_eq!(format("{temp}", VALUES), "45");
+    }
+
+    #[test]
+    fn repeats_placeholders() {
+        assert_eq!(format("{temp} {temp}", VALUES), "45 45");
+    }
+
+    #[test]
+    fn keeps_unknown_placeholders() {
+        assert_eq!(format("{load} {temp}", VALUES), "{load} 45");
+        assert_eq!(format("{}", VALUES), "{}");
+    }
+
+    #[test]
+    fn keeps_unmatched_braces() {
+        assert_eq!(format("{temp", VALUES), "{temp");
+        assert_eq!(format("temp}", VALUES), "temp}");
+        assert_eq!(format("{{temp}}", VALUES), "{45}");
+    }
+
+    #[test]
+    fn handles_multi_byte_text() {
+        assert_eq!(format("🌡{temp}{unit}", VALUES), "🌡45°C");
+    }
+
+    #[test]
+    fn empty_template_is_empty() {
+        assert_eq!(format("", VALUES), "");
+    }
+
+    #[test]
+    fn stacks_number_and_unit() {
+        assert_eq!(stack_lines("100°C"), "100\n°C");
+        assert_eq!(stack_lines("-5.5°F"), "-5.5\n°F");
+        assert_eq!(stack_lines("62° 34%"), "62\n°\n34\n%");
+    }
+
+    #[test]
+    fn stacks_every_part() {
+        assert_eq!(stack_lines("54°C / 61°C"), "54\n°C\n61\n°C");
+        assert_eq!(stack_lines("CPU --"), "CPU\n--");
+        assert_eq!(stack_lines("100"), "100");
+    }
+
+    #[test]
+    fn stacks_multi_byte_text() {
+        assert_eq!(stack_lines("🌡 45°C"), "🌡\n45\n°C");
+        assert_eq!(stack_lines("45°C🔥"), "45\n°C🔥");
+    }
+}
@@ -0,0 +1,213 @@
+// Optional snapshot of the history graph and the peak, so they survive restarts. Each applet
+// instance has its own file, `$XDG_STATE_HOME/cosmic-applet-cpu-temperature/<config id>.json`
+// (`~/.local/state/...` without XDG_STATE_HOME):
+//
+// {
+//   "version": 1,
+//   "saved_at": 1760000000.0,
+//   "peak_celsius": 92.1,
+//   "samples": [[1759999700.5, 54.2], [1759999701.5, 55.0]]
+// }
+//
+// Times are Unix timestamps in seconds, temperatures are Celsius. `peak_celsius` is null
+// until there was a reading, `samples` is oldest first and covers the history graph's
+// window. Files that don't parse or have another version are ignored.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::SinkExt;
+use serde::{Deserialize, Serialize};
+use tokio::signal::unix::{signal, SignalKind};
+
+use crate::history::{TempHistory, HISTORY_WINDOW};
+
+const STATE_DIR_NAME: &str = "cosmic-applet-cpu-temperature";
+// Bumped whenever the format changes, older files are dropped instead of migrated
+const SNAPSHOT_VERSION: u32 = 1;
+// Saved this often while running, and once more on exit
+pub(crate) const SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    version: u32,
+    saved_at: f64,
+    peak_celsius: Option<f32>,
+    samples: Vec<(f64, f32)>,
+}
+
+impl Snapshot {
+    pub(crate) fn new(history: &TempHistory, peak: Option<f32>) -> Self {
+        let (now, now_unix) = now();
+        Self {
+            version: SNAPSHOT_VERSION,
+            saved_at: now_unix,
+            peak_celsius: peak,
+            samples: history
+                .samples()
+                .map(|(time, celsius)| {
+                    (now_unix - now.duration_since(*time).as_secs_f64(), *celsius)
+                })
+                .collect(),
+        }
+    }
+
+    pub(crate) fn peak(&self) -> Option<f32> {
+        self.peak_celsius
+    }
+
+    // Samples that still fall into the history window, with their time on this run's clock.
+    // Samples from the future (the wall clock went back) are dropped.
+    pub(crate) fn samples(&self) -> Vec<(Instant, f32)> {
+        let (now, now_unix) = now();
+        self.samples
+            .iter()
+            .filter_map(|(unix, celsius)| {
+                let age = Duration::try_from_secs_f64(now_unix - unix).ok()?;
+                (age <= HISTORY_WINDOW).then_some((now.checked_sub(age)?, *celsius))
+            })
+            .collect()
+    }
+}
+
+fn now() -> (Instant, f64) {
+    let unix = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs_f64();
+    (Instant::now(), unix)
+}
+
+// `None` if neither XDG_STATE_HOME nor HOME is set
+pub(crate) fn state_dir() -> Option<PathBuf> {
+    let state_home = std::env::var_os("XDG_STATE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+
+    Some(state_home.join(STATE_DIR_NAME))
+}
+
+pub(crate) fn snapshot_path(config_id: &str) -> Option<PathBuf> {
+    Some(state_dir()?.join(format!("{config_id}.json")))
+}
+
+// `None` for missing, corrupt and version-mismatched files
+pub(crate) fn load(path: &Path) -> Option<Snapshot> {
+    let text = fs::read_to_string(path).ok()?;
+    match serde_json::from_str::<Snapshot>(&text) {
+        Ok(snapshot) if snapshot.version == SNAPSHOT_VERSION => Some(snapshot),
+        Ok(snapshot) => {
+            tracing::warn!(
+                version = snapshot.version,
+                "Ignoring history of another version"
+            );
+            None
+        }
+        Err(err) => {
+            tracing::warn!(?err, "Ignoring unreadable history");
+            None
+        }
+    }
+}
+
+// Written to a temporary file first, so a crash midway never leaves half a file behind
+pub(crate) fn save(path: &Path, snapshot: &Snapshot) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let text = serde_json::to_string(snapshot).map_err(io::Error::other)?;
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, text)?;
+    fs::rename(temp_path, path)
+}
+
+// Sends once when the applet is asked to quit, so the history and the sample log can be
+// saved first. Tokio never gives the signals back to their default action, so this runs for
+// the whole process and the applet has to exit on every message, whatever is enabled.
+pub(crate) async fn watch_exit(mut output: mpsc::Sender<()>) {
+    let signals = [
+        SignalKind::terminate(),
+        SignalKind::interrupt(),
+        SignalKind::hangup(),
+    ]
+    .map(signal);
+    match signals {
+        [Ok(mut terminate), Ok(mut interrupt), Ok(mut hangup)] => {
+            tokio::select! {
+                _ = terminate.recv() => {},
+                _ = interrupt.recv() => {},
+                _ = hangup.recv() => {},
+            }
+            let _ = output.send(()).await;
+        }
+        _ => tracing::error!("Failed listening for exit signals"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn round_trips_history_and_peak() {
+        let dir = TestDir::new("persist");
+        let path = dir.join("round-trip.json");
+        let mut history = TempHistory::default();
+        history.push(54.5);
+
+        save(&path, &Snapshot::new(&history, Some(92.1))).unwrap();
+        let snapshot = load(&path).unwrap();
+
+        assert_eq!(snapshot.peak(), Some(92.1));
+        let samples = snapshot.samples();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].1, 54.5);
+    }
+
+    #[test]
+    fn ignores_corrupt_files() {
+        let dir = TestDir::new("persist");
+        dir.write("corrupt.json", "{\"version\": 1, \"samples\": [");
+
+        assert!(load(&dir.join("corrupt.json")).is_none());
+        assert!(load(&dir.join("missing.json")).is_none());
+    }
+
+    #[test]
+    fn ignores_other_versions() {
+        let dir = TestDir::new("persist");
+        dir.write(
+            "version.json",
+            r#"{"version": 999, "saved_at": 0.0, "peak_celsius": null, "samples": []}"#,
+        );
+
+        assert!(load(&dir.join("version.json")).is_none());
+    }
+
+    #[test]
+    fn drops_samples_outside_the_window() {
+        let (_, now_unix) = now();
+        let snapshot = Snapshot {
+            version: SNAPSHOT_VERSION,
+            saved_at: now_unix,
+            peak_celsius: None,
+            samples: vec![
+                (now_unix - HISTORY_WINDOW.as_secs_f64() - 60.0, 40.0),
+                (now_unix - 10.0, 50.0),
+                (now_unix + 3600.0, 60.0),
+            ],
+        };
+
+        let samples = snapshot.samples();
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].1, 50.0);
+    }
+}
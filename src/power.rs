@@ -0,0 +1,41 @@
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::{SinkExt, StreamExt};
+
+#[zbus::proxy(
+    interface = "org.freedesktop.UPower",
+    default_service = "org.freedesktop.UPower",
+    default_path = "/org/freedesktop/UPower"
+)]
+trait UPower {
+    #[zbus(property)]
+    fn on_battery(&self) -> zbus::Result<bool>;
+}
+
+async fn try_watch(output: &mut mpsc::Sender<bool>) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let upower = UPowerProxy::new(&connection).await?;
+    let mut changes = upower.receive_on_battery_changed().await;
+
+    if output.send(upower.on_battery().await?).await.is_err() {
+        return Ok(());
+    }
+
+    while let Some(change) = changes.next().await {
+        if output.send(change.get().await?).await.is_err() {
+            return Ok(());
+        }
+    }
+
+    Ok(())
+}
+
+// Sends whether the machine runs on battery, first the current state and then every change.
+// Without UPower the machine is treated as being on AC for as long as the applet runs, so
+// failures are only logged.
+pub(crate) async fn watch(mut output: mpsc::Sender<bool>) {
+    if let Err(err) = try_watch(&mut output).await {
+        tracing::error!(?err, "Failed watching the power supply state");
+    }
+
+    std::future::pending::<()>().await;
+}
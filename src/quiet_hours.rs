@@ -0,0 +1,142 @@
+// Critical notifications and sounds are held back during the configured night, so a backup
+// job heating the machine doesn't wake the room. The panel still changes, and a single
+// summary follows once the window is over if anything critical happened in it.
+
+use chrono::{Local, Timelike};
+
+const MINUTES_PER_DAY: u16 = 24 * 60;
+
+// Times of day in minutes after midnight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct QuietHours {
+    start: u16,
+    end: u16,
+}
+
+impl QuietHours {
+    // `None` unless both ends are set and differ
+    pub(crate) fn new(start: Option<u16>, end: Option<u16>) -> Option<Self> {
+        let (start, end) = (start?, end?);
+        (start != end && start < MINUTES_PER_DAY && end < MINUTES_PER_DAY)
+            .then_some(Self { start, end })
+    }
+
+    // The window may cross midnight, 22:00 to 07:00 contains 23:30 and 06:59 but not 07:00
+    pub(crate) fn contains(self, minute: u16) -> bool {
+        if self.start < self.end {
+            (self.start..self.end).contains(&minute)
+        } else {
+            minute >= self.start || minute < self.end
+        }
+    }
+
+    pub(crate) fn end(self) -> u16 {
+        self.end
+    }
+}
+
+pub(crate) fn minute_of_day_now() -> u16 {
+    let now = Local::now();
+    // At most 1439
+    (now.hour() * 60 + now.minute()) as u16
+}
+
+// "7:00" or "07:00"
+pub(crate) fn parse_time(input: &str) -> Option<u16> {
+    let (hours, minutes) = input.trim().split_once(':')?;
+    if hours.is_empty() || hours.len() > 2 || minutes.len() != 2 {
+        return None;
+    }
+    let hours = hours.parse::<u16>().ok().filter(|hours| *hours < 24)?;
+    let minutes = minutes
+        .parse::<u16>()
+        .ok()
+        .filter(|minutes| *minutes < 60)?;
+    Some(hours * 60 + minutes)
+}
+
+// "07:00"
+pub(crate) fn format_time(minute: u16) -> String {
+    format!("{:02}:{:02}", minute / 60 % 24, minute % 60)
+}
+
+// Remembers the critical alerts held back during quiet hours
+#[derive(Debug, Default)]
+pub(crate) struct MutedAlerts {
+    // Hottest reading since the first held back alert
+    peak: Option<f32>,
+}
+
+impl MutedAlerts {
+    pub(crate) fn mute(&mut self, celsius: f32) {
+        self.peak = Some(self.peak.map_or(celsius, |peak| peak.max(celsius)));
+    }
+
+    // Called with every reading. Returns the hottest one of the quiet hours once they're over,
+    // if an alert was held back in them.
+    pub(crate) fn update(&mut self, quiet: bool, celsius: f32) -> Option<f32> {
+        if !quiet {
+            return self.peak.take();
+        }
+        if self.peak.is_some() {
+            self.mute(celsius);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window_can_cross_midnight() {
+        let night = QuietHours::new(Some(22 * 60), Some(7 * 60)).unwrap();
+        assert!(night.contains(22 * 60));
+        assert!(night.contains(23 * 60 + 30));
+        assert!(night.contains(0));
+        assert!(night.contains(6 * 60 + 59));
+        assert!(!night.contains(7 * 60));
+        assert!(!night.contains(12 * 60));
+        assert!(!night.contains(21 * 60 + 59));
+
+        let lunch = QuietHours::new(Some(12 * 60), Some(13 * 60)).unwrap();
+        assert!(lunch.contains(12 * 60 + 30));
+        assert!(!lunch.contains(13 * 60));
+        assert!(!lunch.contains(0));
+    }
+
+    #[test]
+    fn needs_both_ends() {
+        assert_eq!(QuietHours::new(None, Some(7 * 60)), None);
+        assert_eq!(QuietHours::new(Some(22 * 60), None), None);
+        assert_eq!(QuietHours::new(Some(60), Some(60)), None);
+        assert_eq!(QuietHours::new(Some(60), Some(MINUTES_PER_DAY)), None);
+    }
+
+    #[test]
+    fn parses_times_of_day() {
+        assert_eq!(parse_time("07:00"), Some(7 * 60));
+        assert_eq!(parse_time("7:05"), Some(7 * 60 + 5));
+        assert_eq!(parse_time(" 23:59 "), Some(MINUTES_PER_DAY - 1));
+        for input in ["", "7", "24:00", "12:60", "12:5", ":30", "123:00", "ab:cd"] {
+            assert_eq!(parse_time(input), None, "{input:?}");
+        }
+        assert_eq!(format_time(7 * 60), "07:00");
+        assert_eq!(format_time(23 * 60 + 5), "23:05");
+    }
+
+    #[test]
+    fn summarizes_once_quiet_hours_end() {
+        let mut muted = MutedAlerts::default();
+        assert_eq!(muted.update(true, 80.0), None);
+        muted.mute(95.0);
+        assert_eq!(muted.update(true, 98.0), None);
+        assert_eq!(muted.update(true, 70.0), None);
+        assert_eq!(muted.update(false, 60.0), Some(98.0));
+        assert_eq!(muted.update(false, 60.0), None);
+        // Nothing held back, nothing to summarize
+        assert_eq!(muted.update(true, 90.0), None);
+        assert_eq!(muted.update(false, 60.0), None);
+    }
+}
@@ -0,0 +1,198 @@
+// Package power from the RAPL energy counters in /sys/class/powercap. The kernel exposes
+// them as `intel-rapl:<n>` zones on AMD (Zen and later) as well, one per socket. Watts are
+// the energy used between two ticks divided by the time in between, so like the CPU load
+// the first sample has no value.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+pub(crate) const POWERCAP_ROOT: &str = "/sys/class/powercap";
+
+// A package zone, sub-zones like "intel-rapl:0:0" (cores) are part of it
+#[derive(Debug)]
+struct Zone {
+    energy: PathBuf,
+    // The counter wraps around to 0 after this many microjoules
+    max_energy_range: u64,
+    last_energy: Option<u64>,
+}
+
+#[derive(Debug)]
+pub(crate) struct RaplMeter {
+    root: PathBuf,
+    // `None` until the first sample looked for them
+    zones: Option<Vec<Zone>>,
+    last_sample: Option<Instant>,
+}
+
+impl Default for RaplMeter {
+    fn default() -> Self {
+        Self::new(Path::new(POWERCAP_ROOT))
+    }
+}
+
+// Microjoules used between two counter values, the counter wrapped around in between when
+// it went down
+fn energy_delta(previous: u64, current: u64, max_energy_range: u64) -> u64 {
+    if current >= previous {
+        current - previous
+    } else {
+        max_energy_range.saturating_sub(previous) + current
+    }
+}
+
+fn read_counter(path: &Path) -> io::Result<u64> {
+    fs::read_to_string(path)?
+        .trim()
+        .parse()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+// "intel-rapl:0" but not "intel-rapl:0:1" or "intel-rapl" (the control type itself)
+fn is_top_level_zone(name: &str) -> bool {
+    name.split_once(':')
+        .is_some_and(|(_, index)| index.parse::<u32>().is_ok())
+}
+
+fn find_zones(root: &Path) -> Result<Vec<Zone>, String> {
+    let entries = fs::read_dir(root).map_err(|err| format!("{}: {err}", root.display()))?;
+    let mut dirs: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_str().is_some_and(is_top_level_zone))
+        .map(|entry| entry.path())
+        .filter(|dir| {
+            fs::read_to_string(dir.join("name"))
+                .is_ok_and(|name| name.trim().starts_with("package"))
+        })
+        .collect();
+    dirs.sort();
+
+    if dirs.is_empty() {
+        return Err(format!("{}: no package zone", root.display()));
+    }
+
+    Ok(dirs
+        .into_iter()
+        .map(|dir| Zone {
+            energy: dir.join("energy_uj"),
+            max_energy_range: read_counter(&dir.join("max_energy_range_uj")).unwrap_or(u64::MAX),
+            last_energy: None,
+        })
+        .collect())
+}
+
+impl RaplMeter {
+    pub(crate) fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            zones: None,
+            last_sample: None,
+        }
+    }
+
+    // Watts of all packages together. `Ok(None)` on the first call, `Err` with the reason when
+    // the counters are missing or unreadable (only root can read them on most kernels).
+    pub(crate) fn sample(&mut self) -> Result<Option<f32>, String> {
+        self.sample_at(Instant::now())
+    }
+
+    fn sample_at(&mut self, now: Instant) -> Result<Option<f32>, String> {
+        // Put back once all counters were read. Otherwise they're looked for again next
+        // time, the permissions might get fixed.
+        let mut zones = match self.zones.take() {
+            Some(zones) => zones,
+            None => find_zones(&self.root)?,
+        };
+
+        let mut used = 0;
+        let mut complete = true;
+        for zone in zones.iter_mut() {
+            let energy = match read_counter(&zone.energy) {
+                Ok(energy) => energy,
+                Err(err) => {
+                    self.last_sample = None;
+                    return Err(format!("{}: {err}", zone.energy.display()));
+                }
+            };
+            match zone.last_energy.replace(energy) {
+                Some(previous) => used += energy_delta(previous, energy, zone.max_energy_range),
+                None => complete = false,
+            }
+        }
+
+        self.zones = Some(zones);
+
+        let elapsed = self
+            .last_sample
+            .replace(now)
+            .map(|last_sample| now.duration_since(last_sample).as_secs_f64())
+            .filter(|elapsed| *elapsed > 0.0);
+        match elapsed {
+            Some(elapsed) if complete => Ok(Some((used as f64 / 1_000_000.0 / elapsed) as f32)),
+            _ => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn handles_counter_wraparound() {
+        assert_eq!(energy_delta(1_000, 5_000, 10_000), 4_000);
+        assert_eq!(energy_delta(9_000, 1_000, 10_000), 2_000);
+        assert_eq!(energy_delta(5_000, 5_000, 10_000), 0);
+    }
+
+    #[test]
+    fn recognizes_package_zones() {
+        assert!(is_top_level_zone("intel-rapl:0"));
+        assert!(is_top_level_zone("intel-rapl:1"));
+        assert!(!is_top_level_zone("intel-rapl:0:0"));
+        assert!(!is_top_level_zone("intel-rapl"));
+    }
+
+    #[test]
+    fn computes_watts_between_samples() {
+        let root = TestDir::new("rapl");
+        for (dir, name, energy) in [
+            ("intel-rapl:0", "package-0", "1000000"),
+            ("intel-rapl:0:0", "core", "500000"),
+            ("intel-rapl:1", "psys", "7000000"),
+        ] {
+            root.write(format!("{dir}/name"), &format!("{name}\n"));
+            root.write(format!("{dir}/energy_uj"), &format!("{energy}\n"));
+            root.write(format!("{dir}/max_energy_range_uj"), "262143328850\n");
+        }
+
+        let start = Instant::now();
+        let mut meter = RaplMeter::new(&root);
+        assert_eq!(meter.sample_at(start), Ok(None));
+
+        // 48 J in two seconds, the psys zone isn't a package
+        root.write("intel-rapl:0/energy_uj", "97000000\n");
+        root.write("intel-rapl:1/energy_uj", "9000000\n");
+        assert_eq!(
+            meter.sample_at(start + Duration::from_secs(2)),
+            Ok(Some(48.0))
+        );
+
+        // An unreadable counter is reported and looked for again
+        fs::remove_file(root.join("intel-rapl:0/energy_uj")).unwrap();
+        assert!(meter.sample_at(start + Duration::from_secs(3)).is_err());
+        root.write("intel-rapl:0/energy_uj", "98000000\n");
+        assert_eq!(meter.sample_at(start + Duration::from_secs(4)), Ok(None));
+    }
+
+    #[test]
+    fn missing_powercap_is_an_error() {
+        let mut meter = RaplMeter::new(Path::new("/nonexistent/powercap"));
+        assert!(meter.sample().is_err());
+    }
+}
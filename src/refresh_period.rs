@@ -0,0 +1,304 @@
+use std::collections::VecDeque;
+
+use crate::locale;
+
+// Bounds for the refresh period typed into the popup. Anything outside of them is clamped
+// to the nearest bound instead of being thrown away.
+pub(crate) const MIN_REFRESH_PERIOD_MS: u64 = 100;
+// Ten minutes
+pub(crate) const MAX_REFRESH_PERIOD_MS: u64 = 10 * 60 * 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PeriodInput {
+    Valid(u64),
+    // Below `MIN_REFRESH_PERIOD_MS`, used as the minimum
+    TooShort,
+    // Above `MAX_REFRESH_PERIOD_MS`, used as the maximum
+    TooLong,
+    // Not a whole number, nothing is saved. Grouped ones like "1.000" are fine.
+    Invalid,
+}
+
+impl PeriodInput {
+    // The period to save, already clamped
+    pub(crate) fn period(self) -> Option<u64> {
+        match self {
+            PeriodInput::Valid(period) => Some(period),
+            PeriodInput::TooShort => Some(MIN_REFRESH_PERIOD_MS),
+            PeriodInput::TooLong => Some(MAX_REFRESH_PERIOD_MS),
+            PeriodInput::Invalid => None,
+        }
+    }
+}
+
+pub(crate) fn parse_period(input: &str) -> PeriodInput {
+    let Some(digits) = locale::normalize_integer(input) else {
+        return PeriodInput::Invalid;
+    };
+    match digits.parse::<u64>() {
+        Ok(period) if period < MIN_REFRESH_PERIOD_MS => PeriodInput::TooShort,
+        Ok(period) if period > MAX_REFRESH_PERIOD_MS => PeriodInput::TooLong,
+        Ok(period) => PeriodInput::Valid(period),
+        // Too many digits for a u64 is still a number, just a very long one
+        Err(err) if *err.kind() == std::num::IntErrorKind::PosOverflow => PeriodInput::TooLong,
+        Err(_) => PeriodInput::Invalid,
+    }
+}
+
+// Text of the refresh period input. It's kept apart from the config, because the config
+// reload that follows every save can arrive after newer edits and must not revert them.
+#[derive(Debug, Default)]
+pub(crate) struct PeriodEdit {
+    text: String,
+    // Bumped on every edit, so only the last scheduled commit is applied
+    generation: u64,
+    // There is an edit that wasn't saved yet
+    dirty: bool,
+    // Periods saved from the input whose config reload didn't arrive yet, oldest first
+    in_flight: VecDeque<u64>,
+}
+
+impl PeriodEdit {
+    pub(crate) fn new(period: u64) -> Self {
+        Self {
+            text: period.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn text(&self) -> &str {
+        &self.text
+    }
+
+    // Returns the generation to commit once the input stayed unchanged for a while
+    pub(crate) fn edit(&mut self, input: String) -> u64 {
+        self.text = input;
+        self.dirty = true;
+        self.invalidate()
+    }
+
+    // Makes any scheduled commit a no-op
+    pub(crate) fn invalidate(&mut self) -> u64 {
+        self.generation += 1;
+        self.generation
+    }
+
+    pub(crate) fn is_current(&self, generation: u64) -> bool {
+        generation == self.generation
+    }
+
+    // Returns the period to save, if it's valid and differs from the `saved` one
+    pub(crate) fn commit(&mut self, saved: u64) -> Option<u64> {
+        self.dirty = false;
+        let period = parse_period(&self.text).period()?;
+        if period == saved {
+            return None;
+        }
+
+        self.in_flight.push_back(period);
+        Some(period)
+    }
+
+    // Called with the period of every config reload. Returns `true` if the text was replaced,
+    // which only happens for changes made elsewhere while nothing is being edited.
+    pub(crate) fn config_changed(&mut self, period: u64) -> bool {
+        if let Some(index) = self.in_flight.iter().position(|&sent| sent == period) {
+            // Our own save coming back. Anything older was superseded by it, anything newer
+            // is still on its way and the text already shows it.
+            self.in_flight.drain(..=index);
+            return false;
+        }
+
+        if self.dirty
+            || !self.in_flight.is_empty()
+            || parse_period(&self.text).period() == Some(period)
+        {
+            return false;
+        }
+
+        self.text = period.to_string();
+        true
+    }
+}
+
+// Picks the samples that are shown when the display period is longer than the sample
+// period. Works on the configured periods, so the battery multiplier stretches both.
+#[derive(Debug, Default)]
+pub(crate) struct DisplayCadence {
+    // Samples since the last shown one, `None` until one was shown
+    since_shown: Option<u64>,
+}
+
+impl DisplayCadence {
+    // Returns `true` if this sample should be shown
+    pub(crate) fn sample(&mut self, sample_period: u64, display_period: u64) -> bool {
+        let every = display_period.div_ceil(sample_period.max(1)).max(1);
+        match self.since_shown {
+            Some(skipped) if skipped + 1 < every => {
+                self.since_shown = Some(skipped + 1);
+                false
+            }
+            _ => {
+                self.since_shown = Some(0);
+                true
+            }
+        }
+    }
+
+    // Shows the next sample right away, e.g. after switching sensors
+    pub(crate) fn reset(&mut self) {
+        self.since_shown = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_periods_within_bounds() {
+        assert_eq!(parse_period("1000"), PeriodInput::Valid(1000));
+        assert_eq!(parse_period("10000"), PeriodInput::Valid(10000));
+        assert_eq!(parse_period(" 2500 "), PeriodInput::Valid(2500));
+    }
+
+    #[test]
+    fn accepts_grouped_periods() {
+        assert_eq!(parse_period("1.000"), PeriodInput::Valid(1000));
+        assert_eq!(parse_period("1,000"), PeriodInput::Valid(1000));
+        assert_eq!(parse_period("1 000"), PeriodInput::Valid(1000));
+        assert_eq!(parse_period("١٠٠٠"), PeriodInput::Valid(1000));
+        assert_eq!(parse_period("600.001"), PeriodInput::TooLong);
+    }
+
+    #[test]
+    fn bounds_are_inclusive() {
+        assert_eq!(
+            parse_period(&MIN_REFRESH_PERIOD_MS.to_string()),
+            PeriodInput::Valid(MIN_REFRESH_PERIOD_MS)
+        );
+        assert_eq!(
+            parse_period(&MAX_REFRESH_PERIOD_MS.to_string()),
+            PeriodInput::Valid(MAX_REFRESH_PERIOD_MS)
+        );
+    }
+
+    #[test]
+    fn clamps_short_periods_to_the_minimum() {
+        assert_eq!(parse_period("0"), PeriodInput::TooShort);
+        assert_eq!(parse_period("99"), PeriodInput::TooShort);
+        assert_eq!(parse_period("99").period(), Some(MIN_REFRESH_PERIOD_MS));
+    }
+
+    #[test]
+    fn clamps_long_periods_to_the_maximum() {
+        assert_eq!(parse_period("600001"), PeriodInput::TooLong);
+        assert_eq!(parse_period("600001").period(), Some(MAX_REFRESH_PERIOD_MS));
+        assert_eq!(
+            parse_period("99999999999999999999999"),
+            PeriodInput::TooLong
+        );
+    }
+
+    #[test]
+    fn rejects_non_numbers() {
+        for input in ["", "abc", "-5", "1.5", "1,00", "1.000.0"] {
+            assert_eq!(parse_period(input), PeriodInput::Invalid, "{input:?}");
+            assert_eq!(parse_period(input).period(), None);
+        }
+    }
+
+    #[test]
+    fn edit_survives_stale_config_reload() {
+        let mut edit = PeriodEdit::new(1000);
+
+        edit.edit("1500".to_string());
+        assert_eq!(edit.commit(1000), Some(1500));
+        edit.edit("15000".to_string());
+        assert_eq!(edit.commit(1500), Some(15000));
+
+        // Reload of the first save arrives after the second one was written
+        assert!(!edit.config_changed(1500));
+        assert_eq!(edit.text(), "15000");
+        assert!(!edit.config_changed(15000));
+        assert_eq!(edit.text(), "15000");
+    }
+
+    #[test]
+    fn uncommitted_edit_survives_config_reload() {
+        let mut edit = PeriodEdit::new(1000);
+
+        edit.edit("1500".to_string());
+        assert_eq!(edit.commit(1000), Some(1500));
+        let generation = edit.edit("150".to_string());
+        assert!(!edit.config_changed(1500));
+        assert_eq!(edit.text(), "150");
+        assert!(edit.is_current(generation));
+    }
+
+    #[test]
+    fn external_change_replaces_text() {
+        let mut edit = PeriodEdit::new(1000);
+
+        assert!(edit.config_changed(2000));
+        assert_eq!(edit.text(), "2000");
+
+        edit.edit("3000".to_string());
+        assert_eq!(edit.commit(2000), Some(3000));
+        assert!(!edit.config_changed(3000));
+        // Once our save came back, changes made elsewhere show up again
+        assert!(edit.config_changed(4000));
+        assert_eq!(edit.text(), "4000");
+    }
+
+    #[test]
+    fn clamped_text_is_kept() {
+        let mut edit = PeriodEdit::new(1000);
+
+        edit.edit("50".to_string());
+        assert_eq!(edit.commit(1000), Some(MIN_REFRESH_PERIOD_MS));
+        assert!(!edit.config_changed(MIN_REFRESH_PERIOD_MS));
+        // Still shows what was typed, next to the hint
+        assert_eq!(edit.text(), "50");
+    }
+
+    #[test]
+    fn only_latest_generation_is_current() {
+        let mut edit = PeriodEdit::new(1000);
+
+        let first = edit.edit("1".to_string());
+        let second = edit.edit("15".to_string());
+        assert!(!edit.is_current(first));
+        assert!(edit.is_current(second));
+        edit.invalidate();
+        assert!(!edit.is_current(second));
+    }
+
+    #[test]
+    fn display_cadence_shows_every_nth_sample() {
+        let mut cadence = DisplayCadence::default();
+
+        let shown: Vec<bool> = (0..7).map(|_| cadence.sample(1000, 3000)).collect();
+        assert_eq!(shown, [true, false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn display_cadence_rounds_up_and_shows_every_sample_when_shorter() {
+        let mut cadence = DisplayCadence::default();
+        let shown: Vec<bool> = (0..4).map(|_| cadence.sample(1000, 1500)).collect();
+        assert_eq!(shown, [true, false, true, false]);
+
+        let mut cadence = DisplayCadence::default();
+        assert!((0..3).all(|_| cadence.sample(2000, 500)));
+    }
+
+    #[test]
+    fn reset_shows_next_sample() {
+        let mut cadence = DisplayCadence::default();
+
+        assert!(cadence.sample(1000, 10000));
+        assert!(!cadence.sample(1000, 10000));
+        cadence.reset();
+        assert!(cadence.sample(1000, 10000));
+    }
+}
@@ -0,0 +1,261 @@
+// Optional log of every sample, for following a thermal problem over a day. One line per
+// sample, CSV with a header, or a JSON object per line if the file name ends in `.jsonl`:
+//
+// timestamp,sensor,celsius
+// 2025-10-09T14:03:21.512+02:00,Package id 0,54.2
+//
+// {"timestamp":"2025-10-09T14:03:21.512+02:00","sensor":"Package id 0","celsius":54.2}
+//
+// Relative paths are in the state directory, next to the history snapshot. Once the file
+// grows past the configured size it's renamed to `<name>.1`, replacing the previous one,
+// and a new file is started.
+
+use std::ffi::OsString;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, SecondsFormat};
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::SinkExt;
+use serde::Serialize;
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncWriteExt, BufWriter};
+use tokio::sync::{mpsc as tokio_mpsc, oneshot};
+
+use crate::persist;
+
+const CSV_HEADER: &str = "timestamp,sensor,celsius\n";
+const JSON_EXTENSION: &str = "jsonl";
+
+#[derive(Debug, Serialize)]
+pub(crate) struct Sample {
+    timestamp: String,
+    sensor: String,
+    celsius: f32,
+}
+
+impl Sample {
+    pub(crate) fn now(sensor: String, celsius: f32) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+            sensor,
+            celsius,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) enum Command {
+    Write(Sample),
+    // Answered once everything sent before is on disk
+    Flush(oneshot::Sender<()>),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum Event {
+    // Where the samples go, replaced whenever the writer is started over
+    Ready(tokio_mpsc::UnboundedSender<Command>),
+    // The writer stopped, with the reason
+    Failed(String),
+}
+
+// The configured path, relative ones in the state directory. `None` without a state
+// directory.
+pub(crate) fn resolve_path(path: &Path) -> Option<PathBuf> {
+    if path.is_absolute() {
+        Some(path.to_path_buf())
+    } else {
+        Some(persist::state_dir()?.join(path))
+    }
+}
+
+// `readings.csv` -> `readings.csv.1`
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".1");
+    PathBuf::from(name)
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == JSON_EXTENSION)
+}
+
+fn format_line(sample: &Sample, json: bool) -> String {
+    if json {
+        // Serializing a struct of strings and a number can't fail
+        let mut line = serde_json::to_string(sample).unwrap_or_default();
+        line.push('\n');
+        return line;
+    }
+
+    format!(
+        "{},{},{}\n",
+        sample.timestamp,
+        escape_csv(&sample.sensor),
+        sample.celsius
+    )
+}
+
+// Quoted only when needed, so plain labels stay readable
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+struct Writer {
+    path: PathBuf,
+    max_bytes: u64,
+    json: bool,
+    // Opened on the first sample
+    file: Option<BufWriter<File>>,
+    size: u64,
+}
+
+impl Writer {
+    async fn handle(&mut self, command: Command) -> io::Result<()> {
+        match command {
+            Command::Write(sample) => self.write(&sample).await,
+            Command::Flush(done) => {
+                self.flush().await?;
+                let _ = done.send(());
+                Ok(())
+            }
+        }
+    }
+
+    async fn write(&mut self, sample: &Sample) -> io::Result<()> {
+        if self.file.is_some() && self.size >= self.max_bytes {
+            self.rotate().await?;
+        }
+        if self.file.is_none() {
+            self.open().await?;
+        }
+
+        let line = format_line(sample, self.json);
+        if let Some(file) = &mut self.file {
+            file.write_all(line.as_bytes()).await?;
+            self.size += line.len() as u64;
+        }
+        Ok(())
+    }
+
+    async fn open(&mut self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        self.size = file.metadata().await?.len();
+        let mut file = BufWriter::new(file);
+        if self.size == 0 && !self.json {
+            file.write_all(CSV_HEADER.as_bytes()).await?;
+            self.size += CSV_HEADER.len() as u64;
+        }
+        self.file = Some(file);
+        Ok(())
+    }
+
+    async fn rotate(&mut self) -> io::Result<()> {
+        if let Some(mut file) = self.file.take() {
+            file.flush().await?;
+        }
+        fs::rename(&self.path, rotated_path(&self.path)).await
+    }
+
+    async fn flush(&mut self) -> io::Result<()> {
+        match &mut self.file {
+            Some(file) => file.flush().await,
+            None => Ok(()),
+        }
+    }
+}
+
+// Writes the samples it's sent until the future is dropped, which happens when the
+// subscription running it goes away (logging disabled, or another file or size). Stops on
+// the first error, a full disk or a missing permission won't go away by itself.
+pub(crate) async fn run(path: PathBuf, max_bytes: u64, mut output: mpsc::Sender<Event>) {
+    let (sender, mut commands) = tokio_mpsc::unbounded_channel();
+    let _ = output.send(Event::Ready(sender)).await;
+
+    let mut writer = Writer {
+        json: is_json(&path),
+        path,
+        max_bytes,
+        file: None,
+        size: 0,
+    };
+    while let Some(command) = commands.recv().await {
+        let mut result = writer.handle(command).await;
+        // Whatever arrived in the meantime goes out with the same flush
+        while result.is_ok() {
+            match commands.try_recv() {
+                Ok(command) => result = writer.handle(command).await,
+                Err(_) => break,
+            }
+        }
+        if result.is_ok() {
+            result = writer.flush().await;
+        }
+
+        if let Err(err) = result {
+            tracing::warn!(?err, path = %writer.path.display(), "Error writing the sample log");
+            let _ = output.send(Event::Failed(err.to_string())).await;
+            break;
+        }
+    }
+
+    // Until the applet drops the subscription for the failure. It starts a new one once the
+    // log settings are changed.
+    std::future::pending::<()>().await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(sensor: &str) -> Sample {
+        Sample {
+            timestamp: "2025-10-09T14:03:21.512+02:00".to_string(),
+            sensor: sensor.to_string(),
+            celsius: 54.2,
+        }
+    }
+
+    #[test]
+    fn formats_csv_lines() {
+        assert_eq!(
+            format_line(&sample("Package id 0"), false),
+            "2025-10-09T14:03:21.512+02:00,Package id 0,54.2\n"
+        );
+        assert_eq!(
+            format_line(&sample("k10temp, \"Tctl\""), false),
+            "2025-10-09T14:03:21.512+02:00,\"k10temp, \"\"Tctl\"\"\",54.2\n"
+        );
+    }
+
+    #[test]
+    fn formats_json_lines() {
+        assert_eq!(
+            format_line(&sample("Package id 0"), true),
+            "{\"timestamp\":\"2025-10-09T14:03:21.512+02:00\",\"sensor\":\"Package id 0\",\"celsius\":54.2}\n"
+        );
+    }
+
+    #[test]
+    fn picks_format_and_rotated_name_from_path() {
+        assert!(is_json(Path::new("/tmp/readings.jsonl")));
+        assert!(!is_json(Path::new("/tmp/readings.csv")));
+        assert_eq!(
+            rotated_path(Path::new("/tmp/readings.csv")),
+            PathBuf::from("/tmp/readings.csv.1")
+        );
+    }
+}
@@ -0,0 +1,171 @@
+// Some EC firmwares report -273.1 °C, or 65535 millidegrees now and then. Values like that
+// are dropped here, before they reach the panel, the peak or the smoothing.
+
+use crate::sysinfo_utils::{CpuSensor, ReadOptions, SensorReading, TempProvider, TempReading};
+use crate::thermal_zone;
+
+// Colder than any machine that is running
+pub(crate) const MIN_PLAUSIBLE_CELSIUS: f32 = -40.0;
+// Above what consumer parts survive, configurable for exotic hardware
+pub(crate) const DEFAULT_MAX_PLAUSIBLE_CELSIUS: f32 = 125.0;
+// A larger change between two readings is taken for garbage, unless the next one agrees
+const MAX_JUMP_CELSIUS: f32 = 40.0;
+
+// `None` for NaN, infinite and out of range values
+pub(crate) fn plausible(celsius: Option<f32>, max_celsius: f32, label: &str) -> Option<f32> {
+    let celsius = celsius?;
+    if celsius.is_finite() && (MIN_PLAUSIBLE_CELSIUS..=max_celsius).contains(&celsius) {
+        return Some(celsius);
+    }
+
+    tracing::debug!(celsius, label, "Rejected implausible temperature");
+    None
+}
+
+// Rejects a single reading that is far off the last accepted one. If the next reading is
+// just as far off, the change was real and it's accepted.
+#[derive(Debug, Default)]
+pub(crate) struct JumpFilter {
+    last: Option<f32>,
+    rejected: bool,
+}
+
+impl JumpFilter {
+    pub(crate) fn accept(&mut self, celsius: f32) -> bool {
+        match self.last {
+            Some(last) if (celsius - last).abs() > MAX_JUMP_CELSIUS && !self.rejected => {
+                self.rejected = true;
+                false
+            }
+            _ => {
+                self.last = Some(celsius);
+                self.rejected = false;
+                true
+            }
+        }
+    }
+
+    pub(crate) fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+// Checks the CPU value of another provider. A rejected value turns into a read error, so
+// the panel keeps the previous one and marks it as stale.
+pub(crate) struct SaneProvider {
+    inner: Box<dyn TempProvider>,
+    jumps: JumpFilter,
+    // Jumps between different sensors are expected, the filter starts over on a switch
+    sensor: Option<CpuSensor>,
+}
+
+impl SaneProvider {
+    pub(crate) fn new(inner: Box<dyn TempProvider>) -> Self {
+        Self {
+            inner,
+            jumps: JumpFilter::default(),
+            sensor: None,
+        }
+    }
+}
+
+impl TempProvider for SaneProvider {
+    fn read(&mut self, options: &ReadOptions) -> SensorReading {
+        let mut reading = self.inner.read(options);
+        let TempReading::Value(temp) = &reading.cpu else {
+            return reading;
+        };
+
+        if reading.cpu_sensor != self.sensor {
+            self.jumps.reset();
+            self.sensor = reading.cpu_sensor.clone();
+        }
+
+        let source = match &reading.cpu_sensor {
+            Some(CpuSensor::Component { label, .. }) => label.clone(),
+            Some(CpuSensor::ThermalZone) => thermal_zone::THERMAL_ROOT.to_string(),
+            _ => "CPU".to_string(),
+        };
+        let max_celsius = options.max_plausible_celsius();
+        let accepted = plausible(Some(temp.celsius), max_celsius, &source).is_some()
+            && self.jumps.accept(temp.celsius);
+        if !accepted {
+            tracing::debug!(celsius = temp.celsius, %source, "Rejected CPU temperature jump");
+            reading.cpu = TempReading::ReadError(source);
+            reading.cpu_sensor = None;
+        }
+        reading
+    }
+
+    // Whatever comes after a resume can be far off the last value and still be right
+    fn rescan_on_next_read(&mut self) {
+        self.jumps.reset();
+        self.inner.rescan_on_next_read();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sysinfo_utils::{build_reading, RawSensor};
+
+    struct Replay {
+        values: Vec<f32>,
+    }
+
+    impl TempProvider for Replay {
+        fn read(&mut self, options: &ReadOptions) -> SensorReading {
+            let sensor = RawSensor {
+                label: "Package id 0".to_string(),
+                temp: Some(self.values.remove(0)),
+                ..Default::default()
+            };
+            build_reading(&[sensor], options)
+        }
+    }
+
+    #[test]
+    fn rejects_non_finite_and_out_of_range_values() {
+        let max = DEFAULT_MAX_PLAUSIBLE_CELSIUS;
+        assert_eq!(plausible(Some(f32::NAN), max, "x"), None);
+        assert_eq!(plausible(Some(f32::INFINITY), max, "x"), None);
+        assert_eq!(plausible(Some(-273.1), max, "x"), None);
+        assert_eq!(plausible(Some(65535.0), max, "x"), None);
+        assert_eq!(plausible(Some(126.0), max, "x"), None);
+        assert_eq!(plausible(Some(126.0), 150.0, "x"), Some(126.0));
+        assert_eq!(plausible(Some(-40.0), max, "x"), Some(-40.0));
+        assert_eq!(plausible(None, max, "x"), None);
+    }
+
+    #[test]
+    fn rejects_single_jumps_only() {
+        let mut filter = JumpFilter::default();
+
+        assert!(filter.accept(50.0));
+        assert!(!filter.accept(5.0));
+        assert!(filter.accept(52.0));
+        // Stays up, so it's real
+        assert!(!filter.accept(95.0));
+        assert!(filter.accept(96.0));
+        assert!(filter.accept(90.0));
+    }
+
+    #[test]
+    fn rejected_value_becomes_read_error() {
+        let mut provider = SaneProvider::new(Box::new(Replay {
+            values: vec![50.0, 120.0, 51.0, 200.0],
+        }));
+        let options = ReadOptions::default();
+
+        assert!(matches!(provider.read(&options).cpu, TempReading::Value(_)));
+        assert_eq!(
+            provider.read(&options).cpu,
+            TempReading::ReadError("Package id 0".to_string())
+        );
+        assert!(matches!(provider.read(&options).cpu, TempReading::Value(_)));
+        assert!(matches!(
+            provider.read(&options).cpu,
+            TempReading::ReadError(_)
+        ));
+    }
+}
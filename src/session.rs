@@ -0,0 +1,86 @@
+use cosmic::iced::futures::channel::mpsc;
+use cosmic::iced::futures::{SinkExt, StreamExt};
+use zbus::zvariant::OwnedObjectPath;
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Manager {
+    fn get_session(&self, session_id: &str) -> zbus::Result<OwnedObjectPath>;
+
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Session",
+    default_service = "org.freedesktop.login1"
+)]
+trait Session {
+    // Set by the lock screen while the session is locked
+    #[zbus(property)]
+    fn locked_hint(&self) -> zbus::Result<bool>;
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum SessionEvent {
+    // `true` while locked or asleep
+    Paused(bool),
+    // Woke up from suspend, sent before the matching `Paused(false)`
+    Resumed,
+}
+
+async fn try_watch(output: &mut mpsc::Sender<SessionEvent>) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let manager = ManagerProxy::new(&connection).await?;
+    // "auto" resolves to the caller's session. Property changes are only emitted on the
+    // real object path, so it can't be used as the path directly.
+    let session_path = manager.get_session("auto").await?;
+    let session = SessionProxy::builder(&connection)
+        .path(session_path)?
+        .build()
+        .await?;
+
+    let mut sleep_signals = manager.receive_prepare_for_sleep().await?;
+    let mut locked_changes = session.receive_locked_hint_changed().await;
+
+    let mut locked = session.locked_hint().await?;
+    let mut sleeping = false;
+    let mut paused = false;
+
+    loop {
+        if paused != (locked || sleeping) {
+            paused = locked || sleeping;
+            if output.send(SessionEvent::Paused(paused)).await.is_err() {
+                return Ok(());
+            }
+        }
+
+        tokio::select! {
+            Some(signal) = sleep_signals.next() => {
+                let was_sleeping = sleeping;
+                sleeping = signal.args()?.start;
+                if was_sleeping && !sleeping && output.send(SessionEvent::Resumed).await.is_err() {
+                    return Ok(());
+                }
+            },
+            Some(change) = locked_changes.next() => {
+                locked = change.get().await?;
+            },
+            else => return Ok(()),
+        }
+    }
+}
+
+// Sends `Paused(true)` while the session is locked or about to sleep, `Paused(false)` once
+// it's back. Without logind polling is simply never paused, so failures are only logged and
+// logind isn't asked again.
+pub(crate) async fn watch(mut output: mpsc::Sender<SessionEvent>) {
+    if let Err(err) = try_watch(&mut output).await {
+        tracing::error!(?err, "Failed watching the session state");
+    }
+
+    std::future::pending::<()>().await;
+}
@@ -0,0 +1,1138 @@
+// Standalone settings window, started with `--settings`. It edits the same config as the
+// applet, which picks up every change through its config subscription. Changes made in the
+// popup come back here the same way, so both stay in sync while open.
+
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use cosmic::app::Core;
+use cosmic::cosmic_config::{self, CosmicConfigEntry};
+use cosmic::iced::widget::row;
+use cosmic::iced::{Alignment, Length, Subscription, Task};
+use cosmic::widget::{dropdown, scrollable, settings, text, text_input, toggler};
+use cosmic::Element;
+
+use crate::applet_state::{
+    format_offset, format_threshold, is_display_period_too_short, parse_celsius, threshold_pair,
+};
+use crate::config::{
+    instance_config_id, CPUTempAppletConfig, CoreAggregation, DisplayMode, GraphRange, PanelStyle,
+    SensorBackend, TemperatureUnit, ThresholdIndication, MAX_DECIMAL_PLACES,
+};
+use crate::config_writer::ConfigWriter;
+use crate::fl;
+use crate::quiet_hours;
+use crate::refresh_period::parse_period;
+use crate::sanity;
+use crate::sysinfo_utils::{new_provider, sensor_names, ReadOptions};
+use crate::units;
+use crate::window::{
+    self, BATTERY_MULTIPLIERS, BATTERY_MULTIPLIER_OPTIONS, CORE_AGGREGATION_OPTIONS,
+    DECIMAL_PLACES_OPTIONS, DISPLAY_MODE_OPTIONS, GRAPH_RANGE_OPTIONS, PANEL_STYLE_OPTIONS,
+    ROUNDING_STEPS, ROUNDING_STEP_OPTIONS, SENSOR_BACKEND_OPTIONS, SMOOTHING_FACTORS,
+    SMOOTHING_OPTIONS, THRESHOLD_INDICATION_OPTIONS, UNIT_OPTIONS,
+};
+
+const APP_ID: &str = "com.gr3q.CosmicExtAppletCPUTemperature.Settings";
+
+pub(crate) const SETTINGS_ARG: &str = "--settings";
+
+// Opens the settings window for `instance` in its own process group, like the system
+// monitor, so closing the panel doesn't take it down
+pub(crate) fn launch(instance: Option<&str>) -> std::io::Result<()> {
+    let mut command = Command::new(std::env::current_exe()?);
+    command.arg(SETTINGS_ARG);
+    if let Some(instance) = instance {
+        command.arg("--instance").arg(instance);
+    }
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0)
+        .spawn()?;
+
+    // Reaps the process once it exits, otherwise it stays around as a zombie
+    std::thread::spawn(move || {
+        if let Err(err) = child.wait() {
+            tracing::error!(?err, "Failed to wait for the settings window");
+        }
+    });
+
+    Ok(())
+}
+
+// Text input contents, kept apart from the config like in the popup. They're only replaced
+// when the config value changed, so a reload of our own write doesn't reset the cursor.
+#[derive(Default)]
+struct Inputs {
+    sample_period: String,
+    display_period: String,
+    warning_threshold: String,
+    critical_threshold: String,
+    hide_below: String,
+    panel_format: String,
+    panel_prefix: String,
+    panel_suffix: String,
+    sensor_blacklist: String,
+    alert_sound: String,
+    quiet_hours_start: String,
+    quiet_hours_end: String,
+    critical_action: String,
+    critical_action_delay: String,
+    monitor_command: String,
+    metrics_port: String,
+    mqtt_broker: String,
+    mqtt_topic: String,
+    mqtt_interval_multiplier: String,
+    mqtt_credentials_file: String,
+    max_plausible: String,
+    offset: String,
+    graph_min: String,
+    graph_max: String,
+    log_path: String,
+    log_max_size: String,
+}
+
+impl Inputs {
+    // Refreshes the inputs whose value differs between `old` and `new`, all of them without
+    // an `old` config
+    fn update(&mut self, old: Option<&CPUTempAppletConfig>, new: &CPUTempAppletConfig) {
+        let changed = |field: fn(&CPUTempAppletConfig) -> String| {
+            old.map_or(true, |old| field(old) != field(new))
+                .then(|| field(new))
+        };
+        // Thresholds are shown in the unit, so they change along with it
+        let unit_changed = old.map_or(true, |old| old.unit != new.unit);
+        let threshold = |field: fn(&CPUTempAppletConfig) -> Option<f32>| {
+            (unit_changed || old.is_some_and(|old| field(old) != field(new)))
+                .then(|| format_threshold(field(new), new.unit))
+        };
+
+        if let Some(text) = changed(|c| c.sample_period_ms.to_string()) {
+            self.sample_period = text;
+        }
+        if let Some(text) = changed(|c| c.display_period_ms.to_string()) {
+            self.display_period = text;
+        }
+        if let Some(text) = threshold(|c| c.warning_threshold_celsius) {
+            self.warning_threshold = text;
+        }
+        if let Some(text) = threshold(|c| c.critical_threshold_celsius) {
+            self.critical_threshold = text;
+        }
+        if let Some(text) = threshold(|c| c.hide_below_celsius) {
+            self.hide_below = text;
+        }
+        if let Some(text) = changed(|c| c.panel_format.clone()) {
+            self.panel_format = text;
+        }
+        if let Some(text) = changed(|c| c.panel_prefix.clone()) {
+            self.panel_prefix = text;
+        }
+        if let Some(text) = changed(|c| c.panel_suffix.clone()) {
+            self.panel_suffix = text;
+        }
+        if let Some(text) = changed(|c| c.sensor_blacklist.join(", ")) {
+            self.sensor_blacklist = text;
+        }
+        if let Some(text) = changed(|c| c.alert_sound.clone().unwrap_or_default()) {
+            self.alert_sound = text;
+        }
+        let time = |minute: Option<u16>| minute.map(quiet_hours::format_time).unwrap_or_default();
+        if let Some(text) = changed(|c| time(c.quiet_hours_start)) {
+            self.quiet_hours_start = text;
+        }
+        if let Some(text) = changed(|c| time(c.quiet_hours_end)) {
+            self.quiet_hours_end = text;
+        }
+        if let Some(text) = changed(|c| c.critical_action.clone().unwrap_or_default()) {
+            self.critical_action = text;
+        }
+        if let Some(text) = changed(|c| c.critical_action_delay_seconds.to_string()) {
+            self.critical_action_delay = text;
+        }
+        if let Some(text) = changed(|c| c.monitor_command.clone().unwrap_or_default()) {
+            self.monitor_command = text;
+        }
+        if let Some(text) = changed(|c| c.metrics_port.to_string()) {
+            self.metrics_port = text;
+        }
+        if let Some(text) = changed(|c| c.mqtt_broker.clone()) {
+            self.mqtt_broker = text;
+        }
+        if let Some(text) = changed(|c| c.mqtt_topic.clone()) {
+            self.mqtt_topic = text;
+        }
+        if let Some(text) = changed(|c| c.mqtt_interval_multiplier.to_string()) {
+            self.mqtt_interval_multiplier = text;
+        }
+        if let Some(text) = changed(|c| {
+            c.mqtt_credentials_file
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default()
+        }) {
+            self.mqtt_credentials_file = text;
+        }
+        if let Some(text) = threshold(|c| Some(c.max_plausible_celsius)) {
+            self.max_plausible = text;
+        }
+        if let Some(text) = changed(|c| format_offset(Some(c.offset_celsius))) {
+            self.offset = text;
+        }
+        if let Some(text) = threshold(|c| Some(c.graph_min_celsius)) {
+            self.graph_min = text;
+        }
+        if let Some(text) = threshold(|c| Some(c.graph_max_celsius)) {
+            self.graph_max = text;
+        }
+        if let Some(text) = changed(|c| {
+            c.log_to_file
+                .as_ref()
+                .map(|path| path.display().to_string())
+                .unwrap_or_default()
+        }) {
+            self.log_path = text;
+        }
+        if let Some(text) = changed(|c| c.log_max_size_mb.to_string()) {
+            self.log_max_size = text;
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SettingsApp {
+    core: Core,
+    config_id: &'static str,
+    config: CPUTempAppletConfig,
+    // Same debounce and retries as the popup
+    config_writer: ConfigWriter,
+    inputs: Inputs,
+    // "Automatic" followed by the sensors of the configured backend
+    sensor_options: Vec<String>,
+    // What the dropdown shows for `sensor_options`, with the driver when it's known
+    sensor_option_names: Vec<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    ConfigChanged(CPUTempAppletConfig),
+    // Panel
+    Unit(usize),
+    DisplayMode(usize),
+    DecimalPlaces(usize),
+    PanelFormat(String),
+    PanelPrefix(String),
+    PanelSuffix(String),
+    ShowIcon(bool),
+    FixedWidth(bool),
+    ShowBothUnits(bool),
+    ShowLoad(bool),
+    LoadWithoutSensors(bool),
+    ShowFan(bool),
+    ShowPower(bool),
+    ShowThrottling(bool),
+    ShowHotCore(bool),
+    PowerInPanel(bool),
+    ThresholdIndication(usize),
+    PanelStyle(usize),
+    HideBelow(String),
+    // Sensors
+    SensorBackend(usize),
+    SelectSensor(usize),
+    CoreAggregation(usize),
+    HottestCcd(bool),
+    PreferTctl(bool),
+    SensorBlacklist(String),
+    ShowDriveTemps(bool),
+    // Refreshing
+    SamplePeriod(String),
+    DisplayPeriod(String),
+    BatterySaver(bool),
+    BatteryMultiplier(usize),
+    Smoothing(usize),
+    RoundingStep(usize),
+    // Alerts
+    WarningThreshold(String),
+    CriticalThreshold(String),
+    CriticalNotification(bool),
+    FlashOnCritical(bool),
+    DisableAnimations(bool),
+    AlertSound(String),
+    QuietHoursStart(String),
+    QuietHoursEnd(String),
+    CriticalAction(String),
+    SubmitCriticalAction,
+    CriticalActionDelay(String),
+    // Other
+    MonitorCommand(String),
+    PersistHistory(bool),
+    LogPath(String),
+    SubmitLogPath,
+    LogMaxSize(String),
+    SubmitLogMaxSize,
+    MetricsEnabled(bool),
+    MetricsPort(String),
+    SubmitMetricsPort,
+    MqttEnabled(bool),
+    SocketEnabled(bool),
+    MqttBroker(String),
+    MqttTopic(String),
+    SubmitMqtt,
+    MqttIntervalMultiplier(String),
+    MqttCredentialsFile(String),
+    MaxPlausible(String),
+    Offset(String),
+    GraphRange(usize),
+    GraphFixedScale(bool),
+    GraphMin(String),
+    GraphMax(String),
+    // Sent by `ConfigWriter::subscription`
+    WriteConfig(u64),
+    // A change that's still waiting is written before the window goes away
+    Closing,
+}
+
+impl SettingsApp {
+    // Lists the sensors once per backend, the settings don't need live values
+    fn update_sensor_options(&mut self) {
+        let reading = new_provider(self.config.sensor_backend).read(&ReadOptions::default());
+        let mut options = vec![fl!("automatic")];
+        options.extend(reading.labels);
+        // Keep showing the selection even if the sensor is currently missing
+        if let Some(selected) = &self.config.selected_sensor {
+            if !options.contains(selected) {
+                options.push(selected.clone());
+            }
+        }
+        self.sensor_option_names = std::iter::once(options[0].clone())
+            .chain(sensor_names(&options[1..], &reading.chips))
+            .collect();
+        self.sensor_options = options;
+    }
+
+    // Both together, like in the popup. Nothing is saved while the warning isn't below the
+    // critical threshold.
+    fn apply_thresholds(&mut self) {
+        let saved = (
+            self.config.warning_threshold_celsius,
+            self.config.critical_threshold_celsius,
+        );
+        if let Some((warning, critical)) = threshold_pair(
+            &self.inputs.warning_threshold,
+            &self.inputs.critical_threshold,
+            self.config.unit,
+            saved,
+        ) {
+            self.config.warning_threshold_celsius = warning;
+            self.config.critical_threshold_celsius = critical;
+        }
+    }
+
+    // Under the input it's about, like the hints in the popup
+    fn error_caption<'a>(&self, error: String) -> Element<'a, Message> {
+        let color = self.core.system_theme().cosmic().destructive_color();
+        text::caption(error)
+            .class(cosmic::theme::Text::Color(color.into()))
+            .into()
+    }
+
+    fn unit_symbol(&self) -> &'static str {
+        units::unit_symbol(self.config.unit)
+    }
+}
+
+impl cosmic::Application for SettingsApp {
+    type Executor = cosmic::SingleThreadExecutor;
+    type Flags = Option<String>; // The instance name, same as the applet's
+    type Message = Message;
+    const APP_ID: &'static str = APP_ID;
+
+    fn core(&self) -> &Core {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut Core {
+        &mut self.core
+    }
+
+    fn init(core: Core, flags: Self::Flags) -> (Self, Task<cosmic::app::Message<Self::Message>>) {
+        // Leaked once, the config subscription needs a 'static ID
+        let config_id: &'static str =
+            Box::leak(instance_config_id(window::ID, flags.as_deref()).into_boxed_str());
+        let config_helper =
+            match cosmic_config::Config::new(config_id, CPUTempAppletConfig::VERSION) {
+                Ok(helper) => Some(helper),
+                Err(err) => {
+                    tracing::error!(?err, "Error opening config");
+                    None
+                }
+            };
+        let config = config_helper
+            .as_ref()
+            .map(|helper| {
+                CPUTempAppletConfig::get_entry(helper).unwrap_or_else(|(errors, config)| {
+                    for err in errors {
+                        tracing::error!(?err, "Error reading config");
+                    }
+                    config
+                })
+            })
+            .unwrap_or_default();
+
+        let mut app = SettingsApp {
+            core,
+            config_id,
+            config,
+            config_writer: ConfigWriter::new(config_id, config_helper),
+            ..Default::default()
+        };
+        app.inputs.update(None, &app.config);
+        app.update_sensor_options();
+
+        app.set_header_title(fl!("settings-title"));
+        let title = match app.core.main_window_id() {
+            Some(id) => app.set_window_title(fl!("settings-title"), id),
+            None => Task::none(),
+        };
+        (app, title)
+    }
+
+    fn on_close_requested(&self, _id: cosmic::iced::window::Id) -> Option<Message> {
+        Some(Message::Closing)
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        Subscription::batch([
+            self.core.watch_config(self.config_id).map(|u| {
+                for err in u.errors {
+                    tracing::error!(?err, "Error watching config");
+                }
+                Message::ConfigChanged(u.config)
+            }),
+            self.config_writer.subscription(Message::WriteConfig),
+        ])
+    }
+
+    fn update(&mut self, message: Self::Message) -> Task<cosmic::app::Message<Self::Message>> {
+        let unit = self.config.unit;
+        let old = self.config.clone();
+        match message {
+            Message::WriteConfig(generation) => {
+                self.config_writer
+                    .write_if_current(&self.config, generation);
+                return Task::none();
+            }
+            Message::Closing => {
+                self.config_writer.flush(&self.config);
+                return Task::none();
+            }
+            // A change that's still waiting to be written is newer, and would overwrite this
+            // one anyway
+            Message::ConfigChanged(_) if self.config_writer.is_dirty() => return Task::none(),
+            Message::ConfigChanged(config) => {
+                self.inputs.update(Some(&self.config), &config);
+                let backend_changed = self.config.sensor_backend != config.sensor_backend;
+                self.config = config;
+                if backend_changed {
+                    self.update_sensor_options();
+                }
+                // Nothing to write, this is what was just read
+                return Task::none();
+            }
+            Message::Unit(index) => {
+                if let Some(unit) = TemperatureUnit::ALL.get(index) {
+                    let old = self.config.clone();
+                    self.config.unit = *unit;
+                    self.inputs.update(Some(&old), &self.config);
+                }
+            }
+            Message::DisplayMode(index) => {
+                if let Some(mode) = DisplayMode::ALL.get(index) {
+                    self.config.display_mode = *mode;
+                }
+            }
+            Message::DecimalPlaces(index) => {
+                self.config.decimal_places = (index as u8).min(MAX_DECIMAL_PLACES);
+            }
+            Message::PanelFormat(input) => {
+                self.config.panel_format = input.clone();
+                self.inputs.panel_format = input;
+            }
+            Message::PanelPrefix(input) => {
+                self.config.panel_prefix = input.clone();
+                self.inputs.panel_prefix = input;
+            }
+            Message::PanelSuffix(input) => {
+                self.config.panel_suffix = input.clone();
+                self.inputs.panel_suffix = input;
+            }
+            Message::ShowIcon(enabled) => self.config.show_icon = enabled,
+            Message::FixedWidth(enabled) => self.config.fixed_width = enabled,
+            Message::ShowBothUnits(enabled) => self.config.show_both_units = enabled,
+            Message::ShowLoad(enabled) => self.config.show_load = enabled,
+            Message::LoadWithoutSensors(enabled) => self.config.load_without_sensors = enabled,
+            Message::ShowFan(enabled) => self.config.show_fan = enabled,
+            Message::ShowPower(enabled) => self.config.show_power = enabled,
+            Message::ShowThrottling(enabled) => self.config.show_throttling = enabled,
+            Message::ShowHotCore(enabled) => self.config.show_hot_core = enabled,
+            Message::PowerInPanel(enabled) => self.config.power_in_panel = enabled,
+            Message::ThresholdIndication(index) => {
+                if let Some(indication) = ThresholdIndication::ALL.get(index) {
+                    self.config.threshold_indication = *indication;
+                }
+            }
+            Message::PanelStyle(index) => {
+                if let Some(style) = PanelStyle::ALL.get(index) {
+                    self.config.panel_style = *style;
+                }
+            }
+            Message::HideBelow(input) => {
+                if let Ok(celsius) = parse_celsius(&input, unit) {
+                    self.config.hide_below_celsius = celsius;
+                }
+                self.inputs.hide_below = input;
+            }
+            Message::SensorBackend(index) => {
+                if let Some(backend) = SensorBackend::ALL.get(index) {
+                    self.config.sensor_backend = *backend;
+                    self.update_sensor_options();
+                }
+            }
+            Message::SelectSensor(index) => {
+                // The first option is the automatic detection
+                self.config.selected_sensor = if index == 0 {
+                    None
+                } else {
+                    self.sensor_options.get(index).cloned()
+                };
+            }
+            Message::CoreAggregation(index) => {
+                if let Some(aggregation) = CoreAggregation::ALL.get(index) {
+                    self.config.core_aggregation = *aggregation;
+                }
+            }
+            Message::HottestCcd(enabled) => self.config.hottest_ccd = enabled,
+            Message::PreferTctl(enabled) => self.config.prefer_tctl = enabled,
+            Message::SensorBlacklist(input) => {
+                self.config.sensor_blacklist = input
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|pattern| !pattern.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                self.inputs.sensor_blacklist = input;
+            }
+            Message::ShowDriveTemps(enabled) => self.config.show_drive_temps = enabled,
+            Message::SamplePeriod(input) => {
+                if let Some(period) = parse_period(&input).period() {
+                    self.config.sample_period_ms = period;
+                }
+                self.inputs.sample_period = input;
+            }
+            Message::DisplayPeriod(input) => {
+                if !is_display_period_too_short(&input, &self.config) {
+                    if let Some(period) = parse_period(&input).period() {
+                        self.config.display_period_ms = period;
+                    }
+                }
+                self.inputs.display_period = input;
+            }
+            Message::BatterySaver(enabled) => self.config.battery_saver = enabled,
+            Message::BatteryMultiplier(index) => {
+                if let Some(multiplier) = BATTERY_MULTIPLIERS.get(index) {
+                    self.config.battery_period_multiplier = *multiplier;
+                }
+            }
+            Message::Smoothing(index) => {
+                if let Some(factor) = SMOOTHING_FACTORS.get(index) {
+                    self.config.smoothing = *factor;
+                }
+            }
+            Message::RoundingStep(index) => {
+                if let Some(step) = ROUNDING_STEPS.get(index) {
+                    self.config.rounding_step = *step;
+                }
+            }
+            Message::WarningThreshold(input) => {
+                self.inputs.warning_threshold = input;
+                self.apply_thresholds();
+            }
+            Message::CriticalThreshold(input) => {
+                self.inputs.critical_threshold = input;
+                self.apply_thresholds();
+            }
+            Message::CriticalNotification(enabled) => self.config.critical_notification = enabled,
+            Message::FlashOnCritical(enabled) => self.config.flash_on_critical = enabled,
+            Message::DisableAnimations(disabled) => self.config.disable_animations = disabled,
+            Message::AlertSound(input) => {
+                // Empty disables the sound
+                self.config.alert_sound =
+                    Some(input.trim().to_string()).filter(|sound| !sound.is_empty());
+                self.inputs.alert_sound = input;
+            }
+            // Empty turns the quiet hours off, anything else that isn't a time is ignored
+            Message::QuietHoursStart(input) => {
+                match input.trim() {
+                    "" => self.config.quiet_hours_start = None,
+                    value => {
+                        if let Some(minute) = quiet_hours::parse_time(value) {
+                            self.config.quiet_hours_start = Some(minute);
+                        }
+                    }
+                }
+                self.inputs.quiet_hours_start = input;
+            }
+            Message::QuietHoursEnd(input) => {
+                match input.trim() {
+                    "" => self.config.quiet_hours_end = None,
+                    value => {
+                        if let Some(minute) = quiet_hours::parse_time(value) {
+                            self.config.quiet_hours_end = Some(minute);
+                        }
+                    }
+                }
+                self.inputs.quiet_hours_end = input;
+            }
+            // Only saved on submit or when the input loses focus, the alert could otherwise
+            // run a half-typed command
+            Message::CriticalAction(input) => {
+                self.inputs.critical_action = input;
+                return Task::none();
+            }
+            Message::SubmitCriticalAction => {
+                // Empty disables the action
+                self.config.critical_action = Some(self.inputs.critical_action.trim())
+                    .filter(|command| !command.is_empty())
+                    .map(str::to_string);
+            }
+            Message::CriticalActionDelay(input) => {
+                if let Ok(seconds) = input.trim().parse::<u64>() {
+                    self.config.critical_action_delay_seconds = seconds;
+                }
+                self.inputs.critical_action_delay = input;
+            }
+            Message::MonitorCommand(input) => {
+                // Empty goes back to the default monitors
+                self.config.monitor_command =
+                    Some(input.trim().to_string()).filter(|command| !command.is_empty());
+                self.inputs.monitor_command = input;
+            }
+            Message::PersistHistory(enabled) => self.config.persist_history = enabled,
+            // Only saved on submit, every keystroke would start a new file
+            Message::LogPath(input) => {
+                self.inputs.log_path = input;
+                return Task::none();
+            }
+            Message::SubmitLogPath => {
+                // Empty stops logging
+                self.config.log_to_file = Some(self.inputs.log_path.trim())
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from);
+            }
+            // Only saved on submit, every keystroke would restart the writer with a limit that
+            // can rotate the log away
+            Message::LogMaxSize(input) => {
+                self.inputs.log_max_size = input;
+                return Task::none();
+            }
+            Message::SubmitLogMaxSize => {
+                if let Ok(megabytes) = self.inputs.log_max_size.trim().parse::<u32>() {
+                    if megabytes > 0 {
+                        self.config.log_max_size_mb = megabytes;
+                    }
+                }
+            }
+            Message::MetricsEnabled(enabled) => self.config.metrics_enabled = enabled,
+            // Only saved on submit, every keystroke would bind another port
+            Message::MetricsPort(input) => {
+                self.inputs.metrics_port = input;
+                return Task::none();
+            }
+            Message::SubmitMetricsPort => {
+                // Port 0 would bind to a random port nobody knows about
+                if let Ok(port) = self.inputs.metrics_port.trim().parse::<u16>() {
+                    if port != 0 {
+                        self.config.metrics_port = port;
+                    }
+                }
+            }
+            Message::MqttEnabled(enabled) => self.config.mqtt_enabled = enabled,
+            Message::SocketEnabled(enabled) => self.config.socket_enabled = enabled,
+            // Only saved on submit, every keystroke would connect again
+            Message::MqttBroker(input) => {
+                self.inputs.mqtt_broker = input;
+                return Task::none();
+            }
+            Message::MqttTopic(input) => {
+                self.inputs.mqtt_topic = input;
+                return Task::none();
+            }
+            Message::MqttCredentialsFile(input) => {
+                self.inputs.mqtt_credentials_file = input;
+                return Task::none();
+            }
+            Message::SubmitMqtt => {
+                let broker = self.inputs.mqtt_broker.trim();
+                if !broker.is_empty() {
+                    self.config.mqtt_broker = broker.to_string();
+                }
+                let topic = self.inputs.mqtt_topic.trim();
+                if !topic.is_empty() {
+                    self.config.mqtt_topic = topic.to_string();
+                }
+                // Empty connects without credentials
+                self.config.mqtt_credentials_file = Some(self.inputs.mqtt_credentials_file.trim())
+                    .filter(|path| !path.is_empty())
+                    .map(PathBuf::from);
+            }
+            Message::MqttIntervalMultiplier(input) => {
+                if let Ok(multiplier) = input.trim().parse::<u32>() {
+                    if multiplier > 0 {
+                        self.config.mqtt_interval_multiplier = multiplier;
+                    }
+                }
+                self.inputs.mqtt_interval_multiplier = input;
+            }
+            Message::MaxPlausible(input) => {
+                // Anything below the minimum would drop every reading
+                if let Ok(Some(celsius)) = parse_celsius(&input, unit) {
+                    if celsius > sanity::MIN_PLAUSIBLE_CELSIUS {
+                        self.config.max_plausible_celsius = celsius;
+                    }
+                }
+                self.inputs.max_plausible = input;
+            }
+            Message::Offset(input) => {
+                if let Ok(offset) = parse_celsius(&input, TemperatureUnit::Celsius) {
+                    self.config.offset_celsius = offset.unwrap_or_default();
+                }
+                self.inputs.offset = input;
+            }
+            Message::GraphRange(index) => {
+                if let Some(range) = GraphRange::ALL.get(index) {
+                    self.config.graph_range = *range;
+                }
+            }
+            Message::GraphFixedScale(enabled) => self.config.graph_fixed_scale = enabled,
+            // An empty or upside down axis is ignored
+            Message::GraphMin(input) => {
+                if let Ok(Some(celsius)) = parse_celsius(&input, unit) {
+                    if celsius < self.config.graph_max_celsius {
+                        self.config.graph_min_celsius = celsius;
+                    }
+                }
+                self.inputs.graph_min = input;
+            }
+            Message::GraphMax(input) => {
+                if let Ok(Some(celsius)) = parse_celsius(&input, unit) {
+                    if celsius > self.config.graph_min_celsius {
+                        self.config.graph_max_celsius = celsius;
+                    }
+                }
+                self.inputs.graph_max = input;
+            }
+        }
+
+        if self.config != old {
+            self.config_writer.save();
+        }
+        Task::none()
+    }
+
+    fn view(&self) -> Element<Self::Message> {
+        let panel = settings::section()
+            .title(fl!("settings-panel"))
+            .add(settings::item(
+                fl!("unit"),
+                dropdown(
+                    UNIT_OPTIONS.as_slice(),
+                    TemperatureUnit::ALL
+                        .iter()
+                        .position(|unit| *unit == self.config.unit),
+                    Message::Unit,
+                ),
+            ))
+            .add(settings::item(
+                fl!("display"),
+                dropdown(
+                    DISPLAY_MODE_OPTIONS.as_slice(),
+                    DisplayMode::ALL
+                        .iter()
+                        .position(|mode| *mode == self.config.display_mode),
+                    Message::DisplayMode,
+                ),
+            ))
+            .add(settings::item(
+                fl!("decimal-places"),
+                dropdown(
+                    DECIMAL_PLACES_OPTIONS,
+                    Some(self.config.decimal_places.min(MAX_DECIMAL_PLACES) as usize),
+                    Message::DecimalPlaces,
+                ),
+            ))
+            .add(settings::item(
+                fl!("rounding-step"),
+                dropdown(
+                    ROUNDING_STEP_OPTIONS.as_slice(),
+                    ROUNDING_STEPS
+                        .iter()
+                        .position(|step| *step == self.config.rounding_step),
+                    Message::RoundingStep,
+                ),
+            ))
+            .add(settings::item(
+                fl!("panel-format"),
+                text_input("", self.inputs.panel_format.as_str()).on_input(Message::PanelFormat),
+            ))
+            .add(settings::item(
+                fl!("panel-prefix"),
+                text_input("", self.inputs.panel_prefix.as_str()).on_input(Message::PanelPrefix),
+            ))
+            .add(settings::item(
+                fl!("panel-suffix"),
+                text_input("", self.inputs.panel_suffix.as_str()).on_input(Message::PanelSuffix),
+            ))
+            .add(settings::item(
+                fl!("show-icon"),
+                toggler(self.config.show_icon).on_toggle(Message::ShowIcon),
+            ))
+            .add(settings::item(
+                fl!("fixed-width"),
+                toggler(self.config.fixed_width).on_toggle(Message::FixedWidth),
+            ))
+            .add(settings::item(
+                fl!("show-both-units"),
+                toggler(self.config.show_both_units).on_toggle(Message::ShowBothUnits),
+            ))
+            .add(settings::item(
+                fl!("show-load"),
+                toggler(self.config.show_load).on_toggle(Message::ShowLoad),
+            ))
+            .add(settings::item(
+                fl!("load-without-sensors"),
+                toggler(self.config.load_without_sensors).on_toggle(Message::LoadWithoutSensors),
+            ))
+            .add(settings::item(
+                fl!("show-fan"),
+                toggler(self.config.show_fan).on_toggle(Message::ShowFan),
+            ))
+            .add(settings::item(
+                fl!("show-power"),
+                toggler(self.config.show_power).on_toggle(Message::ShowPower),
+            ))
+            .add(settings::item(
+                fl!("show-throttling"),
+                toggler(self.config.show_throttling).on_toggle(Message::ShowThrottling),
+            ))
+            .add(settings::item(
+                fl!("show-hot-core"),
+                toggler(self.config.show_hot_core).on_toggle(Message::ShowHotCore),
+            ))
+            .add(settings::item(
+                fl!("power-in-panel"),
+                toggler(self.config.power_in_panel).on_toggle(Message::PowerInPanel),
+            ))
+            .add(settings::item(
+                fl!("panel-style"),
+                dropdown(
+                    PANEL_STYLE_OPTIONS.as_slice(),
+                    PanelStyle::ALL
+                        .iter()
+                        .position(|style| *style == self.config.panel_style),
+                    Message::PanelStyle,
+                ),
+            ))
+            .add(settings::item(
+                fl!("threshold-indication"),
+                dropdown(
+                    THRESHOLD_INDICATION_OPTIONS.as_slice(),
+                    ThresholdIndication::ALL
+                        .iter()
+                        .position(|indication| *indication == self.config.threshold_indication),
+                    Message::ThresholdIndication,
+                ),
+            ))
+            .add(settings::item(
+                fl!("hide-below", unit = self.unit_symbol()),
+                text_input("", self.inputs.hide_below.as_str()).on_input(Message::HideBelow),
+            ));
+
+        let sensors = settings::section()
+            .title(fl!("settings-sensors"))
+            .add(settings::item(
+                fl!("sensor-backend"),
+                dropdown(
+                    SENSOR_BACKEND_OPTIONS,
+                    SensorBackend::ALL
+                        .iter()
+                        .position(|backend| *backend == self.config.sensor_backend),
+                    Message::SensorBackend,
+                ),
+            ))
+            .add(settings::item(
+                fl!("sensor"),
+                dropdown(
+                    self.sensor_option_names.as_slice(),
+                    Some(match &self.config.selected_sensor {
+                        Some(selected) => self
+                            .sensor_options
+                            .iter()
+                            .skip(1)
+                            .position(|label| label == selected)
+                            .map_or(0, |index| index + 1),
+                        None => 0,
+                    }),
+                    Message::SelectSensor,
+                ),
+            ))
+            .add(settings::item(
+                fl!("combine-cores"),
+                dropdown(
+                    CORE_AGGREGATION_OPTIONS.as_slice(),
+                    CoreAggregation::ALL
+                        .iter()
+                        .position(|aggregation| *aggregation == self.config.core_aggregation),
+                    Message::CoreAggregation,
+                ),
+            ))
+            .add(settings::item(
+                fl!("hottest-ccd"),
+                toggler(self.config.hottest_ccd).on_toggle(Message::HottestCcd),
+            ))
+            .add(settings::item(
+                fl!("prefer-tctl"),
+                toggler(self.config.prefer_tctl).on_toggle(Message::PreferTctl),
+            ))
+            .add(settings::item(
+                fl!("ignored-sensors"),
+                text_input("pch_*", self.inputs.sensor_blacklist.as_str())
+                    .on_input(Message::SensorBlacklist),
+            ))
+            .add(settings::item(
+                fl!("show-drive-temps"),
+                toggler(self.config.show_drive_temps).on_toggle(Message::ShowDriveTemps),
+            ));
+
+        let mut refreshing = settings::section()
+            .title(fl!("settings-refreshing"))
+            .add(settings::item(
+                fl!("refresh-interval"),
+                text_input("1000", self.inputs.sample_period.as_str())
+                    .on_input(Message::SamplePeriod),
+            ))
+            .add(settings::item(
+                fl!("display-interval"),
+                text_input("1000", self.inputs.display_period.as_str())
+                    .on_input(Message::DisplayPeriod),
+            ));
+        if is_display_period_too_short(&self.inputs.display_period, &self.config) {
+            refreshing = refreshing.add(self.error_caption(fl!("display-interval-too-short")));
+        }
+        let refreshing = refreshing
+            .add(settings::item(
+                fl!("slower-refresh-on-battery"),
+                toggler(self.config.battery_saver).on_toggle(Message::BatterySaver),
+            ))
+            .add(settings::item(
+                fl!("battery-refresh-multiplier"),
+                dropdown(
+                    BATTERY_MULTIPLIER_OPTIONS,
+                    BATTERY_MULTIPLIERS.iter().position(|multiplier| {
+                        *multiplier == self.config.battery_period_multiplier
+                    }),
+                    Message::BatteryMultiplier,
+                ),
+            ))
+            .add(settings::item(
+                fl!("smoothing"),
+                dropdown(
+                    SMOOTHING_OPTIONS.as_slice(),
+                    SMOOTHING_FACTORS
+                        .iter()
+                        .position(|factor| *factor == self.config.smoothing),
+                    Message::Smoothing,
+                ),
+            ));
+
+        let mut alerts = settings::section()
+            .title(fl!("settings-alerts"))
+            .add(settings::item(
+                fl!("warning-threshold", unit = self.unit_symbol()),
+                text_input("", self.inputs.warning_threshold.as_str())
+                    .on_input(Message::WarningThreshold),
+            ))
+            .add(settings::item(
+                fl!("critical-threshold", unit = self.unit_symbol()),
+                text_input("", self.inputs.critical_threshold.as_str())
+                    .on_input(Message::CriticalThreshold),
+            ));
+        let saved_thresholds = (
+            self.config.warning_threshold_celsius,
+            self.config.critical_threshold_celsius,
+        );
+        let thresholds_in_order = threshold_pair(
+            &self.inputs.warning_threshold,
+            &self.inputs.critical_threshold,
+            self.config.unit,
+            saved_thresholds,
+        )
+        .is_some();
+        if !thresholds_in_order {
+            alerts = alerts.add(self.error_caption(fl!("thresholds-out-of-order")));
+        }
+        let alerts = alerts
+            .add(settings::item(
+                fl!("notify-critical"),
+                toggler(self.config.critical_notification).on_toggle(Message::CriticalNotification),
+            ))
+            .add(settings::item(
+                fl!("flash-on-critical"),
+                toggler(self.config.flash_on_critical).on_toggle(Message::FlashOnCritical),
+            ))
+            .add(settings::item(
+                fl!("disable-animations"),
+                toggler(self.config.disable_animations).on_toggle(Message::DisableAnimations),
+            ))
+            .add(settings::item(
+                fl!("alert-sound"),
+                text_input("dialog-warning", self.inputs.alert_sound.as_str())
+                    .on_input(Message::AlertSound),
+            ))
+            .add(settings::item(
+                fl!("quiet-hours"),
+                row![
+                    text_input("22:00", self.inputs.quiet_hours_start.as_str())
+                        .on_input(Message::QuietHoursStart),
+                    text("–"),
+                    text_input("07:00", self.inputs.quiet_hours_end.as_str())
+                        .on_input(Message::QuietHoursEnd),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+            ))
+            .add(settings::item(
+                fl!("critical-action"),
+                text_input("systemctl suspend", self.inputs.critical_action.as_str())
+                    .on_input(Message::CriticalAction)
+                    .on_submit(Message::SubmitCriticalAction)
+                    .on_unfocus(Message::SubmitCriticalAction),
+            ))
+            .add(settings::item(
+                fl!("critical-action-delay"),
+                text_input("30", self.inputs.critical_action_delay.as_str())
+                    .on_input(Message::CriticalActionDelay),
+            ));
+
+        let other = settings::section()
+            .title(fl!("settings-other"))
+            .add(settings::item(
+                fl!("monitor-command"),
+                text_input("", self.inputs.monitor_command.as_str())
+                    .on_input(Message::MonitorCommand),
+            ))
+            .add(settings::item(
+                fl!("persist-history"),
+                toggler(self.config.persist_history).on_toggle(Message::PersistHistory),
+            ))
+            .add(settings::item(
+                fl!("graph-range"),
+                dropdown(
+                    GRAPH_RANGE_OPTIONS.as_slice(),
+                    GraphRange::ALL
+                        .iter()
+                        .position(|range| *range == self.config.graph_range),
+                    Message::GraphRange,
+                ),
+            ))
+            .add(settings::item(
+                fl!("graph-fixed-scale"),
+                toggler(self.config.graph_fixed_scale).on_toggle(Message::GraphFixedScale),
+            ))
+            .add(settings::item(
+                fl!("graph-min", unit = self.unit_symbol()),
+                text_input("", self.inputs.graph_min.as_str()).on_input(Message::GraphMin),
+            ))
+            .add(settings::item(
+                fl!("graph-max", unit = self.unit_symbol()),
+                text_input("", self.inputs.graph_max.as_str()).on_input(Message::GraphMax),
+            ))
+            .add(settings::item(
+                fl!("log-file"),
+                text_input("readings.csv", self.inputs.log_path.as_str())
+                    .on_input(Message::LogPath)
+                    .on_submit(Message::SubmitLogPath),
+            ))
+            .add(settings::item(
+                fl!("log-max-size"),
+                text_input("10", self.inputs.log_max_size.as_str())
+                    .on_input(Message::LogMaxSize)
+                    .on_submit(Message::SubmitLogMaxSize),
+            ))
+            .add(settings::item(
+                fl!("metrics-enabled"),
+                toggler(self.config.metrics_enabled).on_toggle(Message::MetricsEnabled),
+            ))
+            .add(settings::item(
+                fl!("metrics-port"),
+                text_input("9101", self.inputs.metrics_port.as_str())
+                    .on_input(Message::MetricsPort)
+                    .on_submit(Message::SubmitMetricsPort),
+            ))
+            .add(settings::item(
+                fl!("mqtt-enabled"),
+                toggler(self.config.mqtt_enabled).on_toggle(Message::MqttEnabled),
+            ))
+            .add(settings::item(
+                fl!("mqtt-broker"),
+                text_input("mqtt://localhost:1883", self.inputs.mqtt_broker.as_str())
+                    .on_input(Message::MqttBroker)
+                    .on_submit(Message::SubmitMqtt),
+            ))
+            .add(settings::item(
+                fl!("mqtt-topic"),
+                text_input("cosmic/cpu_temperature", self.inputs.mqtt_topic.as_str())
+                    .on_input(Message::MqttTopic)
+                    .on_submit(Message::SubmitMqtt),
+            ))
+            .add(settings::item(
+                fl!("mqtt-credentials-file"),
+                text_input("", self.inputs.mqtt_credentials_file.as_str())
+                    .on_input(Message::MqttCredentialsFile)
+                    .on_submit(Message::SubmitMqtt),
+            ))
+            .add(settings::item(
+                fl!("mqtt-interval-multiplier"),
+                text_input("1", self.inputs.mqtt_interval_multiplier.as_str())
+                    .on_input(Message::MqttIntervalMultiplier),
+            ))
+            .add(settings::item(
+                fl!("socket-enabled"),
+                toggler(self.config.socket_enabled).on_toggle(Message::SocketEnabled),
+            ))
+            .add(settings::item(
+                fl!("max-plausible", unit = self.unit_symbol()),
+                text_input("125", self.inputs.max_plausible.as_str())
+                    .on_input(Message::MaxPlausible),
+            ))
+            .add(settings::item(
+                fl!("temperature-offset"),
+                text_input("0", self.inputs.offset.as_str()).on_input(Message::Offset),
+            ));
+
+        scrollable(
+            settings::view_column(vec![
+                panel.into(),
+                sensors.into(),
+                refreshing.into(),
+                alerts.into(),
+                other.into(),
+            ])
+            .padding(16),
+        )
+        .width(Length::Fill)
+        .into()
+    }
+}
@@ -0,0 +1,216 @@
+// Local access to every sensor for scripts, on a Unix socket in the runtime directory.
+// A client gets one JSON document and the connection is closed:
+//
+// {"components":[{"label":"Tctl","chip":"k10temp","celsius":54.2,"max":null,"critical":95.0}]}
+//
+// Sending "stream" first keeps the connection open, with another line after every reading:
+//
+// echo stream | socat - UNIX-CONNECT:$XDG_RUNTIME_DIR/cosmic-cpu-temp.sock
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::watch;
+use tokio::task::JoinSet;
+use tokio::time;
+
+use crate::sysinfo_utils::RawSensor;
+
+const SOCKET_NAME: &str = "cosmic-cpu-temp";
+// Clients that only want the document don't have to send anything, they get it after this
+const REQUEST_TIMEOUT: time::Duration = time::Duration::from_millis(200);
+const STREAM_COMMAND: &str = "stream";
+
+#[derive(Debug, Serialize)]
+struct Component<'a> {
+    label: &'a str,
+    chip: Option<&'a str>,
+    celsius: Option<f32>,
+    max: Option<f32>,
+    critical: Option<f32>,
+}
+
+#[derive(Debug, Serialize)]
+struct Document<'a> {
+    components: Vec<Component<'a>>,
+}
+
+// A single line, so streaming clients can split on newlines
+pub(crate) fn document(sensors: &[RawSensor]) -> String {
+    let document = Document {
+        components: sensors
+            .iter()
+            .map(|sensor| Component {
+                label: &sensor.label,
+                chip: sensor.chip.as_deref(),
+                celsius: sensor.temp,
+                max: sensor.max,
+                critical: sensor.critical,
+            })
+            .collect(),
+    };
+    // Serializing strings and numbers can't fail
+    let mut line = serde_json::to_string(&document).unwrap_or_default();
+    line.push('\n');
+    line
+}
+
+// `$XDG_RUNTIME_DIR/cosmic-cpu-temp.sock`, or `cosmic-cpu-temp-<instance>.sock` for a named
+// instance. `None` without a runtime directory, /tmp is shared with other users.
+pub(crate) fn socket_path(instance: Option<&str>) -> Option<PathBuf> {
+    let dir = std::env::var_os("XDG_RUNTIME_DIR").filter(|dir| !dir.is_empty())?;
+    let name = match instance {
+        Some(instance) => format!("{SOCKET_NAME}-{instance}.sock"),
+        None => format!("{SOCKET_NAME}.sock"),
+    };
+    Some(Path::new(&dir).join(name))
+}
+
+// Removes the socket when the server stops, a leftover one would look like a running applet
+struct SocketFile(PathBuf);
+
+impl Drop for SocketFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+// Answers clients with the latest document in `export` until the future is dropped, which
+// happens when the subscription running it goes away (the option was turned off). The
+// clients go with it.
+pub(crate) async fn serve(path: PathBuf, export: watch::Receiver<String>) {
+    // Left behind by a crash, binding fails otherwise
+    let _ = std::fs::remove_file(&path);
+    let listener = match UnixListener::bind(&path) {
+        Ok(listener) => listener,
+        Err(err) => {
+            tracing::error!(?err, path = %path.display(), "Error creating the sensor socket");
+            return;
+        }
+    };
+    let _socket_file = SocketFile(path);
+
+    let mut clients = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    clients.spawn(respond(stream, export.clone()));
+                }
+                Err(err) => {
+                    tracing::error!(?err, "Error accepting a sensor socket connection");
+                    // Out of file descriptors or similar, don't spin on it
+                    time::sleep(time::Duration::from_secs(1)).await;
+                }
+            },
+            Some(result) = clients.join_next() => {
+                // Mostly clients that went away while streaming
+                if let Ok(Err(err)) = result {
+                    tracing::debug!(?err, "Sensor socket client gone");
+                }
+            }
+        }
+    }
+}
+
+async fn respond(stream: UnixStream, mut export: watch::Receiver<String>) -> io::Result<()> {
+    let (reader, mut writer) = stream.into_split();
+    let mut request = String::new();
+    let _ = time::timeout(
+        REQUEST_TIMEOUT,
+        BufReader::new(reader).read_line(&mut request),
+    )
+    .await;
+    let streaming = request.trim() == STREAM_COMMAND;
+
+    loop {
+        let document = export.borrow_and_update().clone();
+        writer.write_all(document.as_bytes()).await?;
+        // The applet going away ends the stream as well
+        if !streaming || export.changed().await.is_err() {
+            break;
+        }
+    }
+
+    writer.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::io::AsyncReadExt;
+
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    // The server binds in the background
+    async fn connect(path: &Path) -> UnixStream {
+        loop {
+            if let Ok(stream) = UnixStream::connect(path).await {
+                return stream;
+            }
+            time::sleep(time::Duration::from_millis(10)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn answers_requests_and_streams() {
+        let dir = TestDir::new("socket");
+        let path = dir.join("test.sock");
+        let (sender, export) = watch::channel("first\n".to_string());
+        let server = tokio::spawn(serve(path.clone(), export));
+
+        // Nothing sent, the document comes after the request timeout
+        let mut response = String::new();
+        connect(&path)
+            .await
+            .read_to_string(&mut response)
+            .await
+            .unwrap();
+        assert_eq!(response, "first\n");
+
+        let (reader, mut writer) = connect(&path).await.into_split();
+        writer.write_all(b"stream\n").await.unwrap();
+        let mut lines = BufReader::new(reader).lines();
+        assert_eq!(lines.next_line().await.unwrap().as_deref(), Some("first"));
+        sender.send("second\n".to_string()).unwrap();
+        assert_eq!(lines.next_line().await.unwrap().as_deref(), Some("second"));
+
+        // Dropping the server ends the stream and removes the socket
+        server.abort();
+        let _ = server.await;
+        assert_eq!(lines.next_line().await.unwrap(), None);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn documents_every_component_on_one_line() {
+        let sensors = [
+            RawSensor {
+                label: "Tctl".to_string(),
+                chip: Some("k10temp".to_string()),
+                temp: Some(54.5),
+                max: None,
+                critical: Some(95.0),
+            },
+            RawSensor {
+                label: "Composite".to_string(),
+                chip: None,
+                temp: None,
+                max: Some(81.85),
+                critical: None,
+            },
+        ];
+
+        assert_eq!(
+            document(&sensors),
+            "{\"components\":[\
+             {\"label\":\"Tctl\",\"chip\":\"k10temp\",\"celsius\":54.5,\"max\":null,\"critical\":95.0},\
+             {\"label\":\"Composite\",\"chip\":null,\"celsius\":null,\"max\":81.85,\"critical\":null}\
+             ]}\n"
+        );
+        assert_eq!(document(&[]), "{\"components\":[]}\n");
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::{Path, PathBuf};
+
+use tokio::process::Command;
+
+use crate::fl;
+
+// Looked up when canberra-gtk-play isn't installed
+const SOUND_THEME: &str = "freedesktop";
+const SOUND_EXTENSIONS: [&str; 3] = ["oga", "ogg", "wav"];
+const DEFAULT_DATA_DIRS: &str = "/usr/local/share:/usr/share";
+
+// Anything with a slash is a file, everything else a sound event name like "bell"
+fn is_file(sound: &str) -> bool {
+    sound.contains('/')
+}
+
+// `<data dir>/sounds/freedesktop/stereo/<name>.oga` and the like, the first that exists
+fn find_theme_sound(name: &str) -> Option<PathBuf> {
+    let data_dirs = std::env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| DEFAULT_DATA_DIRS.to_string());
+
+    data_dirs
+        .split(':')
+        .flat_map(|dir| {
+            SOUND_EXTENSIONS.iter().map(move |extension| {
+                Path::new(dir)
+                    .join("sounds")
+                    .join(SOUND_THEME)
+                    .join("stereo")
+                    .join(format!("{name}.{extension}"))
+            })
+        })
+        .find(|path| path.is_file())
+}
+
+// Plays a sound file or a sound event of the XDG sound theme. canberra-gtk-play knows the
+// user's theme, paplay is the fallback for desktops without it. Returns a description of
+// the failure.
+pub(crate) async fn play(sound: String) -> Result<(), String> {
+    let sound = sound.trim();
+    let canberra_flag = if is_file(sound) { "-f" } else { "-i" };
+    match Command::new("canberra-gtk-play")
+        .arg(canberra_flag)
+        .arg(sound)
+        .status()
+        .await
+    {
+        Ok(status) if status.success() => return Ok(()),
+        Ok(status) => tracing::debug!(%status, sound, "canberra-gtk-play failed, trying paplay"),
+        Err(err) => tracing::debug!(?err, "canberra-gtk-play unavailable, trying paplay"),
+    }
+
+    let path = if is_file(sound) {
+        PathBuf::from(sound)
+    } else {
+        find_theme_sound(sound).ok_or_else(|| {
+            tracing::error!(sound, "Sound event not found");
+            fl!("sound-not-found", sound = sound)
+        })?
+    };
+    let status = Command::new("paplay")
+        .arg(&path)
+        .status()
+        .await
+        .map_err(|err| {
+            tracing::error!(?err, "Failed to run paplay");
+            err.to_string()
+        })?;
+
+    if !status.success() {
+        tracing::error!(%status, path = %path.display(), "Playing the alert sound failed");
+        return Err(status.to_string());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// Readings are combined into buckets of this length, so memory doesn't depend on the
+// refresh period
+const BUCKET_DURATION: Duration = Duration::from_secs(30);
+
+// Periods the popup shows statistics for, the last one is how long buckets are kept
+pub(crate) const STATS_WINDOWS: [Duration; 2] =
+    [Duration::from_secs(10 * 60), Duration::from_secs(60 * 60)];
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    start: Instant,
+    min: f32,
+    max: f32,
+    sum: f64,
+    count: u32,
+}
+
+// All in Celsius
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TempSummary {
+    pub(crate) min: f32,
+    pub(crate) avg: f32,
+    pub(crate) max: f32,
+}
+
+// Minimum, average and maximum of the readings over the `STATS_WINDOWS`. The windows are
+// only as precise as `BUCKET_DURATION`.
+#[derive(Debug, Default)]
+pub(crate) struct TempStats {
+    buckets: VecDeque<Bucket>,
+}
+
+impl TempStats {
+    pub(crate) fn push(&mut self, celsius: f32) {
+        self.push_at(Instant::now(), celsius);
+    }
+
+    fn push_at(&mut self, now: Instant, celsius: f32) {
+        let longest = STATS_WINDOWS[STATS_WINDOWS.len() - 1];
+        while self
+            .buckets
+            .front()
+            .is_some_and(|bucket| now.duration_since(bucket.start) >= longest)
+        {
+            self.buckets.pop_front();
+        }
+
+        match self.buckets.back_mut() {
+            Some(bucket) if now.duration_since(bucket.start) < BUCKET_DURATION => {
+                bucket.min = bucket.min.min(celsius);
+                bucket.max = bucket.max.max(celsius);
+                bucket.sum += celsius as f64;
+                bucket.count += 1;
+            }
+            _ => self.buckets.push_back(Bucket {
+                start: now,
+                min: celsius,
+                max: celsius,
+                sum: celsius as f64,
+                count: 1,
+            }),
+        }
+    }
+
+    // `None` until there is a reading in the window
+    pub(crate) fn summary(&self, window: Duration) -> Option<TempSummary> {
+        self.summary_at(Instant::now(), window)
+    }
+
+    fn summary_at(&self, now: Instant, window: Duration) -> Option<TempSummary> {
+        let buckets = self
+            .buckets
+            .iter()
+            .filter(|bucket| now.duration_since(bucket.start) < window);
+
+        let (min, max, sum, count) = buckets.fold(
+            (f32::INFINITY, f32::NEG_INFINITY, 0.0, 0),
+            |(min, max, sum, count), bucket| {
+                (
+                    min.min(bucket.min),
+                    max.max(bucket.max),
+                    sum + bucket.sum,
+                    count + bucket.count,
+                )
+            },
+        );
+
+        (count > 0).then(|| TempSummary {
+            min,
+            avg: (sum / count as f64) as f32,
+            max,
+        })
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.buckets.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHORT: Duration = STATS_WINDOWS[0];
+    const LONG: Duration = STATS_WINDOWS[1];
+
+    #[test]
+    fn summarizes_each_window() {
+        let start = Instant::now();
+        let at = |seconds| start + Duration::from_secs(seconds);
+        let mut stats = TempStats::default();
+        // Only the long window still has the first two
+        stats.push_at(at(0), 90.0);
+        stats.push_at(at(10), 30.0);
+        stats.push_at(at(45 * 60), 50.0);
+        stats.push_at(at(45 * 60 + 10), 60.0);
+        stats.push_at(at(50 * 60), 70.0);
+
+        let now = at(50 * 60 + 10);
+        assert_eq!(
+            stats.summary_at(now, SHORT),
+            Some(TempSummary {
+                min: 50.0,
+                avg: 60.0,
+                max: 70.0
+            })
+        );
+        assert_eq!(
+            stats.summary_at(now, LONG),
+            Some(TempSummary {
+                min: 30.0,
+                avg: 60.0,
+                max: 90.0
+            })
+        );
+    }
+
+    #[test]
+    fn drops_readings_older_than_the_longest_window() {
+        let start = Instant::now();
+        let mut stats = TempStats::default();
+        stats.push_at(start, 90.0);
+        let later = start + LONG + BUCKET_DURATION;
+        stats.push_at(later, 40.0);
+
+        assert_eq!(stats.buckets.len(), 1);
+        assert_eq!(
+            stats.summary_at(later, LONG),
+            Some(TempSummary {
+                min: 40.0,
+                avg: 40.0,
+                max: 40.0
+            })
+        );
+        assert_eq!(stats.summary_at(later + LONG, LONG), None);
+    }
+
+    #[test]
+    fn clear_forgets_every_reading() {
+        let mut stats = TempStats::default();
+        stats.push(55.0);
+        assert!(stats.summary(SHORT).is_some());
+
+        stats.clear();
+        assert_eq!(stats.summary(LONG), None);
+    }
+}
@@ -1,106 +1,2400 @@
-use std::{cmp::Ordering, i32::MAX};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
 
 use once_cell::sync::Lazy;
 use regex::Regex;
-use sysinfo::{Component, Components};
+use sysinfo::Components;
 
-// In order of priority
-const OVERALL_CPU_TEMP_LABELS: &'static [&'static str] = &[
-    // AMD CPUs
-    "Tctl",
+use crate::config::{CoreAggregation, SensorBackend};
+use crate::cpu_load::CpuLoad;
+use crate::fans::{self, Fan};
+use crate::hwmon::{self, HwmonProvider};
+use crate::rapl::RaplMeter;
+use crate::sanity::{self, SaneProvider};
+use crate::thermal_zone;
+use crate::throttle::{Throttle, ThrottleMonitor};
+
+// In order of priority. The amdgpu ones come first, edge is what most tools report as the
+// GPU temperature, followed by those of nouveau and nvidia.
+const GPU_TEMP_LABELS: &'static [&'static str] = &["edge", "junction", "mem", "GPU core", "GPU"];
+
+// Drivers of AMD CPUs, zenpower replaces k10temp on some setups
+const AMD_CPU_CHIPS: &[&str] = &["k10temp", "zenpower"];
+
+// Prefixes of components that belong to a GPU driver but don't use a known label
+const GPU_DRIVER_NAMES: &'static [&'static str] = &["amdgpu", "radeon", "nouveau", "nvidia"];
+
+// Labels (or their prefixes) of NVMe and SATA drive sensors, compared case-insensitively
+const DRIVE_LABEL_PREFIXES: &'static [&'static str] = &["composite", "nvme", "drivetemp"];
+
+// Labels (or their prefixes) of sensors that don't measure anything useful, compared
+// case-insensitively. Some ACPI zones report a constant 26.8 °C.
+const JUNK_LABEL_PREFIXES: &'static [&'static str] = &["acpitz"];
+
+// Full re-enumeration of components, picks up sensors that appeared since the last one
+const RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+// Don't re-enumerate on every read when there is nothing to find
+const MIN_RESCAN_INTERVAL: Duration = Duration::from_secs(5);
+
+// Kinds of overall CPU sensors, multi-socket systems have one match per socket
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OverallLabel {
+    // AMD die temperature, without the offset some Ryzen parts add to Tctl. zenpower always
+    // reports it, Tctl can be missing there.
+    Tdie,
+    // AMD control temperature, one k10temp instance per socket
+    Tctl,
     // Intel CPUs
-    "Package id 0",
+    Package,
+    // "CPU" of the laptop's embedded controller (thinkpad_acpi, dell_smm). Read by the
+    // firmware, so only used without a sensor of the CPU's own driver.
+    Ec,
     // CPU Temp from some motherboards
-    "CPU Temperature",
+    Motherboard,
+}
+
+impl OverallLabel {
+    fn regex(self) -> &'static Regex {
+        &OVERALL_CPU_TEMP_REGEXES[self as usize]
+    }
+}
+
+// In order of priority
+const DEFAULT_OVERALL_ORDER: &[OverallLabel] = &[
+    OverallLabel::Tdie,
+    OverallLabel::Tctl,
+    OverallLabel::Package,
+    OverallLabel::Ec,
+    OverallLabel::Motherboard,
+];
+// Tctl is what fan curves use, so some people want to see that instead
+const TCTL_FIRST_OVERALL_ORDER: &[OverallLabel] = &[
+    OverallLabel::Tctl,
+    OverallLabel::Tdie,
+    OverallLabel::Package,
+    OverallLabel::Ec,
+    OverallLabel::Motherboard,
 ];
 
-static INTEL_CPU_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^CPU [\d]{1}$").unwrap());
-static AMD_CPU_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Tctl[\d]{1}$").unwrap());
+// Same order as the `OverallLabel` variants
+static OVERALL_CPU_TEMP_REGEXES: Lazy<[Regex; 5]> = Lazy::new(|| {
+    [
+        Regex::new(r"^Tdie$").unwrap(),
+        Regex::new(r"^Tctl$").unwrap(),
+        Regex::new(r"^Package id \d+$").unwrap(),
+        Regex::new(r"^CPU( Temp)?$").unwrap(),
+        Regex::new(r"^CPU Temperature$").unwrap(),
+    ]
+});
 
-// Returns -1 if not found, priority otherwise.
-// Lower number means higher priority
-fn get_overall_temperature_component_index(component: &Component) -> i32 {
-    let mut index: i32 = -1;
-    for item in OVERALL_CPU_TEMP_LABELS {
-        if *item == component.label() {
-            return index;
-        }
+// Per-core labels from EC drivers on some laptops ("CPU 0")
+static INTEL_CPU_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^CPU \d+$").unwrap());
+// Per-core labels from coretemp ("Core 0")
+static INTEL_CORE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Core \d+$").unwrap());
+static AMD_CPU_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Tctl\d+$").unwrap());
+// Per-CCD (core complex die) labels from k10temp and zenpower ("Tccd1")
+static AMD_CCD_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^Tccd\d+$").unwrap());
+
+// (label, temperature) pairs of all components that aren't ignored, for the helpers below
+fn get_readings<'a>(
+    components: &'a Components,
+    options: &'a ReadOptions,
+) -> impl Iterator<Item = (&'a str, Option<f32>)> {
+    components
+        .iter()
+        .filter(|comp| !options.is_ignored(comp.label()))
+        .map(|comp| {
+            (
+                comp.label(),
+                options.plausible(comp.temperature(), comp.label()),
+            )
+        })
+}
+
+// sysinfo puts the driver name in front of the sensor's own label, e.g. "k10temp Tctl".
+// Driver names are lowercase, which tells that apart from "Package id 0" of hwmon.
+fn sysinfo_chip(label: &str) -> Option<&str> {
+    let (chip, rest) = label.split_once(' ')?;
+    let is_driver = chip
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    (is_driver && !rest.trim().is_empty()).then_some(chip)
+}
+
+// The label without the driver name sysinfo puts in front of it
+pub(crate) fn strip_chip<'a>(label: &'a str, chip: &str) -> &'a str {
+    label
+        .strip_prefix(chip)
+        .map(str::trim_start)
+        .filter(|name| !name.is_empty())
+        .unwrap_or(label)
+}
+
+// What the sensor detection matches, "Tctl" for both sysinfo's "k10temp Tctl" and hwmon's
+// "Tctl". Labels keep the driver name everywhere else, they're what sensors are selected by.
+fn sensor_label(label: &str) -> &str {
+    sysinfo_chip(label).map_or(label, |chip| strip_chip(label, chip))
+}
+
+// "Tctl (k10temp)", or just the label when the driver isn't known
+pub(crate) fn sensor_name(label: &str, chip: Option<&str>) -> String {
+    match chip {
+        Some(chip) => format!("{} ({chip})", strip_chip(label, chip)),
+        None => label.to_string(),
+    }
+}
 
-        index = index + 1;
+// Names to show for `labels`, using the drivers in `chips`
+pub(crate) fn sensor_names(labels: &[String], chips: &[(String, String)]) -> Vec<String> {
+    labels
+        .iter()
+        .map(|label| {
+            let chip = chips
+                .iter()
+                .find(|(other, _)| other == label)
+                .map(|(_, chip)| chip.as_str());
+            sensor_name(label, chip)
+        })
+        .collect()
+}
+
+// Sensors of a GPU driver are never taken for the CPU. On APUs amdgpu covers the same die
+// as k10temp, but the CPU is managed by the k10temp values.
+fn is_gpu_chip(chip: Option<&str>) -> bool {
+    chip.is_some_and(|chip| GPU_DRIVER_NAMES.contains(&chip))
+}
+
+// A selected GPU sensor is still shown as asked
+fn counts_for_cpu(label: &str, chip: Option<&str>, options: &ReadOptions) -> bool {
+    !is_gpu_chip(chip) || options.selected_sensor.as_deref() == Some(label)
+}
+
+// Another sensor on the CPU's package, mentioned next to it so it's clear which of the two
+// was picked. On AMD APUs that's amdgpu's "edge" next to k10temp.
+fn get_apu_alternative(
+    cpu_sensor: Option<&CpuSensor>,
+    chips: &[(String, String)],
+) -> Option<String> {
+    let Some(CpuSensor::Component { label, chip }) = cpu_sensor else {
+        return None;
+    };
+    let name = chip
+        .as_deref()
+        .map_or(label.as_str(), |chip| strip_chip(label, chip));
+    let name = sensor_label(name);
+    let amd_cpu = chip
+        .as_deref()
+        .is_some_and(|chip| AMD_CPU_CHIPS.contains(&chip))
+        || OverallLabel::Tctl.regex().is_match(name)
+        || OverallLabel::Tdie.regex().is_match(name)
+        || AMD_CCD_REGEX.is_match(name);
+    if !amd_cpu {
+        return None;
     }
 
-    return index;
+    chips
+        .iter()
+        .find(|(label, chip)| chip == "amdgpu" && sensor_label(label) == GPU_TEMP_LABELS[0])
+        .map(|(label, chip)| sensor_name(label, Some(chip)))
 }
 
-fn get_overall_cpu_temp(components: &Components) -> Option<f32> {
-    let mut target: Option<&Component> = None;
-    let mut current_priority = MAX;
-    for comp in components.iter() {
-        let priority = get_overall_temperature_component_index(comp);
-        // Not a component we need
-        if priority == -1 {
-            continue;
+// (chip, label, temperature) of every sensor, ignored and unread ones included. Grouped by
+// chip in the order the chips first appear, sensors keep the driver's order within a chip.
+fn group_by_chip(
+    sensors: impl IntoIterator<Item = (String, String, Option<f32>)>,
+) -> Vec<(String, String, Option<f32>)> {
+    let mut chips: Vec<String> = vec![];
+    let mut sensors: Vec<(String, String, Option<f32>)> = sensors.into_iter().collect();
+    for (chip, _, _) in &sensors {
+        if !chips.contains(chip) {
+            chips.push(chip.clone());
         }
+    }
+    sensors.sort_by_key(|(chip, _, _)| chips.iter().position(|known| known == chip));
+    sensors
+}
 
+// Everything sysinfo found, for the diagnostic list in the popup
+pub(crate) fn list_all_components(components: &Components) -> Vec<(String, String, Option<f32>)> {
+    group_by_chip(components.iter().map(|comp| {
+        (
+            sysinfo_chip(comp.label())
+                .unwrap_or(comp.label())
+                .to_string(),
+            comp.label().to_string(),
+            comp.temperature(),
+        )
+    }))
+}
+
+// Lower number means higher priority, `None` if the label isn't an overall CPU sensor
+fn get_overall_label_priority(label: &str, order: &[OverallLabel]) -> Option<usize> {
+    let label = sensor_label(label);
+    order.iter().position(|kind| kind.regex().is_match(label))
+}
+
+// Temperatures of every socket, only from the highest priority label kind that has any
+// readings, so a motherboard sensor never gets mixed with the package sensors
+fn get_overall_temps<'a>(
+    readings: impl IntoIterator<Item = (&'a str, Option<f32>)>,
+    order: &[OverallLabel],
+) -> Vec<f32> {
+    let mut temps = Vec::new();
+    let mut current_priority = usize::MAX;
+    for (label, temp) in readings {
+        let Some(priority) = get_overall_label_priority(label, order) else {
+            continue;
+        };
         // Doesn't provide temperature
-        if comp.temperature().is_none() {
+        let Some(temp) = temp else {
             continue;
-        }
+        };
 
         // Lower priority over what we found already
         if priority > current_priority {
             continue;
         }
 
-        target = Some(comp);
-        current_priority = priority;
+        if priority < current_priority {
+            temps.clear();
+            current_priority = priority;
+        }
+        temps.push(temp);
+    }
+
+    temps
+}
+
+fn is_core_temp_label(label: &str) -> bool {
+    let label = sensor_label(label);
+    INTEL_CPU_REGEX.is_match(label)
+        || INTEL_CORE_REGEX.is_match(label)
+        || AMD_CPU_REGEX.is_match(label)
+        || AMD_CCD_REGEX.is_match(label)
+}
+
+// Takes (label, temperature) pairs so it doesn't depend on a live `Components` instance
+fn get_core_temps<'a>(readings: impl IntoIterator<Item = (&'a str, Option<f32>)>) -> Vec<f32> {
+    readings
+        .into_iter()
+        .filter(|(label, _)| is_core_temp_label(label))
+        .filter_map(|(_, temp)| temp)
+        .collect()
+}
+
+// Label (without the driver name) and temperature of every per-core sensor, ordered by
+// core number. Sensors of the same kind ("Core", "Tccd") stay together, in the order the
+// driver lists the kinds.
+fn get_labeled_core_temps<'a>(
+    readings: impl IntoIterator<Item = (&'a str, Option<f32>)>,
+) -> Vec<(String, f32)> {
+    let mut cores: Vec<(String, f32)> = readings
+        .into_iter()
+        .filter(|(label, _)| is_core_temp_label(label))
+        .filter_map(|(label, temp)| Some((sensor_label(label).to_string(), temp?)))
+        .collect();
+
+    let kinds: Vec<String> = cores.iter().fold(Vec::new(), |mut kinds, (label, _)| {
+        let kind = core_kind(label).to_string();
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+        kinds
+    });
+    cores.sort_by_key(|(label, _)| {
+        let kind = core_kind(label);
+        let number = label[kind.len()..]
+            .trim()
+            .parse::<u32>()
+            .unwrap_or(u32::MAX);
+        (kinds.iter().position(|k| k == kind), number)
+    });
+    cores
+}
+
+// The hottest of `get_labeled_core_temps`, the first one listed on a tie. A core that stays
+// the hottest hints at bad thermal paste or the scheduler favoring it.
+pub(crate) fn hottest_core(cores: &[(String, f32)]) -> Option<&(String, f32)> {
+    cores.iter().fold(None, |hottest, core| match hottest {
+        Some(hottest) if hottest.1 >= core.1 => Some(hottest),
+        _ => Some(core),
+    })
+}
+
+// "Core 12" -> "Core ", the label without its trailing number
+fn core_kind(label: &str) -> &str {
+    label.trim_end_matches(|c: char| c.is_ascii_digit())
+}
+
+fn is_ccd_label(label: &str) -> bool {
+    AMD_CCD_REGEX.is_match(sensor_label(label))
+}
+
+// Label (without the driver name) and temperature of every CCD, ordered by CCD number
+fn get_ccd_temps<'a>(
+    readings: impl IntoIterator<Item = (&'a str, Option<f32>)>,
+) -> Vec<(String, f32)> {
+    let mut ccds: Vec<(String, f32)> = readings
+        .into_iter()
+        .filter(|(label, _)| is_ccd_label(label))
+        .filter_map(|(label, temp)| Some((sensor_label(label).to_string(), temp?)))
+        .collect();
+
+    // Sorting the labels would put "Tccd10" before "Tccd2"
+    ccds.sort_by_key(|(label, _)| {
+        label
+            .trim_start_matches("Tccd")
+            .parse::<u32>()
+            .unwrap_or(u32::MAX)
+    });
+    ccds
+}
+
+// Lower number means higher priority, `None` if the label doesn't look like a GPU sensor
+fn get_gpu_label_priority(label: &str) -> Option<usize> {
+    if let Some(index) = GPU_TEMP_LABELS.iter().position(|item| *item == label) {
+        return Some(index);
     }
 
-    if let Some(target_exists) = target {
-        return target_exists.temperature();
+    let lowercase = label.to_lowercase();
+    let driver = GPU_DRIVER_NAMES
+        .iter()
+        .find(|driver| lowercase.starts_with(*driver))?;
+
+    // "amdgpu edge" should rank like "edge"
+    let rest = label[driver.len()..].trim();
+    GPU_TEMP_LABELS
+        .iter()
+        .position(|item| *item == rest)
+        .or(Some(GPU_TEMP_LABELS.len()))
+}
+
+pub(crate) fn is_drive_temp_label(label: &str) -> bool {
+    let label = label.to_lowercase();
+    DRIVE_LABEL_PREFIXES
+        .iter()
+        .any(|prefix| label.starts_with(prefix))
+}
+
+// Label and temperature of every drive sensor, sorted by label
+fn get_drive_temps<'a>(
+    readings: impl IntoIterator<Item = (&'a str, Option<f32>)>,
+) -> Vec<(String, f32)> {
+    let mut drives: Vec<(String, f32)> = readings
+        .into_iter()
+        .filter(|(label, _)| is_drive_temp_label(label))
+        .filter_map(|(label, temp)| Some((label.to_string(), temp?)))
+        .collect();
+
+    drives.sort_by_cached_key(|(label, _)| label.to_lowercase());
+    drives
+}
+
+// Exact label, or a simple glob where `*` matches any run of characters and `?` a single one
+fn matches_pattern(pattern: &str, label: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let label: Vec<char> = label.chars().collect();
+    let (mut p, mut l) = (0, 0);
+    // Pattern position after the last `*` and the label position it was matched up to
+    let mut star: Option<(usize, usize)> = None;
+
+    while l < label.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, l));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == label[l] => {
+                p += 1;
+                l += 1;
+            }
+            // Let the last `*` swallow one more character and try again
+            _ => match star {
+                Some((after_star, matched)) => {
+                    star = Some((after_star, matched + 1));
+                    p = after_star;
+                    l = matched + 1;
+                }
+                None => return false,
+            },
+        }
     }
 
-    return None;
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn is_junk_label(label: &str) -> bool {
+    let label = label.to_lowercase();
+    JUNK_LABEL_PREFIXES
+        .iter()
+        .any(|prefix| label.starts_with(prefix))
+}
+
+// Label and temperature of the hottest sensor. Bogus values are already dropped by
+// `ReadOptions::plausible`, like for every other use of the readings.
+fn get_hottest_temp<'a>(
+    readings: impl IntoIterator<Item = (&'a str, Option<f32>)>,
+) -> Option<(String, f32)> {
+    readings
+        .into_iter()
+        .filter(|(label, _)| !is_junk_label(label))
+        .filter_map(|(label, temp)| Some((label, temp?)))
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(label, temp)| (label.to_string(), temp))
+}
+
+fn find_gpu_temp<'a>(
+    readings: impl IntoIterator<Item = (&'a str, Option<f32>)>,
+) -> Option<(String, f32)> {
+    readings
+        .into_iter()
+        .filter_map(|(label, temp)| Some((get_gpu_label_priority(label)?, label, temp?)))
+        .min_by_key(|(priority, _, _)| *priority)
+        .map(|(_, label, temp)| (label.to_string(), temp))
+}
+
+fn get_gpu_temp_from_components(
+    components: &Components,
+    options: &ReadOptions,
+) -> Option<(String, f32)> {
+    find_gpu_temp(get_readings(components, options))
 }
 
-fn get_cpu_core_temps(components: &Components) -> Vec<f32> {
-    let mut temps: Vec<f32> = vec![];
-    for comp in components.iter() {
-        let mut temp: Option<f32> = None;
-        if INTEL_CPU_REGEX.is_match(comp.label()) {
-            temp = comp.temperature();
-        } else if AMD_CPU_REGEX.is_match(comp.label()) {
-            temp = comp.temperature();
+fn aggregate_core_temps(temps: &[f32], aggregation: CoreAggregation) -> Option<f32> {
+    if temps.is_empty() {
+        return None;
+    }
+
+    match aggregation {
+        CoreAggregation::Max => temps.iter().copied().reduce(f32::max),
+        CoreAggregation::Average => Some(temps.iter().sum::<f32>() / temps.len() as f32),
+        CoreAggregation::Median => {
+            let mut sorted = temps.to_vec();
+            sorted.sort_by(f32::total_cmp);
+
+            let middle = sorted.len() / 2;
+            if sorted.len() % 2 == 0 {
+                Some((sorted[middle - 1] + sorted[middle]) / 2.0)
+            } else {
+                Some(sorted[middle])
+            }
         }
+    }
+}
+
+// Priority of the label kind `get_overall_temps` picks
+fn get_best_overall_priority<'a>(
+    readings: impl IntoIterator<Item = (&'a str, Option<f32>)>,
+    order: &[OverallLabel],
+) -> Option<usize> {
+    readings
+        .into_iter()
+        .filter(|(_, temp)| temp.is_some())
+        .filter_map(|(label, _)| get_overall_label_priority(label, order))
+        .min()
+}
+
+// Borrows owned (label, temperature) pairs for the helpers above
+fn as_pairs(readings: &[(String, Option<f32>)]) -> impl Iterator<Item = (&str, Option<f32>)> {
+    readings.iter().map(|(label, temp)| (label.as_str(), *temp))
+}
+
+// Which sensors the CPU temperature was taken from, their limits are the ones to show
+#[derive(Clone, Debug, PartialEq)]
+enum CpuSource {
+    Selected(String),
+    Ccds,
+    // Overall sensors of the given priority, see `get_overall_label_priority`
+    Overall(usize),
+    Cores,
+}
 
-        if let Some(valid_temp) = temp {
-            temps.push(valid_temp);
+impl CpuSource {
+    // Whether the sensor with this label is one of the sources
+    fn matches(&self, label: &str, options: &ReadOptions) -> bool {
+        match self {
+            CpuSource::Selected(selected) => label == selected,
+            CpuSource::Ccds => is_ccd_label(label),
+            CpuSource::Overall(priority) => {
+                get_overall_label_priority(label, options.overall_order()) == Some(*priority)
+            }
+            CpuSource::Cores => is_core_temp_label(label),
         }
     }
+}
 
-    return temps;
+// Unplugged, or its driver was unloaded. `get_cpu_temp` falls back to the automatic
+// detection then.
+fn is_selected_missing(readings: &[(String, Option<f32>)], options: &ReadOptions) -> bool {
+    options
+        .selected_sensor
+        .as_deref()
+        .is_some_and(|label| !readings.iter().any(|(other, _)| other == label))
 }
 
-pub(crate) fn get_temp() -> Option<f32> {
-    let components = Components::new_with_refreshed_list();
-    let overall_temp = get_overall_cpu_temp(&components);
-    if overall_temp.is_some() {
-        return overall_temp;
+// Picks the CPU temperature the same way for every provider. A selected sensor that exists
+// but has no value gives `None`, the automatic detection is only used if it's missing.
+fn get_cpu_temp(
+    readings: &[(String, Option<f32>)],
+    options: &ReadOptions,
+) -> Option<(f32, CpuSource)> {
+    if let Some(label) = options.selected_sensor.as_deref() {
+        if let Some((_, temp)) = readings.iter().find(|(other, _)| other == label) {
+            return temp.map(|celsius| (celsius, CpuSource::Selected(label.to_string())));
+        }
     }
 
-    let cpu_temps = get_cpu_core_temps(&components);
-    if cpu_temps.len() == 0 {
-        return None;
+    // CCDs can differ a lot from each other and from Tctl under uneven load
+    if options.hottest_ccd {
+        let hottest_ccd = get_ccd_temps(as_pairs(readings))
+            .into_iter()
+            .map(|(_, temp)| temp)
+            .reduce(f32::max);
+        if let Some(celsius) = hottest_ccd {
+            return Some((celsius, CpuSource::Ccds));
+        }
+    }
+
+    // Hottest socket
+    let order = options.overall_order();
+    let overall_temp = get_overall_temps(as_pairs(readings), order)
+        .into_iter()
+        .reduce(f32::max);
+    if let Some(celsius) = overall_temp {
+        let priority = get_best_overall_priority(as_pairs(readings), order)?;
+        return Some((celsius, CpuSource::Overall(priority)));
     }
 
-    return cpu_temps
+    let cpu_temps = get_core_temps(as_pairs(readings));
+    aggregate_core_temps(&cpu_temps, options.core_aggregation)
+        .map(|celsius| (celsius, CpuSource::Cores))
+}
+
+fn get_cpu_priority(readings: &[(String, Option<f32>)], options: &ReadOptions) -> Option<usize> {
+    match get_cpu_temp(readings, options)?.1 {
+        CpuSource::Overall(priority) => Some(priority),
+        _ => None,
+    }
+}
+
+// Where the CPU temperature was taken from, `None` if no component gave one
+fn get_cpu_sensor(readings: &[(String, Option<f32>)], options: &ReadOptions) -> Option<CpuSensor> {
+    let (celsius, source) = get_cpu_temp(readings, options)?;
+    let label = match source {
+        CpuSource::Selected(label) => label,
+        CpuSource::Cores => {
+            return Some(CpuSensor::Cores {
+                aggregation: options.core_aggregation,
+                count: get_core_temps(as_pairs(readings)).len(),
+            })
+        }
+        // The hottest CCD or socket is the one that reported the value
+        CpuSource::Ccds | CpuSource::Overall(_) => readings
+            .iter()
+            .find(|(label, temp)| *temp == Some(celsius) && source.matches(label, options))
+            .map(|(label, _)| label.clone())?,
+    };
+
+    Some(CpuSensor::Component { label, chip: None })
+}
+
+// The readings of `get_readings` that can be the CPU's, see `counts_for_cpu`
+fn get_cpu_readings(components: &Components, options: &ReadOptions) -> Vec<(String, Option<f32>)> {
+    get_readings(components, options)
+        .filter(|(label, _)| counts_for_cpu(label, sysinfo_chip(label), options))
+        .map(|(label, temp)| (label.to_string(), temp))
+        .collect()
+}
+
+fn get_temp_from_components(components: &Components, options: &ReadOptions) -> Option<SensorTemp> {
+    let readings = get_cpu_readings(components, options);
+    let (celsius, source) = get_cpu_temp(&readings, options)?;
+
+    Some(SensorTemp::with_limits_of(celsius, components, |label| {
+        source.matches(label, options)
+            && !options.is_ignored(label)
+            && counts_for_cpu(label, sysinfo_chip(label), options)
+    }))
+}
+
+// Labels of the CPU sensors that would have been used but didn't report a value
+fn get_unread_cpu_labels<'a>(
+    readings: impl IntoIterator<Item = (&'a str, Option<f32>)>,
+    selected_sensor: Option<&str>,
+) -> Vec<&'a str> {
+    let mut labels: Vec<&str> = readings
+        .into_iter()
+        .filter(|(_, temp)| temp.is_none())
+        .map(|(label, _)| label)
+        .filter(|label| {
+            Some(*label) == selected_sensor
+                || get_overall_label_priority(label, DEFAULT_OVERALL_ORDER).is_some()
+                || is_core_temp_label(label)
+        })
+        .collect();
+
+    // Chips that use the same label aren't necessarily next to each other, the first one
+    // keeps its place
+    let mut seen = HashSet::new();
+    labels.retain(|label| seen.insert(*label));
+    labels
+}
+
+// The parts of the config the sensor layer needs
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ReadOptions {
+    // Component label, `None` means automatic detection
+    pub(crate) selected_sensor: Option<String>,
+    pub(crate) core_aggregation: CoreAggregation,
+    // Use the hottest AMD CCD instead of the overall temperature when there are any
+    pub(crate) hottest_ccd: bool,
+    // Prefer AMD Tctl over Tdie
+    pub(crate) prefer_tctl: bool,
+    // Drive sensors are only refreshed and returned when this is set, reading them can
+    // be slow and wake up sleeping disks
+    pub(crate) drive_temps: bool,
+    // Look for the hottest of all sensors, this refreshes the drive sensors as well
+    pub(crate) hottest_any: bool,
+    // Fill `SensorReading::all`, this refreshes the drive sensors as well
+    pub(crate) all_components: bool,
+    // Fill `SensorReading::components`, drive sensors keep their last value unless one of
+    // the above refreshes them
+    pub(crate) components: bool,
+    // Fill `SensorReading::fans`
+    pub(crate) fans: bool,
+    // Fill `SensorReading::power`
+    pub(crate) power: bool,
+    // Fill `SensorReading::throttle`
+    pub(crate) throttle: bool,
+    // Labels or globs of sensors to leave out of everything, see `matches_pattern`
+    pub(crate) blacklist: Vec<String>,
+    // Values above are dropped, `None` uses `sanity::DEFAULT_MAX_PLAUSIBLE_CELSIUS`
+    pub(crate) max_plausible_celsius: Option<f32>,
+    // Calibration of the CPU value in Celsius, see `apply_offset`
+    pub(crate) offset_celsius: f32,
+    pub(crate) sensor_offsets: BTreeMap<String, f32>,
+}
+
+impl ReadOptions {
+    fn is_ignored(&self, label: &str) -> bool {
+        self.blacklist
+            .iter()
+            .any(|pattern| matches_pattern(pattern, label))
+    }
+
+    pub(crate) fn max_plausible_celsius(&self) -> f32 {
+        self.max_plausible_celsius
+            .unwrap_or(sanity::DEFAULT_MAX_PLAUSIBLE_CELSIUS)
+    }
+
+    fn plausible(&self, celsius: Option<f32>, label: &str) -> Option<f32> {
+        sanity::plausible(celsius, self.max_plausible_celsius(), label)
+    }
+
+    // The sensor's own offset, or the global one
+    pub(crate) fn offset_celsius(&self, label: Option<&str>) -> f32 {
+        label
+            .and_then(|label| self.sensor_offsets.get(label))
+            .copied()
+            .unwrap_or(self.offset_celsius)
+    }
+
+    fn overall_order(&self) -> &'static [OverallLabel] {
+        if self.prefer_tctl {
+            TCTL_FIRST_OVERALL_ORDER
+        } else {
+            DEFAULT_OVERALL_ORDER
+        }
+    }
+}
+
+// Temperature with the limits reported by the driver, in Celsius
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct SensorTemp {
+    pub(crate) celsius: f32,
+    // Either the driver's high limit or the highest value seen so far, sysinfo raises it
+    // whenever the temperature goes above it
+    pub(crate) max: Option<f32>,
+    pub(crate) critical: Option<f32>,
+}
+
+impl SensorTemp {
+    // For values that don't come with limits (thermal zones)
+    fn new(celsius: f32) -> Self {
+        Self {
+            celsius,
+            max: None,
+            critical: None,
+        }
+    }
+
+    // Uses the lowest limits of the matching components. All cores and sockets of a CPU
+    // share their limits, so this only matters if a driver reports odd values.
+    fn with_limits_of(
+        celsius: f32,
+        components: &Components,
+        matches: impl Fn(&str) -> bool,
+    ) -> Self {
+        let limit = |get: fn(&sysinfo::Component) -> Option<f32>| {
+            components
+                .iter()
+                .filter(|comp| matches(comp.label()))
+                .filter_map(get)
+                .reduce(f32::min)
+        };
+
+        Self {
+            celsius,
+            max: limit(sysinfo::Component::max),
+            critical: limit(sysinfo::Component::critical),
+        }
+    }
+}
+
+// Where the CPU temperature came from, shown in the popup
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum CpuSensor {
+    // A single component: the selected one, the hottest socket or the hottest CCD.
+    // `chip` is the hwmon driver name, when the provider knows it.
+    Component {
+        label: String,
+        chip: Option<String>,
+    },
+    // Per-core sensors combined with `aggregation`
+    Cores {
+        aggregation: CoreAggregation,
+        count: usize,
+    },
+    // Kernel thermal zones, no component was usable
+    ThermalZone,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) enum TempReading {
+    Value(SensorTemp),
+    // Nothing that looks like a CPU sensor exists
+    #[default]
+    NoSensors,
+    // CPU sensors exist but none of them could be read (e.g. an EC timeout).
+    // Holds the labels of the sensors or the thermal zone directory.
+    ReadError(String),
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SensorReading {
+    pub(crate) cpu: TempReading,
+    // Where the CPU value came from, `None` unless there is one
+    pub(crate) cpu_sensor: Option<CpuSensor>,
+    // Label and temperature of the matched GPU component
+    pub(crate) gpu: Option<(String, f32)>,
+    // Label and temperature of every AMD CCD, empty on other CPUs
+    pub(crate) ccds: Vec<(String, f32)>,
+    // Label and temperature of every per-core sensor, CCDs included
+    pub(crate) cores: Vec<(String, f32)>,
+    // Empty unless `ReadOptions::drive_temps` was set
+    pub(crate) drives: Vec<(String, f32)>,
+    // Label and temperature of the hottest sensor, `None` unless `ReadOptions::hottest_any`
+    // was set
+    pub(crate) hottest: Option<(String, f32)>,
+    // Labels of all components that report a temperature, sorted and deduplicated
+    pub(crate) labels: Vec<String>,
+    // Label and temperature of every component that reports one, in the driver's order
+    pub(crate) temps: Vec<(String, f32)>,
+    // CPU utilization in percent, filled in by `read_sensors`
+    pub(crate) load: Option<f32>,
+    // (chip, label, temperature) of every sensor, empty unless
+    // `ReadOptions::all_components` was set
+    pub(crate) all: Vec<(String, String, Option<f32>)>,
+    // Number of sensors the provider found, ignored ones included
+    pub(crate) component_count: usize,
+    // Position of the CPU sensor's label in the overall order, `None` unless one of those
+    // gave the value
+    pub(crate) cpu_priority: Option<usize>,
+    // How long the provider took, filled in by `read_sensors`
+    pub(crate) read_duration: Duration,
+    // (label, driver name) of the sensors whose driver is known
+    pub(crate) chips: Vec<(String, String)>,
+    // Name of another sensor on the CPU's package, see `get_apu_alternative`
+    pub(crate) cpu_alternative: Option<String>,
+    // A sensor is selected but doesn't exist (anymore), the value is from the automatic
+    // detection then
+    pub(crate) selected_missing: bool,
+    // Every sensor with its limits, empty unless `ReadOptions::components` was set
+    pub(crate) components: Vec<RawSensor>,
+    // Every fan, empty unless `ReadOptions::fans` was set. Filled in by `read_sensors`.
+    pub(crate) fans: Vec<Fan>,
+    // Package power in watts, `None` on the first sample and unless `ReadOptions::power` was
+    // set. Filled in by `read_sensors`.
+    pub(crate) power: Option<f32>,
+    // Why there is no power reading, see `RaplMeter::sample`
+    pub(crate) power_error: Option<String>,
+    // `None` unless `ReadOptions::throttle` was set. Filled in by `read_sensors`.
+    pub(crate) throttle: Option<Throttle>,
+    // The CPU value before the calibration offset, `None` unless there is one
+    pub(crate) cpu_raw: Option<f32>,
+}
+
+// One temperature input as read by a provider that collects everything up front
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct RawSensor {
+    pub(crate) label: String,
+    // Driver name, e.g. "coretemp"
+    pub(crate) chip: Option<String>,
+    pub(crate) temp: Option<f32>,
+    pub(crate) max: Option<f32>,
+    pub(crate) critical: Option<f32>,
+}
+
+// Applies the same detection as `SysinfoProvider` to already read values
+pub(crate) fn build_reading(sensors: &[RawSensor], options: &ReadOptions) -> SensorReading {
+    let all = if options.all_components {
+        group_by_chip(sensors.iter().map(|sensor| {
+            let chip = sensor
+                .chip
+                .clone()
+                .or_else(|| sysinfo_chip(&sensor.label).map(str::to_string))
+                .unwrap_or_else(|| sensor.label.clone());
+            (chip, sensor.label.clone(), sensor.temp)
+        }))
+    } else {
+        vec![]
+    };
+    let component_count = sensors.len();
+    let sensors: Vec<RawSensor> = sensors
+        .iter()
+        .filter(|sensor| !options.is_ignored(&sensor.label))
+        .map(|sensor| RawSensor {
+            temp: options.plausible(sensor.temp, &sensor.label),
+            ..sensor.clone()
+        })
+        .collect();
+    let readings: Vec<(String, Option<f32>)> = sensors
+        .iter()
+        .map(|sensor| (sensor.label.clone(), sensor.temp))
+        .collect();
+    let counts_for_cpu = |sensor: &RawSensor| {
+        let chip = sensor
+            .chip
+            .as_deref()
+            .or_else(|| sysinfo_chip(&sensor.label));
+        counts_for_cpu(&sensor.label, chip, options)
+    };
+    let cpu_readings: Vec<(String, Option<f32>)> = sensors
         .iter()
-        .max_by(|a, b| {
-            if a > b {
-                Ordering::Greater
+        .filter(|sensor| counts_for_cpu(sensor))
+        .map(|sensor| (sensor.label.clone(), sensor.temp))
+        .collect();
+    let chips: Vec<(String, String)> = sensors
+        .iter()
+        .filter_map(|sensor| Some((sensor.label.clone(), sensor.chip.clone()?)))
+        .collect();
+
+    let cpu = match get_cpu_temp(&cpu_readings, options) {
+        Some((celsius, source)) => {
+            // Lowest limits of the sources, like `SensorTemp::with_limits_of`
+            let limit = |get: fn(&RawSensor) -> Option<f32>| {
+                sensors
+                    .iter()
+                    .filter(|sensor| counts_for_cpu(sensor))
+                    .filter(|sensor| source.matches(&sensor.label, options))
+                    .filter_map(get)
+                    .reduce(f32::min)
+            };
+            TempReading::Value(SensorTemp {
+                celsius,
+                max: limit(|sensor| sensor.max),
+                critical: limit(|sensor| sensor.critical),
+            })
+        }
+        None => {
+            let unread =
+                get_unread_cpu_labels(as_pairs(&cpu_readings), options.selected_sensor.as_deref());
+            if unread.is_empty() {
+                TempReading::NoSensors
             } else {
-                Ordering::Less
+                TempReading::ReadError(unread.join(", "))
+            }
+        }
+    };
+
+    let mut labels: Vec<String> = readings
+        .iter()
+        .filter(|(_, temp)| temp.is_some())
+        .map(|(label, _)| label.clone())
+        .collect();
+    labels.sort();
+    labels.dedup();
+
+    let cpu_sensor = match cpu {
+        TempReading::Value(_) => {
+            get_cpu_sensor(&cpu_readings, options).map(|sensor| match sensor {
+                CpuSensor::Component { label, .. } => {
+                    let chip = sensors
+                        .iter()
+                        .find(|sensor| sensor.label == label)
+                        .and_then(|sensor| sensor.chip.clone());
+                    CpuSensor::Component { label, chip }
+                }
+                sensor => sensor,
+            })
+        }
+        _ => None,
+    };
+
+    SensorReading {
+        cpu_alternative: get_apu_alternative(cpu_sensor.as_ref(), &chips),
+        cpu_sensor,
+        cpu,
+        gpu: find_gpu_temp(as_pairs(&readings)),
+        ccds: get_ccd_temps(as_pairs(&readings)),
+        cores: get_labeled_core_temps(as_pairs(&readings)),
+        drives: if options.drive_temps {
+            get_drive_temps(as_pairs(&readings))
+        } else {
+            vec![]
+        },
+        hottest: if options.hottest_any {
+            get_hottest_temp(as_pairs(&readings))
+        } else {
+            None
+        },
+        labels,
+        temps: readings
+            .iter()
+            .filter_map(|(label, temp)| Some((label.clone(), (*temp)?)))
+            .collect(),
+        load: None,
+        all,
+        component_count,
+        cpu_priority: get_cpu_priority(&cpu_readings, options),
+        read_duration: Duration::ZERO,
+        chips,
+        selected_missing: is_selected_missing(&readings, options),
+        components: if options.components { sensors } else { vec![] },
+        fans: vec![],
+        power: None,
+        power_error: None,
+        throttle: None,
+        cpu_raw: None,
+    }
+}
+
+// Source of the sensor readings. `SysinfoProvider` reads the hardware, the tests use a mock.
+pub(crate) trait TempProvider: Send {
+    fn read(&mut self, options: &ReadOptions) -> SensorReading;
+
+    // Lists the sensors again on the next read, hwmon numbering can change across a suspend
+    fn rescan_on_next_read(&mut self) {}
+}
+
+// Every provider's CPU value goes through the jump check of `SaneProvider`
+pub(crate) fn new_provider(backend: SensorBackend) -> Box<dyn TempProvider> {
+    let provider: Box<dyn TempProvider> = match backend {
+        SensorBackend::Sysinfo => Box::new(SysinfoProvider::default()),
+        SensorBackend::Hwmon => Box::new(HwmonProvider::default()),
+    };
+    Box::new(SaneProvider::new(provider))
+}
+
+// Lets `Window` keep deriving `Default`
+impl Default for Box<dyn TempProvider> {
+    fn default() -> Self {
+        Box::new(SysinfoProvider::default())
+    }
+}
+
+// Enumerating components re-scans every hwmon device, so the list is kept around and
+// only the values are refreshed on every read
+#[derive(Default)]
+pub(crate) struct SysinfoProvider {
+    components: Components,
+    // `None` until the first scan happened
+    last_rescan: Option<Instant>,
+    // Fallback for when no component is usable, `None` until the zones were looked up
+    thermal_zones: Option<Vec<PathBuf>>,
+}
+
+impl SysinfoProvider {
+    fn rescan(&mut self) {
+        self.components.refresh(true);
+        self.last_rescan = Some(Instant::now());
+    }
+
+    fn refresh(&mut self, include_drives: bool) {
+        let rescan_due = self
+            .last_rescan
+            .map_or(true, |last_rescan| last_rescan.elapsed() >= RESCAN_INTERVAL);
+
+        if rescan_due {
+            self.rescan();
+            self.thermal_zones = None;
+        } else {
+            for comp in self.components.iter_mut() {
+                if include_drives || !is_drive_temp_label(comp.label()) {
+                    comp.refresh();
+                }
             }
+        }
+    }
+
+    fn has_sensor(&self, label: &str) -> bool {
+        self.components.iter().any(|comp| comp.label() == label)
+    }
+
+    fn labels(&self, options: &ReadOptions) -> Vec<String> {
+        let mut labels: Vec<String> = get_readings(&self.components, options)
+            .filter(|(_, temp)| temp.is_some())
+            .map(|(label, _)| label.to_string())
+            .collect();
+
+        labels.sort();
+        labels.dedup();
+        labels
+    }
+
+    // When a sensor is selected only that component is read, the automatic detection
+    // is only used if the selected label doesn't exist (anymore)
+    fn get_temp_value(&mut self, options: &ReadOptions) -> Option<SensorTemp> {
+        self.refresh(options.drive_temps || options.hottest_any || options.all_components);
+        let missing_sensor = options
+            .selected_sensor
+            .as_deref()
+            .is_some_and(|label| !self.has_sensor(label));
+        let temp = get_temp_from_components(&self.components, options);
+
+        // A component we relied on vanished or stopped reporting (e.g. module unload),
+        // make sure we aren't looking at a stale list
+        let just_rescanned = self
+            .last_rescan
+            .is_some_and(|last_rescan| last_rescan.elapsed() < MIN_RESCAN_INTERVAL);
+        if (temp.is_some() && !missing_sensor) || just_rescanned {
+            return temp.or_else(|| self.get_thermal_zone_temp());
+        }
+
+        self.rescan();
+        get_temp_from_components(&self.components, options).or_else(|| self.get_thermal_zone_temp())
+    }
+
+    // Tells apart missing sensors from sensors that exist but failed to report a value
+    fn get_temp(&mut self, options: &ReadOptions) -> TempReading {
+        if let Some(temp) = self.get_temp_value(options) {
+            return TempReading::Value(temp);
+        }
+
+        let readings = get_cpu_readings(&self.components, options);
+        let unread = get_unread_cpu_labels(as_pairs(&readings), options.selected_sensor.as_deref());
+        if !unread.is_empty() {
+            return TempReading::ReadError(unread.join(", "));
+        }
+
+        // Only looked up once no component could be used
+        if self
+            .thermal_zones
+            .as_ref()
+            .is_some_and(|zones| !zones.is_empty())
+        {
+            return TempReading::ReadError(thermal_zone::THERMAL_ROOT.to_string());
+        }
+
+        TempReading::NoSensors
+    }
+
+    fn get_thermal_zone_temp(&mut self) -> Option<SensorTemp> {
+        let zones = self.thermal_zones.get_or_insert_with(|| {
+            thermal_zone::find_cpu_zones(Path::new(thermal_zone::THERMAL_ROOT))
+        });
+        thermal_zone::read_zones_temp(zones).map(SensorTemp::new)
+    }
+
+    // Uses the values refreshed by `get_temp_value`
+    fn get_gpu_temp(&self, options: &ReadOptions) -> Option<(String, f32)> {
+        get_gpu_temp_from_components(&self.components, options)
+    }
+}
+
+impl TempProvider for SysinfoProvider {
+    fn rescan_on_next_read(&mut self) {
+        self.last_rescan = None;
+    }
+
+    fn read(&mut self, options: &ReadOptions) -> SensorReading {
+        let cpu = self.get_temp(options);
+        let readings: Vec<(String, Option<f32>)> = get_readings(&self.components, options)
+            .map(|(label, temp)| (label.to_string(), temp))
+            .collect();
+        let cpu_readings = get_cpu_readings(&self.components, options);
+        let chips: Vec<(String, String)> = readings
+            .iter()
+            .filter_map(|(label, _)| Some((label.clone(), sysinfo_chip(label)?.to_string())))
+            .collect();
+        let cpu_sensor = match cpu {
+            // Components are always looked at first
+            TempReading::Value(_) => Some(
+                match get_cpu_sensor(&cpu_readings, options).unwrap_or(CpuSensor::ThermalZone) {
+                    CpuSensor::Component { label, .. } => CpuSensor::Component {
+                        chip: sysinfo_chip(&label).map(str::to_string),
+                        label,
+                    },
+                    sensor => sensor,
+                },
+            ),
+            _ => None,
+        };
+        SensorReading {
+            cpu_alternative: get_apu_alternative(cpu_sensor.as_ref(), &chips),
+            cpu_sensor,
+            cpu,
+            gpu: self.get_gpu_temp(options),
+            ccds: get_ccd_temps(as_pairs(&readings)),
+            cores: get_labeled_core_temps(as_pairs(&readings)),
+            drives: if options.drive_temps {
+                get_drive_temps(as_pairs(&readings))
+            } else {
+                vec![]
+            },
+            // Recomputed on every read, so it follows whichever sensor is currently hottest
+            hottest: if options.hottest_any {
+                get_hottest_temp(as_pairs(&readings))
+            } else {
+                None
+            },
+            labels: self.labels(options),
+            temps: readings
+                .iter()
+                .filter_map(|(label, temp)| Some((label.clone(), (*temp)?)))
+                .collect(),
+            load: None,
+            all: if options.all_components {
+                list_all_components(&self.components)
+            } else {
+                vec![]
+            },
+            component_count: self.components.list().len(),
+            cpu_priority: get_cpu_priority(&cpu_readings, options),
+            read_duration: Duration::ZERO,
+            chips,
+            selected_missing: is_selected_missing(&readings, options),
+            components: if options.components {
+                self.components
+                    .iter()
+                    .filter(|comp| !options.is_ignored(comp.label()))
+                    .map(|comp| RawSensor {
+                        label: comp.label().to_string(),
+                        chip: sysinfo_chip(comp.label()).map(str::to_string),
+                        temp: options.plausible(comp.temperature(), comp.label()),
+                        max: comp.max(),
+                        critical: comp.critical(),
+                    })
+                    .collect()
+            } else {
+                vec![]
+            },
+            fans: vec![],
+            power: None,
+            power_error: None,
+            throttle: None,
+            cpu_raw: None,
+        }
+    }
+}
+
+// Calibrates the CPU value before the smoothing and the thresholds see it. The driver's
+// limits are on the same scale as the value, so they're moved along.
+fn apply_offset(reading: &mut SensorReading, options: &ReadOptions) {
+    let TempReading::Value(temp) = &mut reading.cpu else {
+        return;
+    };
+    let label = match &reading.cpu_sensor {
+        Some(CpuSensor::Component { label, .. }) => Some(label.as_str()),
+        _ => None,
+    };
+    let offset = options.offset_celsius(label);
+    reading.cpu_raw = Some(temp.celsius);
+    temp.celsius += offset;
+    temp.max = temp.max.map(|max| max + offset);
+    temp.critical = temp.critical.map(|critical| critical + offset);
+}
+
+// Some EC based hwmon drivers take 100+ ms per read, so this runs on the blocking thread
+// pool instead of the UI thread. The load, the fans, the power and the throttling are
+// sampled on the same tick, whatever the backend.
+pub(crate) async fn read_sensors(
+    sensors: Arc<Mutex<Box<dyn TempProvider>>>,
+    load: Arc<Mutex<CpuLoad>>,
+    power: Arc<Mutex<RaplMeter>>,
+    throttle: Arc<Mutex<ThrottleMonitor>>,
+    options: ReadOptions,
+) -> SensorReading {
+    let result = tokio::task::spawn_blocking(move || {
+        let started = Instant::now();
+        let mut reading = sensors
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .read(&options);
+        reading.read_duration = started.elapsed();
+        apply_offset(&mut reading, &options);
+        let mut load = load.lock().unwrap_or_else(PoisonError::into_inner);
+        reading.load = load.sample();
+        if options.throttle {
+            // The first sample has no load per core either
+            let cores = if reading.load.is_some() {
+                load.cores()
+            } else {
+                Vec::new()
+            };
+            reading.throttle = Some(
+                throttle
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .sample(&cores),
+            );
+        }
+        drop(load);
+        if options.fans {
+            reading.fans = fans::read_fans(Path::new(hwmon::HWMON_ROOT));
+        }
+        if options.power {
+            match power
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .sample()
+            {
+                Ok(watts) => reading.power = watts,
+                Err(err) => reading.power_error = Some(err),
+            }
+        }
+        reading
+    })
+    .await;
+
+    result.unwrap_or_else(|err| {
+        tracing::error!(?err, "Reading sensors failed");
+        SensorReading::default()
+    })
+}
+
+// Why the sensors can't be read, sysinfo and the hwmon provider treat it like missing sensors
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct AccessProblem {
+    pub(crate) path: PathBuf,
+    // With the errno, "Permission denied (os error 13)"
+    pub(crate) error: String,
+}
+
+fn is_denied(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::PermissionDenied
+}
+
+// A chip is unreadable when it has temperature inputs and every read of them was refused.
+// Other errors are left to the readings, an EC timing out now and then isn't a setup problem.
+fn chip_denied(reads: &[io::Result<String>]) -> Option<&io::Error> {
+    let mut errors = reads
+        .iter()
+        .map(|read| read.as_ref().err().filter(|err| is_denied(err)));
+    let first = errors.next()??;
+    errors.all(|err| err.is_some()).then_some(first)
+}
+
+fn temp_inputs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    Ok(fs::read_dir(dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("temp") && name.ends_with("_input"))
+        })
+        .collect())
+}
+
+// Reads the hwmon directory and the temperature inputs of every chip directly, since the
+// sensor libraries drop the errors. Returns the first place access was refused, `None` if
+// everything could be read or there's no hwmon at all.
+pub(crate) fn probe_access(root: &Path) -> Option<AccessProblem> {
+    let problem = |path: &Path, err: &io::Error| AccessProblem {
+        path: path.to_path_buf(),
+        error: err.to_string(),
+    };
+
+    if let Err(err) = fs::read_dir(root) {
+        return is_denied(&err).then(|| problem(root, &err));
+    }
+
+    hwmon::list_chips(root).into_iter().find_map(|chip| {
+        let reads: Vec<io::Result<String>> = match temp_inputs(&chip) {
+            Ok(inputs) => inputs.iter().map(fs::read_to_string).collect(),
+            Err(err) => vec![Err(err)],
+        };
+        chip_denied(&reads).map(|err| problem(&chip, err))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reports fixed (label, temperature) pairs, without limits
+    struct MockProvider {
+        sensors: Vec<RawSensor>,
+    }
+
+    impl MockProvider {
+        fn new(readings: &[(&str, Option<f32>)]) -> Self {
+            Self {
+                sensors: readings
+                    .iter()
+                    .map(|(label, temp)| RawSensor {
+                        label: label.to_string(),
+                        temp: *temp,
+                        ..Default::default()
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    impl TempProvider for MockProvider {
+        fn read(&mut self, options: &ReadOptions) -> SensorReading {
+            build_reading(&self.sensors, options)
+        }
+    }
+
+    fn read_cpu(readings: &[(&str, Option<f32>)], options: &ReadOptions) -> TempReading {
+        let mut provider: Box<dyn TempProvider> = Box::new(MockProvider::new(readings));
+        provider.read(options).cpu
+    }
+
+    fn celsius(celsius: f32) -> TempReading {
+        TempReading::Value(SensorTemp::new(celsius))
+    }
+
+    #[test]
+    fn provider_prefers_amd_over_intel_over_motherboard() {
+        let options = ReadOptions::default();
+        let all = [
+            ("CPU Temperature", Some(40.0)),
+            ("Package id 0", Some(50.0)),
+            ("Tctl", Some(60.0)),
+            ("Tdie", Some(55.0)),
+        ];
+
+        assert_eq!(read_cpu(&all, &options), celsius(55.0));
+        assert_eq!(read_cpu(&all[..3], &options), celsius(60.0));
+        assert_eq!(read_cpu(&all[..2], &options), celsius(50.0));
+        assert_eq!(read_cpu(&all[..1], &options), celsius(40.0));
+    }
+
+    // Labels of a ThinkPad T480: coretemp, thinkpad_acpi, the PCH and the SSD
+    const THINKPAD: &[(&str, Option<f32>)] = &[
+        ("Package id 0", Some(52.0)),
+        ("Core 0", Some(49.0)),
+        ("Core 1", Some(51.0)),
+        ("Core 2", Some(48.0)),
+        ("Core 3", Some(50.0)),
+        ("CPU", Some(53.0)),
+        ("GPU", Some(44.0)),
+        ("temp3", Some(0.0)),
+        ("Composite", Some(36.9)),
+    ];
+
+    // Labels of a Dell Latitude 7490's dell_smm, which has no coretemp on some kernels
+    const DELL: &[(&str, Option<f32>)] = &[
+        ("CPU", Some(61.0)),
+        ("Ambient", Some(42.0)),
+        ("SODIMM", Some(45.0)),
+        ("Other", Some(40.0)),
+    ];
+
+    #[test]
+    fn ec_cpu_sensors_rank_below_the_cpu_driver() {
+        let options = ReadOptions::default();
+        // coretemp wins, thinkpad_acpi reads the same die through the firmware
+        assert_eq!(read_cpu(THINKPAD, &options), celsius(52.0));
+        let without_coretemp: Vec<(&str, Option<f32>)> = THINKPAD
+            .iter()
+            .filter(|(label, _)| *label != "Package id 0")
+            .copied()
+            .collect();
+        // Not the hottest core
+        assert_eq!(read_cpu(&without_coretemp, &options), celsius(53.0));
+
+        assert_eq!(read_cpu(DELL, &options), celsius(61.0));
+        assert_eq!(
+            read_cpu(&[("CPU Temp", Some(58.0))], &options),
+            celsius(58.0)
+        );
+        // Above the motherboard's, below AMD's
+        let ec_and_board = [("CPU Temperature", Some(40.0)), ("CPU", Some(61.0))];
+        assert_eq!(read_cpu(&ec_and_board, &options), celsius(61.0));
+        let ec_and_amd = [("CPU", Some(61.0)), ("Tctl", Some(64.0))];
+        assert_eq!(read_cpu(&ec_and_amd, &options), celsius(64.0));
+        // Per-core EC labels stay cores
+        assert!(!OverallLabel::Ec.regex().is_match("CPU 0"));
+    }
+
+    #[test]
+    fn provider_falls_back_to_cores() {
+        let readings = [
+            ("Core 0", Some(50.0)),
+            ("Core 1", Some(58.0)),
+            ("edge", Some(70.0)),
+        ];
+        let average = ReadOptions {
+            core_aggregation: CoreAggregation::Average,
+            ..Default::default()
+        };
+
+        assert_eq!(read_cpu(&readings, &ReadOptions::default()), celsius(58.0));
+        assert_eq!(read_cpu(&readings, &average), celsius(54.0));
+    }
+
+    #[test]
+    fn coretemp_cores_without_package_sensor() {
+        // `sensors` on an i7-11800H laptop, minus "Package id 0" like on older Atoms
+        let readings = [
+            ("acpitz", Some(27.8)),
+            ("pch_tigerlake", Some(48.0)),
+            ("Core 0", Some(51.0)),
+            ("Core 1", Some(49.0)),
+            ("Core 2", Some(55.0)),
+            ("Core 3", Some(50.0)),
+            ("Core 4", Some(62.0)),
+            ("Core 5", Some(48.0)),
+            ("Core 6", Some(52.0)),
+            ("Core 7", Some(50.0)),
+            ("iwlwifi_1", Some(41.0)),
+            ("Composite", Some(38.9)),
+            ("Sensor 1", Some(38.9)),
+            ("Sensor 2", Some(41.9)),
+        ];
+        let mut provider: Box<dyn TempProvider> = Box::new(MockProvider::new(&readings));
+
+        let reading = provider.read(&ReadOptions::default());
+        assert_eq!(reading.cpu, celsius(62.0));
+        assert_eq!(
+            reading.cpu_sensor,
+            Some(CpuSensor::Cores {
+                aggregation: CoreAggregation::Max,
+                count: 8
+            })
+        );
+    }
+
+    fn apu_sensors() -> Vec<RawSensor> {
+        [
+            ("k10temp", "Tctl", 70.0),
+            ("amdgpu", "edge", 75.0),
+            ("nvme", "Composite", 38.9),
+        ]
+        .into_iter()
+        .map(|(chip, label, temp)| RawSensor {
+            label: label.to_string(),
+            chip: Some(chip.to_string()),
+            temp: Some(temp),
+            ..Default::default()
         })
-        .copied();
+        .collect()
+    }
+
+    #[test]
+    fn apu_prefers_k10temp_and_mentions_amdgpu() {
+        let reading = build_reading(&apu_sensors(), &ReadOptions::default());
+
+        assert_eq!(reading.cpu, celsius(70.0));
+        assert_eq!(
+            reading.cpu_sensor,
+            Some(CpuSensor::Component {
+                label: "Tctl".to_string(),
+                chip: Some("k10temp".to_string()),
+            })
+        );
+        assert_eq!(reading.cpu_alternative.as_deref(), Some("edge (amdgpu)"));
+        assert_eq!(reading.gpu, Some(("edge".to_string(), 75.0)));
+    }
+
+    #[test]
+    fn apu_gpu_sensor_can_still_be_selected() {
+        let options = ReadOptions {
+            selected_sensor: Some("edge".to_string()),
+            ..Default::default()
+        };
+        let reading = build_reading(&apu_sensors(), &options);
+
+        assert_eq!(reading.cpu, celsius(75.0));
+        assert_eq!(reading.cpu_alternative, None);
+    }
+
+    // sysinfo's labels, with the driver name in front
+    const SYSINFO_AMD: &[(&str, Option<f32>)] = &[
+        ("k10temp Tctl", Some(62.0)),
+        ("k10temp Tccd2", Some(58.0)),
+        ("k10temp Tccd1", Some(66.0)),
+        ("amdgpu edge", Some(71.0)),
+        ("nvme Composite", Some(41.0)),
+    ];
+    const SYSINFO_INTEL: &[(&str, Option<f32>)] = &[
+        ("coretemp Package id 0", Some(57.0)),
+        ("coretemp Core 1", Some(55.0)),
+        ("coretemp Core 0", Some(54.0)),
+        ("acpitz temp1", Some(27.8)),
+    ];
+
+    #[test]
+    fn detects_sysinfo_labels_with_the_driver_name() {
+        let options = ReadOptions::default();
+        let amd = MockProvider::new(SYSINFO_AMD).read(&options);
+        assert_eq!(amd.cpu, celsius(62.0));
+        assert_eq!(
+            amd.cpu_sensor,
+            Some(CpuSensor::Component {
+                label: "k10temp Tctl".to_string(),
+                chip: None,
+            })
+        );
+        assert_eq!(amd.cpu_priority, Some(1));
+        assert_eq!(
+            amd.ccds,
+            vec![("Tccd1".to_string(), 66.0), ("Tccd2".to_string(), 58.0)]
+        );
+        assert_eq!(amd.gpu, Some(("amdgpu edge".to_string(), 71.0)));
+
+        let intel = MockProvider::new(SYSINFO_INTEL).read(&options);
+        assert_eq!(intel.cpu, celsius(57.0));
+        assert_eq!(
+            intel.cores,
+            vec![("Core 0".to_string(), 54.0), ("Core 1".to_string(), 55.0)]
+        );
+
+        let hottest_ccd = ReadOptions {
+            hottest_ccd: true,
+            ..Default::default()
+        };
+        assert_eq!(read_cpu(SYSINFO_AMD, &hottest_ccd), celsius(66.0));
+        assert_eq!(
+            get_overall_label_priority("k10temp Tdie", DEFAULT_OVERALL_ORDER),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn sysinfo_gpu_sensors_are_not_the_cpu() {
+        let options = ReadOptions::default();
+        assert!(!counts_for_cpu(
+            "amdgpu edge",
+            sysinfo_chip("amdgpu edge"),
+            &options
+        ));
+        assert!(counts_for_cpu(
+            "k10temp Tctl",
+            sysinfo_chip("k10temp Tctl"),
+            &options
+        ));
+
+        // Still shown as asked, like with hwmon's chip names
+        let readings = [("k10temp Tctl", Some(62.0)), ("amdgpu edge", Some(71.0))];
+        let options = ReadOptions {
+            selected_sensor: Some("amdgpu edge".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(read_cpu(&readings, &options), celsius(71.0));
+    }
+
+    #[test]
+    fn unplugged_selected_sensor_falls_back_to_automatic() {
+        let options = ReadOptions {
+            selected_sensor: Some("usb_dongle temp1".to_string()),
+            ..Default::default()
+        };
+        let reading = build_reading(&apu_sensors(), &options);
+
+        assert!(reading.selected_missing);
+        assert_eq!(reading.cpu, celsius(70.0));
+        assert!(!build_reading(&apu_sensors(), &ReadOptions::default()).selected_missing);
+    }
+
+    #[test]
+    fn names_sensors_with_their_chip() {
+        assert_eq!(sysinfo_chip("k10temp Tctl"), Some("k10temp"));
+        assert_eq!(sysinfo_chip("Package id 0"), None);
+        assert_eq!(sysinfo_chip("acpitz"), None);
+        assert_eq!(sensor_label("k10temp Tctl"), "Tctl");
+        assert_eq!(sensor_label("coretemp Package id 0"), "Package id 0");
+        assert_eq!(sensor_label("Package id 0"), "Package id 0");
+        assert_eq!(
+            sensor_name("k10temp Tctl", Some("k10temp")),
+            "Tctl (k10temp)"
+        );
+        assert_eq!(sensor_name("Tctl", Some("k10temp")), "Tctl (k10temp)");
+        assert_eq!(sensor_name("Core 0", None), "Core 0");
+        assert_eq!(
+            sensor_names(
+                &["Core 0".to_string(), "Tctl".to_string()],
+                &[("Tctl".to_string(), "k10temp".to_string())]
+            ),
+            ["Core 0", "Tctl (k10temp)"]
+        );
+    }
+
+    #[test]
+    fn provider_uses_selected_sensor() {
+        let readings = [("Tctl", Some(60.0)), ("Composite", Some(38.0))];
+        let options = ReadOptions {
+            selected_sensor: Some("Composite".to_string()),
+            ..Default::default()
+        };
+        let missing = ReadOptions {
+            selected_sensor: Some("nvme9".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(read_cpu(&readings, &options), celsius(38.0));
+        assert_eq!(read_cpu(&readings, &missing), celsius(60.0));
+    }
+
+    #[test]
+    fn provider_reports_hottest_ccd() {
+        let readings = [
+            ("Tctl", Some(78.0)),
+            ("Tccd1", Some(72.5)),
+            ("Tccd2", Some(80.0)),
+        ];
+        let options = ReadOptions {
+            hottest_ccd: true,
+            ..Default::default()
+        };
+
+        assert_eq!(read_cpu(&readings, &options), celsius(80.0));
+    }
+
+    #[test]
+    fn provider_without_sensors() {
+        let options = ReadOptions::default();
+
+        assert_eq!(read_cpu(&[], &options), TempReading::NoSensors);
+        assert_eq!(
+            read_cpu(&[("edge", Some(45.0)), ("Composite", Some(38.0))], &options),
+            TempReading::NoSensors
+        );
+    }
+
+    #[test]
+    fn provider_reports_unread_sensors() {
+        let readings = [("Package id 0", None), ("Core 0", None)];
+
+        assert_eq!(
+            read_cpu(&readings, &ReadOptions::default()),
+            TempReading::ReadError("Package id 0, Core 0".to_string())
+        );
+    }
+
+    #[test]
+    fn matches_single_and_multi_digit_core_labels() {
+        for label in ["CPU 0", "CPU 9", "CPU 10", "CPU 127", "Tctl1", "Tctl12"] {
+            assert!(is_core_temp_label(label), "{label} should match");
+        }
+    }
+
+    #[test]
+    fn matches_coretemp_core_labels() {
+        for label in ["Core 0", "Core 7", "Core 12"] {
+            assert!(is_core_temp_label(label), "{label} should match");
+        }
+    }
+
+    #[test]
+    fn rejects_unrelated_labels() {
+        for label in [
+            "CPU",
+            "Core",
+            "Core X",
+            "Package id 0",
+            "Tctl",
+            "edge",
+            "Composite",
+            "CPU 1 fan",
+        ] {
+            assert!(!is_core_temp_label(label), "{label} should not match");
+        }
+    }
+
+    #[test]
+    fn matches_ccd_labels() {
+        for label in ["Tccd1", "Tccd2", "Tccd12"] {
+            assert!(is_core_temp_label(label), "{label} should match");
+        }
+    }
+
+    #[test]
+    fn collects_ccd_temps_of_k10temp() {
+        let labels = [
+            ("Tctl", Some(78.0)),
+            ("Tdie", Some(68.0)),
+            ("Tccd1", Some(72.5)),
+            ("Tccd2", Some(57.25)),
+        ];
+
+        assert_eq!(
+            get_ccd_temps(labels),
+            vec![("Tccd1".to_string(), 72.5), ("Tccd2".to_string(), 57.25)]
+        );
+    }
+
+    #[test]
+    fn orders_ccds_by_number() {
+        let labels = [
+            ("Tccd10", Some(50.0)),
+            ("Tccd2", Some(52.0)),
+            ("Tccd1", Some(51.0)),
+        ];
+
+        let order: Vec<String> = get_ccd_temps(labels)
+            .into_iter()
+            .map(|(label, _)| label)
+            .collect();
+        assert_eq!(order, vec!["Tccd1", "Tccd2", "Tccd10"]);
+    }
+
+    #[test]
+    fn skips_ccds_without_reading() {
+        let labels = [("Tctl", Some(60.0)), ("Tccd1", None), ("Tccd2", Some(55.0))];
+
+        assert_eq!(get_ccd_temps(labels), vec![("Tccd2".to_string(), 55.0)]);
+    }
+
+    #[test]
+    fn no_ccds_on_intel() {
+        let labels = [("Package id 0", Some(55.0)), ("Core 0", Some(52.0))];
+
+        assert!(get_ccd_temps(labels).is_empty());
+    }
+
+    #[test]
+    fn matches_drive_labels() {
+        for label in ["Composite", "nvme", "nvme0", "drivetemp", "Drivetemp 2"] {
+            assert!(is_drive_temp_label(label), "{label} should match");
+        }
+        for label in ["Tctl", "Package id 0", "edge", "acpitz"] {
+            assert!(!is_drive_temp_label(label), "{label} should not match");
+        }
+    }
+
+    #[test]
+    fn groups_all_sensors_by_chip() {
+        let sensor = |chip: &str, label: &str, temp: Option<f32>| {
+            (chip.to_string(), label.to_string(), temp)
+        };
+        let grouped = group_by_chip([
+            sensor("k10temp", "k10temp Tctl", Some(55.0)),
+            sensor("nvme", "nvme Composite", Some(40.0)),
+            sensor("k10temp", "k10temp Tccd1", None),
+            sensor("amdgpu", "amdgpu edge", Some(48.0)),
+        ]);
+
+        assert_eq!(
+            grouped,
+            vec![
+                sensor("k10temp", "k10temp Tctl", Some(55.0)),
+                sensor("k10temp", "k10temp Tccd1", None),
+                sensor("nvme", "nvme Composite", Some(40.0)),
+                sensor("amdgpu", "amdgpu edge", Some(48.0)),
+            ]
+        );
+        assert_eq!(sysinfo_chip("iwlwifi_1 temp1"), Some("iwlwifi_1"));
+    }
+
+    #[test]
+    fn all_sensors_include_ignored_and_unread_ones() {
+        let sensors = [
+            RawSensor {
+                label: "Tctl".to_string(),
+                chip: Some("k10temp".to_string()),
+                temp: Some(55.0),
+                ..Default::default()
+            },
+            RawSensor {
+                label: "temp1".to_string(),
+                chip: Some("iwlwifi_1".to_string()),
+                temp: None,
+                ..Default::default()
+            },
+        ];
+        let options = ReadOptions {
+            all_components: true,
+            blacklist: vec!["Tctl".to_string()],
+            ..Default::default()
+        };
+
+        let all = build_reading(&sensors, &options).all;
+        assert_eq!(
+            all,
+            vec![
+                ("k10temp".to_string(), "Tctl".to_string(), Some(55.0)),
+                ("iwlwifi_1".to_string(), "temp1".to_string(), None),
+            ]
+        );
+        assert!(build_reading(&sensors, &ReadOptions::default())
+            .all
+            .is_empty());
+    }
+
+    #[test]
+    fn sorts_drive_temps_by_label() {
+        let labels = [
+            ("nvme1", Some(41.0)),
+            ("Tctl", Some(60.0)),
+            ("drivetemp", Some(33.0)),
+            ("Composite", Some(38.85)),
+            ("nvme0", None),
+        ];
+
+        assert_eq!(
+            get_drive_temps(labels),
+            vec![
+                ("Composite".to_string(), 38.85),
+                ("drivetemp".to_string(), 33.0),
+                ("nvme1".to_string(), 41.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn collects_temps_of_all_cores() {
+        let labels = [
+            ("Package id 0", Some(60.0)),
+            ("Core 0", Some(50.0)),
+            ("Core 1", Some(52.0)),
+            ("Core 10", Some(71.0)),
+            ("Core 11", None),
+            ("Core 15", Some(55.0)),
+            ("acpitz", Some(26.8)),
+        ];
+
+        assert_eq!(get_core_temps(labels), vec![50.0, 52.0, 71.0, 55.0]);
+    }
+
+    #[test]
+    fn orders_labeled_core_temps_by_number() {
+        let labels = [
+            ("Core 10", Some(71.0)),
+            ("Core 2", Some(52.0)),
+            ("Tccd1", Some(60.0)),
+            ("Core 0", Some(50.0)),
+            ("Core 1", None),
+        ];
+
+        assert_eq!(
+            get_labeled_core_temps(labels),
+            vec![
+                ("Core 0".to_string(), 50.0),
+                ("Core 2".to_string(), 52.0),
+                ("Core 10".to_string(), 71.0),
+                ("Tccd1".to_string(), 60.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn no_core_temps_without_matching_labels() {
+        let labels = [("edge", Some(45.0)), ("Composite", Some(38.0))];
+
+        assert!(get_core_temps(labels).is_empty());
+    }
+
+    #[test]
+    fn reads_single_socket() {
+        let labels = [
+            ("Package id 0", Some(55.0)),
+            ("Core 0", Some(52.0)),
+            ("Core 1", Some(54.0)),
+        ];
+
+        assert_eq!(get_overall_temps(labels, DEFAULT_OVERALL_ORDER), vec![55.0]);
+    }
+
+    #[test]
+    fn reads_every_intel_socket() {
+        let labels = [
+            ("Package id 0", Some(48.0)),
+            ("Core 0", Some(47.0)),
+            ("Package id 1", Some(63.0)),
+            ("Core 0", Some(61.0)),
+        ];
+
+        assert_eq!(
+            get_overall_temps(labels, DEFAULT_OVERALL_ORDER),
+            vec![48.0, 63.0]
+        );
+    }
+
+    #[test]
+    fn reads_every_amd_socket() {
+        let labels = [
+            ("Tctl", Some(70.0)),
+            ("Tccd1", Some(65.0)),
+            ("Tctl", Some(58.5)),
+        ];
+
+        assert_eq!(
+            get_overall_temps(labels, DEFAULT_OVERALL_ORDER),
+            vec![70.0, 58.5]
+        );
+    }
+
+    #[test]
+    fn overall_temps_prefer_higher_priority_labels() {
+        let labels = [
+            ("CPU Temperature", Some(80.0)),
+            ("Package id 0", Some(50.0)),
+            ("Package id 1", Some(52.0)),
+        ];
+
+        assert_eq!(
+            get_overall_temps(labels, DEFAULT_OVERALL_ORDER),
+            vec![50.0, 52.0]
+        );
+    }
+
+    #[test]
+    fn overall_temps_skip_sensors_without_reading() {
+        let labels = [
+            ("Package id 0", None),
+            ("Package id 1", Some(52.0)),
+            ("Package id 10", Some(49.0)),
+        ];
+
+        assert_eq!(
+            get_overall_temps(labels, DEFAULT_OVERALL_ORDER),
+            vec![52.0, 49.0]
+        );
+    }
+
+    #[test]
+    fn prefers_tdie_by_default() {
+        let labels = [
+            ("Tctl", Some(78.0)),
+            ("Tdie", Some(68.0)),
+            ("Tccd1", Some(72.5)),
+        ];
+
+        assert_eq!(get_overall_temps(labels, DEFAULT_OVERALL_ORDER), vec![68.0]);
+    }
+
+    #[test]
+    fn prefers_tctl_when_asked() {
+        let labels = [
+            ("Tctl", Some(78.0)),
+            ("Tdie", Some(68.0)),
+            ("Tccd1", Some(72.5)),
+        ];
+        let options = ReadOptions {
+            prefer_tctl: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            get_overall_temps(labels, options.overall_order()),
+            vec![78.0]
+        );
+    }
+
+    #[test]
+    fn falls_back_to_the_other_amd_label() {
+        let tctl_only = [("Tctl", Some(78.0))];
+        let tdie_only = [("Tdie", Some(68.0))];
+
+        assert_eq!(
+            get_overall_temps(tctl_only, DEFAULT_OVERALL_ORDER),
+            vec![78.0]
+        );
+        assert_eq!(
+            get_overall_temps(tdie_only, TCTL_FIRST_OVERALL_ORDER),
+            vec![68.0]
+        );
+    }
+
+    #[test]
+    fn rejects_partial_overall_labels() {
+        for label in ["Package id", "Package id 0 ", "Tctl0", "CPU Temperature 2"] {
+            assert_eq!(
+                get_overall_label_priority(label, DEFAULT_OVERALL_ORDER),
+                None,
+                "{label}"
+            );
+        }
+    }
+
+    #[test]
+    fn ranks_gpu_labels_in_order() {
+        assert_eq!(get_gpu_label_priority("edge"), Some(0));
+        assert_eq!(get_gpu_label_priority("mem"), Some(2));
+        assert_eq!(get_gpu_label_priority("GPU"), Some(4));
+    }
+
+    #[test]
+    fn ranks_driver_prefixed_gpu_labels_like_the_label() {
+        assert_eq!(get_gpu_label_priority("amdgpu edge"), Some(0));
+        assert_eq!(get_gpu_label_priority("nouveau GPU core"), Some(3));
+        // Known driver, unknown sensor: after every known label
+        assert_eq!(
+            get_gpu_label_priority("radeon temp1"),
+            Some(GPU_TEMP_LABELS.len())
+        );
+    }
+
+    #[test]
+    fn ignores_labels_of_other_sensors() {
+        for label in ["Package id 0", "Tctl", "Composite", "acpitz", "Edge"] {
+            assert_eq!(get_gpu_label_priority(label), None, "{label}");
+        }
+    }
+
+    #[test]
+    fn lists_unread_cpu_sensors() {
+        let labels = [
+            ("Package id 0", None),
+            ("Core 0", None),
+            ("Core 1", Some(50.0)),
+            ("edge", None),
+        ];
+
+        assert_eq!(
+            get_unread_cpu_labels(labels, None),
+            vec!["Package id 0", "Core 0"]
+        );
+    }
+
+    #[test]
+    fn lists_each_unread_cpu_sensor_once() {
+        let labels = [
+            ("Package id 0", None),
+            ("Core 0", None),
+            ("Package id 0", None),
+            ("Core 0", Some(50.0)),
+            ("Core 0", None),
+        ];
+
+        assert_eq!(
+            get_unread_cpu_labels(labels, None),
+            vec!["Package id 0", "Core 0"]
+        );
+    }
+
+    #[test]
+    fn lists_unread_selected_sensor() {
+        let labels = [("Composite", None), ("acpitz", Some(40.0))];
+
+        assert_eq!(
+            get_unread_cpu_labels(labels, Some("Composite")),
+            vec!["Composite"]
+        );
+        assert!(get_unread_cpu_labels(labels, None).is_empty());
+    }
+
+    #[test]
+    fn aggregates_max() {
+        let temps = [50.0, 72.5, 61.0];
+
+        assert_eq!(
+            aggregate_core_temps(&temps, CoreAggregation::Max),
+            Some(72.5)
+        );
+    }
+
+    #[test]
+    fn aggregates_average() {
+        assert_eq!(
+            aggregate_core_temps(&[50.0, 51.0], CoreAggregation::Average),
+            Some(50.5)
+        );
+
+        let average = aggregate_core_temps(&[50.0, 50.0, 51.0], CoreAggregation::Average).unwrap();
+        assert!((average - 50.333_336).abs() < 0.0001);
+    }
+
+    #[test]
+    fn aggregates_median_of_odd_count() {
+        let temps = [70.0, 50.0, 55.0, 90.0, 52.0];
+
+        assert_eq!(
+            aggregate_core_temps(&temps, CoreAggregation::Median),
+            Some(55.0)
+        );
+    }
+
+    #[test]
+    fn aggregates_median_of_even_count() {
+        let temps = [70.0, 50.0, 55.0, 90.0];
+
+        assert_eq!(
+            aggregate_core_temps(&temps, CoreAggregation::Median),
+            Some(62.5)
+        );
+    }
+
+    #[test]
+    fn aggregates_single_value() {
+        for aggregation in CoreAggregation::ALL {
+            assert_eq!(aggregate_core_temps(&[48.0], aggregation), Some(48.0));
+        }
+    }
+
+    #[test]
+    fn aggregates_nothing_without_temps() {
+        for aggregation in CoreAggregation::ALL {
+            assert_eq!(aggregate_core_temps(&[], aggregation), None);
+        }
+    }
+
+    #[test]
+    fn finds_hottest_sensor() {
+        let readings = [
+            ("Tctl", Some(55.0)),
+            ("Composite", Some(71.85)),
+            ("edge", Some(48.0)),
+        ];
+
+        assert_eq!(
+            get_hottest_temp(readings),
+            Some(("Composite".to_string(), 71.85))
+        );
+    }
+
+    #[test]
+    fn hottest_sensor_skips_bogus_readings() {
+        let readings = [
+            ("Tctl", Some(55.0)),
+            ("Sensor 1", Some(-273.1)),
+            ("Sensor 2", Some(127.0)),
+            ("acpitz", Some(98.0)),
+            ("Core 0", None),
+        ];
+        let options = ReadOptions {
+            hottest_any: true,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            MockProvider::new(&readings).read(&options).hottest,
+            Some(("Tctl".to_string(), 55.0))
+        );
+    }
+
+    #[test]
+    fn hottest_sensor_follows_the_configured_limit() {
+        let readings = [("Tctl", Some(55.0)), ("junction", Some(121.0))];
+        let options = |max_plausible_celsius| ReadOptions {
+            hottest_any: true,
+            max_plausible_celsius: Some(max_plausible_celsius),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            MockProvider::new(&readings).read(&options(125.0)).hottest,
+            Some(("junction".to_string(), 121.0))
+        );
+        assert_eq!(
+            MockProvider::new(&readings).read(&options(110.0)).hottest,
+            Some(("Tctl".to_string(), 55.0))
+        );
+    }
+
+    #[test]
+    fn no_hottest_sensor_without_readings() {
+        let readings = [("acpitz", Some(26.8)), ("Core 0", None)];
+
+        assert_eq!(get_hottest_temp(readings), None);
+    }
+
+    #[test]
+    fn names_the_sensor_that_gave_the_value() {
+        let readings: Vec<(String, Option<f32>)> = [
+            ("Tctl", Some(70.0)),
+            ("Tctl", Some(58.5)),
+            ("Tccd1", Some(65.0)),
+            ("Core 0", Some(60.0)),
+        ]
+        .iter()
+        .map(|(label, temp)| (label.to_string(), *temp))
+        .collect();
+        let ccd = ReadOptions {
+            hottest_ccd: true,
+            ..Default::default()
+        };
+
+        let component = |label: &str| {
+            Some(CpuSensor::Component {
+                label: label.to_string(),
+                chip: None,
+            })
+        };
+
+        assert_eq!(
+            get_cpu_sensor(&readings, &ReadOptions::default()),
+            component("Tctl")
+        );
+        assert_eq!(get_cpu_sensor(&readings, &ccd), component("Tccd1"));
+        assert_eq!(
+            get_cpu_sensor(&readings[3..], &ccd),
+            Some(CpuSensor::Cores {
+                aggregation: CoreAggregation::Max,
+                count: 1,
+            })
+        );
+        assert_eq!(get_cpu_sensor(&[], &ccd), None);
+    }
+
+    #[test]
+    fn blacklisted_package_falls_back_to_cores() {
+        let options = ReadOptions {
+            blacklist: vec!["Package id 0".to_string()],
+            ..Default::default()
+        };
+        let mut provider: Box<dyn TempProvider> = Box::new(MockProvider::new(&[
+            ("Package id 0", Some(90.0)),
+            ("Core 0", Some(50.0)),
+            ("Core 1", Some(60.0)),
+        ]));
+
+        let reading = provider.read(&options);
+        assert_eq!(reading.cpu, celsius(60.0));
+        assert_eq!(
+            reading.cpu_sensor,
+            Some(CpuSensor::Cores {
+                aggregation: CoreAggregation::Max,
+                count: 2
+            })
+        );
+        assert_eq!(reading.labels, ["Core 0", "Core 1"]);
+    }
+
+    #[test]
+    fn blacklisted_sensors_are_never_the_hottest() {
+        let options = ReadOptions {
+            blacklist: vec!["pch_*".to_string()],
+            hottest_any: true,
+            ..Default::default()
+        };
+        let mut provider: Box<dyn TempProvider> = Box::new(MockProvider::new(&[
+            ("pch_skylake", Some(100.0)),
+            ("Tctl", Some(60.0)),
+        ]));
+
+        assert_eq!(
+            provider.read(&options).hottest,
+            Some(("Tctl".to_string(), 60.0))
+        );
+    }
+
+    #[test]
+    fn blacklisted_selected_sensor_uses_automatic_detection() {
+        let options = ReadOptions {
+            selected_sensor: Some("acpitz temp1".to_string()),
+            blacklist: vec!["acpitz*".to_string()],
+            ..Default::default()
+        };
+        let readings = [("acpitz temp1", Some(26.8)), ("Tctl", Some(60.0))];
+
+        assert_eq!(read_cpu(&readings, &options), celsius(60.0));
+    }
+
+    #[test]
+    fn matches_exact_labels_and_globs() {
+        assert!(matches_pattern("acpitz", "acpitz"));
+        assert!(!matches_pattern("acpitz", "acpitz temp1"));
+        assert!(matches_pattern("pch_*", "pch_skylake"));
+        assert!(matches_pattern("*temp*", "acpitz temp1"));
+        assert!(matches_pattern("Core ?", "Core 3"));
+        assert!(!matches_pattern("Core ?", "Core 12"));
+        assert!(matches_pattern("*", ""));
+        assert!(!matches_pattern("pch_*", "Tctl"));
+    }
+
+    #[test]
+    fn recognizes_refused_chips() {
+        let denied = || Err(io::Error::from_raw_os_error(13));
+        let timed_out = || Err(io::Error::from(io::ErrorKind::TimedOut));
+
+        assert!(chip_denied(&[denied(), denied()]).is_some());
+        assert_eq!(
+            chip_denied(&[denied()]).map(ToString::to_string).as_deref(),
+            Some("Permission denied (os error 13)")
+        );
+        // One readable input means the chip is accessible
+        assert!(chip_denied(&[denied(), Ok("54000\n".to_string())]).is_none());
+        assert!(chip_denied(&[timed_out(), timed_out()]).is_none());
+        assert!(chip_denied(&[]).is_none());
+    }
+
+    #[test]
+    fn probe_finds_nothing_to_complain_about() {
+        let root = crate::test_dir::TestDir::new("probe");
+        root.write("hwmon0/temp1_input", "54000\n");
+
+        assert_eq!(probe_access(&root), None);
+        assert_eq!(probe_access(Path::new("/nonexistent/hwmon")), None);
+    }
+
+    #[test]
+    fn offsets_the_cpu_value() {
+        let component = |label: &str| {
+            let mut reading = SensorReading {
+                cpu: TempReading::Value(SensorTemp {
+                    celsius: 72.0,
+                    max: None,
+                    critical: Some(100.0),
+                }),
+                cpu_sensor: Some(CpuSensor::Component {
+                    label: label.to_string(),
+                    chip: None,
+                }),
+                ..Default::default()
+            };
+            let options = ReadOptions {
+                offset_celsius: -2.0,
+                sensor_offsets: BTreeMap::from([("EC".to_string(), -7.0)]),
+                ..Default::default()
+            };
+            apply_offset(&mut reading, &options);
+            reading
+        };
+
+        let ec = component("EC");
+        assert_eq!(ec.cpu_raw, Some(72.0));
+        assert_eq!(
+            ec.cpu,
+            TempReading::Value(SensorTemp {
+                celsius: 65.0,
+                max: None,
+                critical: Some(93.0),
+            })
+        );
+        // Sensors without their own offset get the global one
+        assert_eq!(component("Tctl").cpu_raw, Some(72.0));
+        assert!(matches!(component("Tctl").cpu, TempReading::Value(temp) if temp.celsius == 70.0));
+
+        let mut error = SensorReading {
+            cpu: TempReading::ReadError("EC".to_string()),
+            ..Default::default()
+        };
+        apply_offset(&mut error, &ReadOptions::default());
+        assert_eq!(error.cpu_raw, None);
+    }
+
+    #[test]
+    fn finds_the_hottest_core() {
+        let cores = get_labeled_core_temps([
+            ("Core 0", Some(71.0)),
+            ("Core 11", Some(88.0)),
+            ("Core 2", Some(88.0)),
+            ("Core 3", None),
+        ]);
+        // Ties go to the lower core number
+        assert_eq!(hottest_core(&cores), Some(&("Core 2".to_string(), 88.0)));
+        assert_eq!(hottest_core(&[]), None);
+    }
 }
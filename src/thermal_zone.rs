@@ -0,0 +1,116 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(crate) const THERMAL_ROOT: &str = "/sys/class/thermal";
+
+// Substrings of thermal zone types that describe the CPU
+const CPU_ZONE_TYPES: &[&str] = &["cpu", "x86_pkg_temp", "soc"];
+
+fn is_cpu_zone_type(zone_type: &str) -> bool {
+    let zone_type = zone_type.trim().to_lowercase();
+    CPU_ZONE_TYPES.iter().any(|item| zone_type.contains(item))
+}
+
+// Thermal zones report millidegrees Celsius
+pub(crate) fn parse_millidegrees(contents: &str) -> Option<f32> {
+    let millidegrees = contents.trim().parse::<i64>().ok()?;
+    Some(millidegrees as f32 / 1000.0)
+}
+
+// Directories of the CPU related thermal zones under `root`, sorted by name
+pub(crate) fn find_cpu_zones(root: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(root) else {
+        return vec![];
+    };
+
+    let mut zones: Vec<PathBuf> = entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.starts_with("thermal_zone"))
+        })
+        .map(|entry| entry.path())
+        .filter(|zone| {
+            fs::read_to_string(zone.join("type"))
+                .is_ok_and(|zone_type| is_cpu_zone_type(&zone_type))
+        })
+        .collect();
+
+    zones.sort();
+    zones
+}
+
+// Hottest of the given zones, zones that can't be read are skipped
+pub(crate) fn read_zones_temp(zones: &[PathBuf]) -> Option<f32> {
+    zones
+        .iter()
+        .filter_map(|zone| fs::read_to_string(zone.join("temp")).ok())
+        .filter_map(|contents| parse_millidegrees(&contents))
+        .reduce(f32::max)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn recognizes_cpu_zone_types() {
+        for zone_type in ["cpu-thermal\n", "x86_pkg_temp\n", "soc_thermal\n", "CPU"] {
+            assert!(is_cpu_zone_type(zone_type), "{zone_type} should match");
+        }
+    }
+
+    #[test]
+    fn ignores_other_zone_types() {
+        for zone_type in [
+            "acpitz\n",
+            "iwlwifi_1\n",
+            "gpu-thermal\n",
+            "INT3400 Thermal\n",
+        ] {
+            assert!(!is_cpu_zone_type(zone_type), "{zone_type} should not match");
+        }
+    }
+
+    #[test]
+    fn parses_millidegrees() {
+        assert_eq!(parse_millidegrees("45000\n"), Some(45.0));
+        assert_eq!(parse_millidegrees("52375"), Some(52.375));
+        assert_eq!(parse_millidegrees("-5000\n"), Some(-5.0));
+        assert_eq!(parse_millidegrees(""), None);
+        assert_eq!(parse_millidegrees("N/A\n"), None);
+    }
+
+    #[test]
+    fn finds_and_reads_cpu_zones() {
+        let root = TestDir::new("thermal-zone");
+        let zones = [
+            ("thermal_zone0", "cpu-thermal\n", "48500\n"),
+            ("thermal_zone1", "gpu-thermal\n", "90000\n"),
+            ("thermal_zone2", "soc_thermal\n", "51000\n"),
+            ("cooling_device0", "cpu-thermal\n", "99000\n"),
+        ];
+        for (name, zone_type, temp) in zones {
+            root.write(format!("{name}/type"), zone_type);
+            root.write(format!("{name}/temp"), temp);
+        }
+
+        let found = find_cpu_zones(&root);
+        let temp = read_zones_temp(&found);
+
+        assert_eq!(
+            found,
+            vec![root.join("thermal_zone0"), root.join("thermal_zone2")]
+        );
+        assert_eq!(temp, Some(51.0));
+    }
+
+    #[test]
+    fn missing_root_has_no_zones() {
+        assert!(find_cpu_zones(Path::new("/nonexistent/thermal")).is_empty());
+        assert_eq!(read_zones_temp(&[]), None);
+    }
+}
@@ -0,0 +1,226 @@
+// Whether the CPU is actually throttling, not only how hot it is. Intel's coretemp driver
+// counts throttle events under `cpu*/thermal_throttle`, elsewhere (AMD) it's inferred from
+// busy cores running well below their base clock. Each core is judged by its own load and
+// clock, idle cores clock down to save power and would drag an average down. Only what happened since the previous
+// tick counts, so it clears with the first sample after the throttling stopped.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(crate) const CPU_ROOT: &str = "/sys/devices/system/cpu";
+// The clock of busy cores below this share of the base clock counts as throttled
+const THROTTLED_CLOCK_RATIO: f64 = 0.9;
+// Load of a core in percent, below it a low clock is just power saving
+const BUSY_LOAD: f32 = 50.0;
+// Counters of the CPU's own throttling, cores and the whole package
+const COUNTER_FILES: [&str; 2] = ["core_throttle_count", "package_throttle_count"];
+// Nominal clock in kHz, `intel_pstate` and `amd-pstate` respectively
+const BASE_CLOCK_FILES: [&str; 2] = ["base_frequency", "amd_pstate_nominal_freq"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Throttle {
+    pub(crate) active: bool,
+    // Times it started throttling since the applet started
+    pub(crate) events: u64,
+}
+
+#[derive(Debug)]
+pub(crate) struct ThrottleMonitor {
+    root: PathBuf,
+    // Sum of the counters at the last sample, `None` before the first one or without them
+    last_count: Option<u64>,
+    // Looked up on the first sample that needs it, `Some(None)` when there's none
+    base_khz: Option<Option<u64>>,
+    throttle: Throttle,
+}
+
+impl Default for ThrottleMonitor {
+    fn default() -> Self {
+        Self::new(Path::new(CPU_ROOT))
+    }
+}
+
+// "cpu12" but not "cpufreq" or "cpuidle"
+fn is_cpu_dir(name: &str) -> bool {
+    name.strip_prefix("cpu")
+        .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn read_number(path: &Path) -> Option<u64> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+// Sum of every CPU's counters, `None` if there are none. The package counter is repeated in
+// every CPU of the package, that doesn't matter for telling whether it went up.
+fn read_counters(root: &Path) -> Option<u64> {
+    let entries = fs::read_dir(root).ok()?;
+    let counts: Vec<u64> = entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_str().is_some_and(is_cpu_dir))
+        .flat_map(|entry| {
+            let dir = entry.path().join("thermal_throttle");
+            COUNTER_FILES.map(|file| read_number(&dir.join(file)))
+        })
+        .flatten()
+        .collect();
+    (!counts.is_empty()).then(|| counts.iter().sum())
+}
+
+fn read_base_khz(root: &Path) -> Option<u64> {
+    let cpufreq = root.join("cpu0").join("cpufreq");
+    BASE_CLOCK_FILES
+        .iter()
+        .find_map(|file| read_number(&cpufreq.join(file)))
+        .filter(|khz| *khz > 0)
+}
+
+// The busy cores run well below the base clock on average. `cores` are (percent, MHz).
+fn inferred(cores: &[(f32, u64)], base_khz: Option<u64>) -> bool {
+    let Some(base_khz) = base_khz else {
+        return false;
+    };
+    let busy: Vec<u64> = cores
+        .iter()
+        .filter(|(load, _)| *load >= BUSY_LOAD)
+        .map(|(_, mhz)| *mhz)
+        .collect();
+    if busy.is_empty() {
+        return false;
+    }
+    let clock_khz = (busy.iter().sum::<u64>() * 1000) as f64 / busy.len() as f64;
+    clock_khz < base_khz as f64 * THROTTLED_CLOCK_RATIO
+}
+
+impl ThrottleMonitor {
+    pub(crate) fn new(root: &Path) -> Self {
+        Self {
+            root: root.to_path_buf(),
+            last_count: None,
+            base_khz: None,
+            throttle: Throttle::default(),
+        }
+    }
+
+    // `cores`, (percent, MHz) of every core, are only used without the counters. Files that
+    // don't exist just mean it can't tell, that's never an error.
+    pub(crate) fn sample(&mut self, cores: &[(f32, u64)]) -> Throttle {
+        let active = match read_counters(&self.root) {
+            Some(count) => self
+                .last_count
+                .replace(count)
+                .is_some_and(|last| count > last),
+            None => {
+                self.last_count = None;
+                let root = &self.root;
+                let base_khz = *self.base_khz.get_or_insert_with(|| read_base_khz(root));
+                inferred(cores, base_khz)
+            }
+        };
+
+        if active && !self.throttle.active {
+            self.throttle.events += 1;
+        }
+        self.throttle.active = active;
+        self.throttle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_dir::TestDir;
+
+    #[test]
+    fn recognizes_cpu_dirs() {
+        assert!(is_cpu_dir("cpu0"));
+        assert!(is_cpu_dir("cpu15"));
+        assert!(!is_cpu_dir("cpu"));
+        assert!(!is_cpu_dir("cpufreq"));
+        assert!(!is_cpu_dir("cpuidle"));
+    }
+
+    #[test]
+    fn infers_throttling_of_busy_cores() {
+        // 3.0 GHz base clock
+        let base = Some(3_000_000);
+        assert!(inferred(&[(90.0, 2000), (95.0, 2100)], base));
+        assert!(!inferred(&[(90.0, 2900), (95.0, 2900)], base));
+        // Idle cores clock down to save power
+        assert!(!inferred(&[(5.0, 800), (3.0, 800)], base));
+        assert!(!inferred(&[], base));
+        assert!(!inferred(&[(90.0, 800)], None));
+    }
+
+    #[test]
+    fn idle_cores_dont_count() {
+        // Half the cores busy at full boost, the others idle. The average of all of them
+        // would be well below the base clock.
+        let cores = [
+            (100.0, 4500),
+            (100.0, 4500),
+            (100.0, 4500),
+            (100.0, 4500),
+            (0.5, 800),
+            (0.5, 800),
+            (0.5, 800),
+            (0.5, 800),
+        ];
+        assert!(!inferred(&cores, Some(3_800_000)));
+    }
+
+    #[test]
+    fn counts_throttle_events_from_the_counters() {
+        let root = TestDir::new("throttle");
+        let write = |cpu: &str, core: u64, package: u64| {
+            let dir = format!("{cpu}/thermal_throttle");
+            root.write(format!("{dir}/core_throttle_count"), &format!("{core}\n"));
+            root.write(
+                format!("{dir}/package_throttle_count"),
+                &format!("{package}\n"),
+            );
+        };
+        write("cpu0", 3, 10);
+        write("cpu1", 0, 10);
+        fs::create_dir_all(root.join("cpufreq")).unwrap();
+
+        let mut monitor = ThrottleMonitor::new(&root);
+        // Throttled before the applet started, that isn't now
+        assert_eq!(monitor.sample(&[]), Throttle::default());
+
+        write("cpu1", 1, 10);
+        let throttling = Throttle {
+            active: true,
+            events: 1,
+        };
+        assert_eq!(monitor.sample(&[]), throttling);
+        write("cpu1", 2, 12);
+        assert_eq!(monitor.sample(&[]), throttling);
+        // Cleared on the first sample without new events
+        assert_eq!(
+            monitor.sample(&[]),
+            Throttle {
+                active: false,
+                events: 1,
+            }
+        );
+        write("cpu0", 4, 10);
+        assert_eq!(monitor.sample(&[]).events, 2);
+    }
+
+    #[test]
+    fn infers_without_counters() {
+        let root = TestDir::new("throttle-infer");
+        root.write("cpu0/cpufreq/amd_pstate_nominal_freq", "3800000\n");
+
+        let mut monitor = ThrottleMonitor::new(&root);
+        assert!(!monitor.sample(&[(95.0, 4200)]).active);
+        assert!(monitor.sample(&[(95.0, 2400)]).active);
+        assert_eq!(monitor.sample(&[(95.0, 4000)]).events, 1);
+    }
+
+    #[test]
+    fn missing_sysfs_is_not_throttling() {
+        let mut monitor = ThrottleMonitor::new(Path::new("/nonexistent/cpu"));
+        assert_eq!(monitor.sample(&[(100.0, 400)]), Throttle::default());
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// How far back the diagnostics look, long enough to catch an occasional slow EC read
+const TIMING_WINDOW: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct TimingSummary {
+    pub(crate) avg: Duration,
+    pub(crate) max: Duration,
+}
+
+// Durations of the last `TIMING_WINDOW`, at most one per tick so it stays small even with
+// the shortest refresh period
+#[derive(Debug, Default)]
+pub(crate) struct RecentDurations {
+    samples: VecDeque<(Instant, Duration)>,
+}
+
+impl RecentDurations {
+    pub(crate) fn push(&mut self, duration: Duration) {
+        self.push_at(Instant::now(), duration);
+    }
+
+    fn push_at(&mut self, now: Instant, duration: Duration) {
+        while self
+            .samples
+            .front()
+            .is_some_and(|(time, _)| now.duration_since(*time) > TIMING_WINDOW)
+        {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((now, duration));
+    }
+
+    pub(crate) fn summary(&self) -> Option<TimingSummary> {
+        let max = self.samples.iter().map(|(_, duration)| *duration).max()?;
+        let total: Duration = self.samples.iter().map(|(_, duration)| *duration).sum();
+        Some(TimingSummary {
+            avg: total / self.samples.len() as u32,
+            max,
+        })
+    }
+}
+
+// "3 ms", the fraction only below 10 ms where it's still telling
+pub(crate) fn format_millis(duration: Duration) -> String {
+    let millis = duration.as_secs_f64() * 1000.0;
+    if millis < 10.0 {
+        format!("{millis:.1} ms")
+    } else {
+        format!("{millis:.0} ms")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_the_last_minute() {
+        let start = Instant::now();
+        let mut durations = RecentDurations::default();
+        assert_eq!(durations.summary(), None);
+
+        durations.push_at(start, Duration::from_millis(120));
+        durations.push_at(start + Duration::from_secs(30), Duration::from_millis(2));
+        durations.push_at(start + Duration::from_secs(40), Duration::from_millis(4));
+        assert_eq!(
+            durations.summary(),
+            Some(TimingSummary {
+                avg: Duration::from_millis(42),
+                max: Duration::from_millis(120),
+            })
+        );
+
+        // The slow read is more than a minute old now
+        durations.push_at(start + Duration::from_secs(61), Duration::from_millis(6));
+        assert_eq!(
+            durations.summary(),
+            Some(TimingSummary {
+                avg: Duration::from_millis(4),
+                max: Duration::from_millis(6),
+            })
+        );
+    }
+
+    #[test]
+    fn formats_milliseconds() {
+        assert_eq!(format_millis(Duration::from_micros(3300)), "3.3 ms");
+        assert_eq!(format_millis(Duration::from_micros(112_400)), "112 ms");
+    }
+}
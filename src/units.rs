@@ -0,0 +1,280 @@
+use crate::config::TemperatureUnit;
+use crate::locale;
+
+// Shown instead of a value that is missing or not a number
+pub(crate) const NO_VALUE: &str = "--";
+
+pub(crate) fn to_fahrenheit(celsius: f32) -> f32 {
+    celsius * 1.8 + 32.0
+}
+
+pub(crate) fn from_fahrenheit(fahrenheit: f32) -> f32 {
+    (fahrenheit - 32.0) / 1.8
+}
+
+pub(crate) fn to_kelvin(celsius: f32) -> f32 {
+    celsius + 273.15
+}
+
+pub(crate) fn from_kelvin(kelvin: f32) -> f32 {
+    kelvin - 273.15
+}
+
+pub(crate) fn to_unit(celsius: f32, unit: TemperatureUnit) -> f32 {
+    match unit {
+        TemperatureUnit::Celsius => celsius,
+        TemperatureUnit::Fahrenheit => to_fahrenheit(celsius),
+        TemperatureUnit::Kelvin => to_kelvin(celsius),
+    }
+}
+
+pub(crate) fn from_unit(value: f32, unit: TemperatureUnit) -> f32 {
+    match unit {
+        TemperatureUnit::Celsius => value,
+        TemperatureUnit::Fahrenheit => from_fahrenheit(value),
+        TemperatureUnit::Kelvin => from_kelvin(value),
+    }
+}
+
+pub(crate) fn unit_symbol(unit: TemperatureUnit) -> &'static str {
+    match unit {
+        TemperatureUnit::Celsius => "°C",
+        TemperatureUnit::Fahrenheit => "°F",
+        TemperatureUnit::Kelvin => "K",
+    }
+}
+
+// Shown next to `unit` when both units are on, Celsius users get Fahrenheit and the rest
+// Celsius
+pub(crate) fn secondary_unit(unit: TemperatureUnit) -> TemperatureUnit {
+    match unit {
+        TemperatureUnit::Celsius => TemperatureUnit::Fahrenheit,
+        TemperatureUnit::Fahrenheit | TemperatureUnit::Kelvin => TemperatureUnit::Celsius,
+    }
+}
+
+// Rounds half away from zero. The formatter rounds half to even, which would show 98.5 as
+// "98" but 99.5 as "100".
+fn round_to(value: f32, decimals: usize) -> f32 {
+    let scale = 10f32.powi(decimals as i32);
+    let rounded = (value * scale).round() / scale;
+    // "-0" looks broken
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+// With the decimal separator of the locale
+fn format_converted(value: f32, decimals: usize) -> Option<String> {
+    value
+        .is_finite()
+        .then(|| locale::localize(format!("{:.*}", decimals, round_to(value, decimals))))
+}
+
+// The value in `unit` without the symbol, `None` for NaN and infinity from a broken sensor
+pub(crate) fn format_number(
+    celsius: f32,
+    unit: TemperatureUnit,
+    decimals: usize,
+) -> Option<String> {
+    format_converted(to_unit(celsius, unit), decimals)
+}
+
+// Like `format_number`, rounded to the nearest multiple of `step` in `unit`. Never has
+// decimals, the steps are whole degrees.
+pub(crate) fn format_number_in_steps(
+    celsius: f32,
+    unit: TemperatureUnit,
+    step: u8,
+) -> Option<String> {
+    let step = f32::from(step.max(1));
+    format_converted(round_to(to_unit(celsius, unit) / step, 0) * step, 0)
+}
+
+// "45°C", always with `decimals` decimals so the width only changes with the integer part
+pub(crate) fn format_temp(celsius: f32, unit: TemperatureUnit, decimals: usize) -> String {
+    format_value(to_unit(celsius, unit), unit, decimals)
+}
+
+// Like `format_temp`, for a value that is already in `unit`
+pub(crate) fn format_value(value: f32, unit: TemperatureUnit, decimals: usize) -> String {
+    match format_converted(value, decimals) {
+        Some(number) => format!("{number}{}", unit_symbol(unit)),
+        None => NO_VALUE.to_string(),
+    }
+}
+
+// A temperature difference in `unit`, Kelvin steps are as large as Celsius ones
+pub(crate) fn to_unit_delta(celsius: f32, unit: TemperatureUnit) -> f32 {
+    match unit {
+        TemperatureUnit::Celsius | TemperatureUnit::Kelvin => celsius,
+        TemperatureUnit::Fahrenheit => celsius * 9.0 / 5.0,
+    }
+}
+
+// "−18°" or "+3°", always signed so it can't be mistaken for a temperature. Uses the real
+// minus sign, which is as wide as the plus.
+pub(crate) fn format_delta(celsius: f32, unit: TemperatureUnit, decimals: usize) -> String {
+    let Some(number) = format_converted(to_unit_delta(celsius, unit).abs(), decimals) else {
+        return NO_VALUE.to_string();
+    };
+    let symbol = match unit {
+        TemperatureUnit::Kelvin => "K",
+        TemperatureUnit::Celsius | TemperatureUnit::Fahrenheit => "°",
+    };
+    let sign = match round_to(to_unit_delta(celsius, unit), decimals) {
+        rounded if rounded < 0.0 => "\u{2212}",
+        rounded if rounded > 0.0 => "+",
+        _ => "±",
+    };
+    format!("{sign}{number}{symbol}")
+}
+
+// The temperature as it's shown, converted back to Celsius. The alerts compare these, so they
+// go off together with the panel's color.
+pub(crate) fn shown_celsius(celsius: f32, unit: TemperatureUnit, decimals: usize) -> f32 {
+    from_unit(round_to(to_unit(celsius, unit), decimals), unit)
+}
+
+// Compares the values as they're shown, so a temperature displayed as the threshold
+// counts as reaching it. NaN never reaches anything.
+pub(crate) fn reaches_threshold(
+    celsius: f32,
+    threshold_celsius: f32,
+    unit: TemperatureUnit,
+    decimals: usize,
+) -> bool {
+    shown_celsius(celsius, unit, decimals) >= shown_celsius(threshold_celsius, unit, decimals)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_units() {
+        assert_eq!(to_fahrenheit(100.0), 212.0);
+        assert_eq!(to_fahrenheit(-40.0), -40.0);
+        assert_eq!(to_kelvin(0.0), 273.15);
+        for unit in [
+            TemperatureUnit::Celsius,
+            TemperatureUnit::Fahrenheit,
+            TemperatureUnit::Kelvin,
+        ] {
+            assert!((from_unit(to_unit(42.5, unit), unit) - 42.5).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn rounds_to_steps_in_the_display_unit() {
+        let step = |celsius, unit| format_number_in_steps(celsius, unit, 5);
+        assert_eq!(step(52.4, TemperatureUnit::Celsius).as_deref(), Some("50"));
+        assert_eq!(step(52.5, TemperatureUnit::Celsius).as_deref(), Some("55"));
+        assert_eq!(step(-1.0, TemperatureUnit::Celsius).as_deref(), Some("0"));
+        // 52.4°C is 126.32°F
+        assert_eq!(
+            step(52.4, TemperatureUnit::Fahrenheit).as_deref(),
+            Some("125")
+        );
+        assert_eq!(step(f32::NAN, TemperatureUnit::Celsius), None);
+        assert_eq!(
+            format_number_in_steps(61.9, TemperatureUnit::Celsius, 0).as_deref(),
+            Some("62")
+        );
+    }
+
+    #[test]
+    fn formats_signed_deltas() {
+        assert_eq!(
+            format_delta(-18.0, TemperatureUnit::Celsius, 0),
+            "\u{2212}18°"
+        );
+        assert_eq!(format_delta(3.2, TemperatureUnit::Celsius, 0), "+3°");
+        assert_eq!(format_delta(-0.3, TemperatureUnit::Celsius, 0), "±0°");
+        assert_eq!(
+            format_delta(-10.0, TemperatureUnit::Fahrenheit, 0),
+            "\u{2212}18°"
+        );
+        assert_eq!(format_delta(5.0, TemperatureUnit::Kelvin, 1), "+5.0K");
+        assert_eq!(
+            format_delta(f32::NAN, TemperatureUnit::Celsius, 0),
+            NO_VALUE
+        );
+    }
+
+    #[test]
+    fn formats_negative_celsius() {
+        assert_eq!(format_temp(-5.0, TemperatureUnit::Celsius, 0), "-5°C");
+        assert_eq!(format_temp(-0.3, TemperatureUnit::Celsius, 0), "0°C");
+        assert_eq!(format_temp(-10.0, TemperatureUnit::Fahrenheit, 1), "14.0°F");
+    }
+
+    #[test]
+    fn rounds_half_away_from_zero() {
+        assert_eq!(format_temp(99.5, TemperatureUnit::Celsius, 0), "100°C");
+        assert_eq!(format_temp(98.5, TemperatureUnit::Celsius, 0), "99°C");
+        assert_eq!(format_temp(99.49, TemperatureUnit::Celsius, 0), "99°C");
+        assert_eq!(format_temp(45.25, TemperatureUnit::Celsius, 1), "45.3°C");
+    }
+
+    #[test]
+    fn keeps_trailing_zeros() {
+        assert_eq!(format_temp(45.0, TemperatureUnit::Celsius, 2), "45.00°C");
+        assert_eq!(format_temp(0.0, TemperatureUnit::Kelvin, 0), "273K");
+    }
+
+    #[test]
+    fn broken_readings_show_no_value() {
+        for celsius in [f32::NAN, f32::INFINITY, f32::NEG_INFINITY] {
+            assert_eq!(format_temp(celsius, TemperatureUnit::Celsius, 0), NO_VALUE);
+            assert_eq!(format_number(celsius, TemperatureUnit::Fahrenheit, 1), None);
+        }
+        assert!(!reaches_threshold(
+            f32::NAN,
+            90.0,
+            TemperatureUnit::Celsius,
+            0
+        ));
+    }
+
+    #[test]
+    fn thresholds_compare_shown_values() {
+        // Shown as "90°C", so it's critical
+        assert!(reaches_threshold(89.6, 90.0, TemperatureUnit::Celsius, 0));
+        assert!(!reaches_threshold(89.6, 90.0, TemperatureUnit::Celsius, 1));
+        assert!(!reaches_threshold(89.4, 90.0, TemperatureUnit::Celsius, 0));
+        assert!(reaches_threshold(
+            90.0,
+            90.0,
+            TemperatureUnit::Fahrenheit,
+            2
+        ));
+    }
+
+    #[test]
+    fn shown_values_agree_with_the_thresholds() {
+        for unit in TemperatureUnit::ALL {
+            for (celsius, threshold) in [(94.6, 95.0), (94.4, 95.0), (95.0, 95.0), (89.9, 90.2)] {
+                assert_eq!(
+                    shown_celsius(celsius, unit, 0) >= shown_celsius(threshold, unit, 0),
+                    reaches_threshold(celsius, threshold, unit, 0)
+                );
+            }
+        }
+        assert_eq!(shown_celsius(94.6, TemperatureUnit::Celsius, 0), 95.0);
+        assert_eq!(shown_celsius(94.64, TemperatureUnit::Celsius, 1), 94.6);
+    }
+
+    #[test]
+    fn secondary_unit_differs_from_primary() {
+        for unit in TemperatureUnit::ALL {
+            assert_ne!(secondary_unit(unit), unit);
+        }
+        assert_eq!(
+            secondary_unit(TemperatureUnit::Fahrenheit),
+            TemperatureUnit::Celsius
+        );
+    }
+}
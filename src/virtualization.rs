@@ -0,0 +1,74 @@
+// Whether the applet runs in a virtual machine. Hypervisors rarely pass any temperature
+// sensor through, so a VM without sensors is expected rather than a setup problem.
+
+use std::fs;
+use std::path::Path;
+
+pub(crate) const DMI_PRODUCT_NAME: &str = "/sys/class/dmi/id/product_name";
+pub(crate) const CPUINFO: &str = "/proc/cpuinfo";
+
+// Substrings of the DMI product name of the common hypervisors, lowercase
+const VIRTUAL_PRODUCTS: &[&str] = &[
+    "virtualbox",
+    "vmware",
+    "kvm",
+    "qemu",
+    "bochs",
+    // Hyper-V
+    "virtual machine",
+    // Xen
+    "hvm domu",
+    "parallels",
+    "bhyve",
+    // QEMU's default machine types, "Standard PC (Q35 + ICH9, 2009)"
+    "standard pc",
+];
+
+fn is_virtual_product(product: &str) -> bool {
+    let product = product.trim().to_lowercase();
+    VIRTUAL_PRODUCTS.iter().any(|name| product.contains(name))
+}
+
+// The `hypervisor` CPU flag, set by every hypervisor that follows the x86 convention
+fn has_hypervisor_flag(cpuinfo: &str) -> bool {
+    cpuinfo
+        .lines()
+        .filter(|line| line.starts_with("flags"))
+        .filter_map(|line| line.split_once(':'))
+        .any(|(_, flags)| flags.split_whitespace().any(|flag| flag == "hypervisor"))
+}
+
+// Files that can't be read (no DMI on ARM boards, a sandbox) don't count as a hint
+pub(crate) fn detect(product_name: &Path, cpuinfo: &Path) -> bool {
+    fs::read_to_string(product_name).is_ok_and(|product| is_virtual_product(&product))
+        || fs::read_to_string(cpuinfo).is_ok_and(|cpuinfo| has_hypervisor_flag(&cpuinfo))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognizes_virtual_products() {
+        assert!(is_virtual_product("VirtualBox\n"));
+        assert!(is_virtual_product("VMware Virtual Platform\n"));
+        assert!(is_virtual_product("Standard PC (Q35 + ICH9, 2009)\n"));
+        assert!(is_virtual_product("Virtual Machine\n"));
+        assert!(!is_virtual_product("ThinkPad T14 Gen 3\n"));
+        assert!(!is_virtual_product("\n"));
+    }
+
+    #[test]
+    fn finds_the_hypervisor_flag() {
+        let guest = "processor\t: 0\nflags\t\t: fpu vme de pse hypervisor lahf_lm\n";
+        assert!(has_hypervisor_flag(guest));
+        let host = "processor\t: 0\nflags\t\t: fpu vme de pse lahf_lm\nmodel name\t: hypervisor\n";
+        assert!(!has_hypervisor_flag(host));
+    }
+
+    #[test]
+    fn missing_files_are_no_hint() {
+        let missing = Path::new("/nonexistent/product_name");
+        assert!(!detect(missing, missing));
+    }
+}
@@ -1,35 +1,266 @@
 // Mandatory COSMIC imports
 use cosmic::app::Core;
-use cosmic::applet::cosmic_panel_config::PanelAnchor;
+use cosmic::applet::cosmic_panel_config::{CosmicPanelConfig, PanelAnchor, PanelSize};
+use cosmic::cctk::wayland_protocols::xdg::shell::client::xdg_positioner::{Anchor, Gravity};
 use cosmic::cosmic_config::CosmicConfigEntry;
 use cosmic::iced::futures::SinkExt;
-use cosmic::iced::Rectangle;
+use cosmic::iced::keyboard::{self, key::Named, Key};
+use cosmic::iced::mouse::ScrollDelta;
+use cosmic::iced::widget::mouse_area;
 use cosmic::iced::{
+    event,
     platform_specific::shell::commands::popup::{destroy_popup, get_popup},
-    widget::{column, horizontal_space, row, vertical_space},
+    widget::{
+        canvas, column, focus_next, focus_previous, horizontal_space, progress_bar, row,
+        vertical_space,
+    },
     window::Id,
-    Alignment, Length, Subscription, Task,
+    Alignment, Event, Length, Subscription, Task,
 };
+use cosmic::iced::{Color, Limits, Rectangle};
 use cosmic::iced_futures::stream;
 use cosmic::iced_runtime::core::window;
 use cosmic::widget::rectangle_tracker::{rectangle_tracker_subscription, RectangleUpdate};
 use cosmic::Element;
 use once_cell::sync::Lazy;
+use std::path::{Path, PathBuf};
+#[cfg(debug_assertions)]
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Instant;
 
 // Widgets we're going to use
 use cosmic::widget::Id as WidgetID;
 use cosmic::widget::{
-    autosize, button, container, settings, text_input, toggler, RectangleTracker,
+    autosize, button, container, dropdown, icon, scrollable, segmented_button, segmented_control,
+    settings, text, text_input, toggler, tooltip, RectangleTracker,
 };
 use tokio::{sync::watch, time};
 
-use crate::config::CPUTempAppletConfig;
-use crate::sysinfo_utils::get_temp;
+use crate::alert::{FlashAlert, SustainedAlert, ThresholdAlert};
+use crate::applet_state::{AppletState, PeriodField, ThresholdField};
+use crate::config::{
+    instance_config_id, CPUTempAppletConfig, CoreAggregation, DisplayMode, GraphRange, PanelStyle,
+    SensorBackend, TemperatureUnit, ThresholdIndication, MAX_DECIMAL_PLACES,
+};
+use crate::config_writer::ConfigWriter;
+use crate::cpu_load::CpuLoad;
+use crate::critical_action;
+use crate::dbus;
+use crate::fans::{self, Fan};
+use crate::fl;
+use crate::graph::HistoryGraph;
+use crate::history::TempHistory;
+use crate::hwmon;
+use crate::locale;
+use crate::metrics::{self, Metrics};
+use crate::monitor;
+use crate::motion::{self, Motion};
+use crate::mqtt;
+use crate::notifications;
+use crate::panel_format;
+use crate::persist::{self, Snapshot};
+use crate::power;
+use crate::quiet_hours::{self, MutedAlerts, QuietHours};
+use crate::rapl::RaplMeter;
+use crate::refresh_period::{
+    DisplayCadence, PeriodInput, MAX_REFRESH_PERIOD_MS, MIN_REFRESH_PERIOD_MS,
+};
+use crate::sample_log::{self, Sample};
+use crate::session::{self, SessionEvent};
+use crate::settings_app;
+use crate::socket;
+use crate::sound;
+use crate::stats::{TempStats, STATS_WINDOWS};
+use crate::sysinfo_utils::{
+    hottest_core, new_provider, probe_access, read_sensors, sensor_name, sensor_names, strip_chip,
+    AccessProblem, CpuSensor, ReadOptions, SensorReading, TempProvider, TempReading,
+};
+use crate::throttle::{Throttle, ThrottleMonitor};
+use crate::timing::{self, RecentDurations};
+use crate::units;
+use crate::virtualization;
+
+// Config of the panel the applet is in is `<PANEL_CONFIG_PREFIX>.<panel name>`
+const PANEL_CONFIG_PREFIX: &str = "com.system76.CosmicPanel";
 
 // Every COSMIC Application and Applet MUST have an ID
-const ID: &str = "com.gr3q.CosmicExtAppletCPUTemperature";
+pub(crate) const ID: &str = "com.gr3q.CosmicExtAppletCPUTemperature";
+
+// Installed with the applet (data/usr/share/icons), shown next to the temperature when enabled
+const THERMOMETER_ICON: &str = "thermometer-symbolic";
+const THERMOMETER_WARM_ICON: &str = "thermometer-warm-symbolic";
+const THERMOMETER_HOT_ICON: &str = "thermometer-hot-symbolic";
+const REFRESH_ICON: &str = "view-refresh-symbolic";
+const PIN_ICON: &str = "view-pin-symbolic";
+// A pinned popup that the compositor closes again this soon after it was reopened can't be
+// kept (its output went away or similar), it's unpinned then instead of reopening forever
+const PINNED_REOPEN_GUARD: time::Duration = time::Duration::from_secs(1);
+
+const GRAPH_HEIGHT: f32 = 80.0;
+// The per-core bars start at this, so the differences between cores are visible
+const CORE_BAR_MIN_CELSIUS: f32 = 30.0;
+const CORE_BAR_HEIGHT: f32 = 6.0;
+const CORE_LABEL_WIDTH: f32 = 64.0;
+
+// Refresh periods without a CPU value before the panel shows a warning
+const STALE_REFRESH_PERIODS: u32 = 3;
+
+// The hidden panel text comes back this far above `hide_below_celsius`, so it doesn't
+// flicker while the temperature hovers around it
+const HIDE_HYSTERESIS_CELSIUS: f32 = 2.0;
+
+// Readings dropped after waking up from suspend, fewer than `STALE_REFRESH_PERIODS`
+const RESUME_SKIPPED_SAMPLES: u8 = 2;
+
+// How long a failed system monitor launch is reported in the popup
+const MONITOR_ERROR_DURATION: time::Duration = time::Duration::from_secs(5);
+// How long the copy button says "Copied"
+const COPIED_DURATION: time::Duration = time::Duration::from_secs(2);
+
+// Touchpad scrolling distance that switches to the next sensor
+const SCROLL_STEP_PIXELS: f32 = 40.0;
+
+// Space between the panel item and the popup
+const POPUP_GAP: i32 = 4;
+
+// Size limits of the popup, it's sized to fit its content in between
+const POPUP_MIN_WIDTH: f32 = 300.0;
+const POPUP_MAX_WIDTH: f32 = 372.0;
+const POPUP_MIN_HEIGHT: f32 = 100.0;
+const POPUP_MAX_HEIGHT: f32 = 1080.0;
+
+// How long the refresh period input has to stay unchanged before it's saved
+const PERIOD_COMMIT_DELAY: time::Duration = time::Duration::from_millis(500);
+
+// Failed writes in a row before the popup says that settings aren't saved
+const CONFIG_WRITE_FAILURES_SHOWN: u32 = 3;
+// How long quitting waits for the sample log to be written
+const SAMPLE_LOG_FLUSH_TIMEOUT: time::Duration = time::Duration::from_secs(1);
+
+// Same width as a digit, unlike a regular space it isn't trimmed or collapsed by the layout
+const FIGURE_SPACE: char = '\u{2007}';
+// In front of the panel text while the updates are paused
+const PAUSED_MARK: &str = "\u{23F8} ";
+
+// Same order as `TemperatureUnit::ALL`
+pub(crate) static UNIT_OPTIONS: Lazy<Vec<String>> =
+    Lazy::new(|| vec![fl!("celsius"), fl!("fahrenheit"), fl!("kelvin")]);
+// Same order as `DisplayMode::ALL`
+pub(crate) static DISPLAY_MODE_OPTIONS: Lazy<Vec<String>> = Lazy::new(|| {
+    vec![
+        fl!("display-cpu"),
+        fl!("display-gpu"),
+        fl!("display-both"),
+        fl!("display-hottest"),
+        fl!("display-headroom"),
+    ]
+});
+// Same order as `ThresholdIndication::ALL`
+pub(crate) static THRESHOLD_INDICATION_OPTIONS: Lazy<Vec<String>> = Lazy::new(|| {
+    vec![
+        fl!("indication-color"),
+        fl!("indication-icon"),
+        fl!("indication-both"),
+    ]
+});
+// Same order as `PanelStyle::ALL`
+pub(crate) static PANEL_STYLE_OPTIONS: Lazy<Vec<String>> =
+    Lazy::new(|| vec![fl!("panel-style-text"), fl!("panel-style-icon-only")]);
+// Same order as `GraphRange::ALL`
+pub(crate) static GRAPH_RANGE_OPTIONS: Lazy<Vec<String>> = Lazy::new(|| {
+    vec![
+        fl!("graph-range-5m"),
+        fl!("graph-range-30m"),
+        fl!("graph-range-2h"),
+    ]
+});
+// Panel icons of each `TempLevel`. The theme's icon is used when it has one, otherwise the
+// copy built into the binary, so it also works when the icons weren't installed. Looked up
+// once, finding a themed icon goes through the disk.
+static LEVEL_ICONS: Lazy<[icon::Handle; 3]> = Lazy::new(|| {
+    [
+        (
+            THERMOMETER_ICON,
+            include_bytes!("../data/usr/share/icons/thermometer-symbolic.svg").as_slice(),
+        ),
+        (
+            THERMOMETER_WARM_ICON,
+            include_bytes!("../data/usr/share/icons/thermometer-warm-symbolic.svg").as_slice(),
+        ),
+        (
+            THERMOMETER_HOT_ICON,
+            include_bytes!("../data/usr/share/icons/thermometer-hot-symbolic.svg").as_slice(),
+        ),
+    ]
+    .map(|(name, bundled)| {
+        let themed = icon::from_name(name).symbolic(true);
+        if themed.clone().path().is_some() {
+            themed.handle()
+        } else {
+            let mut handle = icon::from_svg_bytes(bundled);
+            handle.symbolic = true;
+            handle
+        }
+    })
+});
+// Same order as `CoreAggregation::ALL`
+pub(crate) static CORE_AGGREGATION_OPTIONS: Lazy<Vec<String>> =
+    Lazy::new(|| vec![fl!("maximum"), fl!("average"), fl!("median")]);
+// Same order as `SensorBackend::ALL`, these are technical names that aren't translated
+pub(crate) const SENSOR_BACKEND_OPTIONS: &[&str] = &["sysinfo", "hwmon (sysfs)"];
+// Refresh period multipliers offered for battery mode
+pub(crate) const BATTERY_MULTIPLIERS: [u32; 4] = [2, 3, 5, 10];
+// Same order as `BATTERY_MULTIPLIERS`
+pub(crate) const BATTERY_MULTIPLIER_OPTIONS: &[&str] = &["2×", "3×", "5×", "10×"];
+// Weight of a new reading, lower is smoother
+pub(crate) const SMOOTHING_FACTORS: [Option<f32>; 4] = [None, Some(0.5), Some(0.3), Some(0.1)];
+// Same order as `SMOOTHING_FACTORS`
+pub(crate) static SMOOTHING_OPTIONS: Lazy<Vec<String>> = Lazy::new(|| {
+    vec![
+        fl!("smoothing-off"),
+        fl!("smoothing-light"),
+        fl!("smoothing-medium"),
+        fl!("smoothing-strong"),
+    ]
+});
+// Coarse panel values only change when something happens
+pub(crate) const ROUNDING_STEPS: [Option<u8>; 4] = [None, Some(2), Some(5), Some(10)];
+// Same order as `ROUNDING_STEPS`
+pub(crate) static ROUNDING_STEP_OPTIONS: Lazy<Vec<String>> = Lazy::new(|| {
+    ROUNDING_STEPS
+        .iter()
+        .map(|step| match step {
+            Some(step) => format!("{step}°"),
+            None => fl!("rounding-off"),
+        })
+        .collect()
+});
+// Index is the number of decimal places
+pub(crate) const DECIMAL_PLACES_OPTIONS: &[&str] = &["0", "1", "2"];
 
 static AUTOSIZE_MAIN_ID: Lazy<WidgetID> = Lazy::new(|| WidgetID::new("autosize-main"));
+// Debug builds count how often the panel text is formatted, how many of those gave the text
+// that was shown already, and how often the panel is drawn. The view still runs after every
+// update, an unchanged text only spares the stacking in `update_panel_text`.
+#[cfg(debug_assertions)]
+static PANEL_TEXT_FORMATS: AtomicU64 = AtomicU64::new(0);
+#[cfg(debug_assertions)]
+static PANEL_TEXT_UNCHANGED: AtomicU64 = AtomicU64::new(0);
+#[cfg(debug_assertions)]
+static PANEL_VIEWS: AtomicU64 = AtomicU64::new(0);
+
+// Focused when the popup opens
+static PERIOD_INPUT_ID: Lazy<WidgetID> = Lazy::new(|| WidgetID::new("period-input"));
+
+// Command line arguments of the applet, see `main`
+#[derive(Clone, Debug, Default)]
+pub struct Flags {
+    // Instance name, see `instance_config_id`
+    pub instance: Option<String>,
+    // Label of the sensor to show, whatever is configured
+    pub sensor: Option<String>,
+}
 
 /*
 *  Every COSMIC model must be a struct data type.
@@ -44,28 +275,1613 @@ static AUTOSIZE_MAIN_ID: Lazy<WidgetID> = Lazy::new(|| WidgetID::new("autosize-m
 #[derive(Default)]
 pub struct Window {
     core: Core,
+    // Config ID of this instance, see `instance_config_id`
+    config_id: &'static str,
+    // Passed on to the settings window, so it edits the same config
+    instance: Option<String>,
+    // `--sensor`, read instead of the configured sensor by this process only
+    sensor_override: Option<String>,
     popup: Option<Id>,
+    // Kept open when the focus moves elsewhere, until it's unpinned or the panel button is
+    // clicked. Not saved, every start begins unpinned.
+    popup_pinned: bool,
+    // When a pinned popup was last opened again after the compositor closed it
+    popup_reopened_at: Option<Instant>,
+    // The right-click menu, never open at the same time as `popup`
+    context_menu: Option<Id>,
     rectangle_tracker: Option<RectangleTracker<u32>>,
     rectangle: Rectangle,
-    temp: Option<f32>,
+    // Temperatures and the inputs of the popup
+    state: AppletState,
+    // Readings still to drop after a resume
+    resume_samples_to_skip: u8,
+    // Set while the CPU sensors exist but fail to report, `temp` is stale then
+    read_error: Option<String>,
+    // Found by `probe_access` at startup and on every manual refresh
+    access_problem: Option<AccessProblem>,
+    // A hypervisor was detected, at startup and on every manual refresh like `access_problem`
+    virtual_machine: bool,
+    // The CPU value before the calibration offset, for the diagnostics
+    cpu_raw: Option<f32>,
+    gpu_temp: Option<f32>,
+    // Label and temperature of every AMD CCD
+    ccd_temps: Vec<(String, f32)>,
+    // Label and temperature of every core, ordered by core number
+    core_temps: Vec<(String, f32)>,
+    // Label of the hottest of `core_temps`
+    hot_core: Option<String>,
+    // Only read while the popup is open
+    drive_temps: Vec<(String, f32)>,
+    // Label of the matched GPU component
+    gpu_sensor: Option<String>,
+    // Where the last CPU value came from, kept while readings fail
+    cpu_sensor: Option<CpuSensor>,
+    // Time of the last CPU value (or of the start), used to notice stuck sensors
+    last_reading_at: Option<Instant>,
+    // Touchpad scrolling over the panel item that didn't add up to a full step yet
+    scroll_pixels: f32,
+    // Label and temperature of the hottest sensor, only read in that display mode
+    hottest_sensor: Option<(String, f32)>,
+    sensors: Arc<Mutex<Box<dyn TempProvider>>>,
+    // Kept between ticks, utilization needs two samples
+    cpu_load: Arc<Mutex<CpuLoad>>,
+    rapl: Arc<Mutex<RaplMeter>>,
+    // Package power in watts, only while `show_power` is on
+    power: Option<f32>,
+    // Why there is no power reading, shown in the diagnostics
+    power_error: Option<String>,
+    throttle_monitor: Arc<Mutex<ThrottleMonitor>>,
+    // Only while `show_throttling` is on
+    throttle: Option<Throttle>,
+    // Empty unless `show_fan` is on
+    fans: Vec<Fan>,
+    // Percent, `None` until the second sample
+    load: Option<f32>,
+    // What `view` shows, see `update_panel_text`
+    panel_text: String,
+    // `panel_text` on several lines, for narrow vertical panels
+    stacked_panel_text: String,
+    // Length in characters of a single line text that was too wide for a vertical panel,
+    // and the panel breadth it was measured at
+    vertical_overflow: Option<(usize, f32)>,
+    // Read by the metrics server, only updated while it's enabled
+    metrics: Arc<Mutex<Metrics>>,
+    metrics_port_string: String,
+    // Prevents piling up reads when the sensors are slower than the refresh period
+    reading_in_flight: bool,
+    sensor_labels: Vec<String>,
+    // (label, driver name) of the sensors whose driver is known
+    sensor_chips: Vec<(String, String)>,
+    // Another sensor on the CPU's package, on AMD APUs amdgpu's next to k10temp
+    cpu_alternative: Option<String>,
+    // The selected sensor went away, the automatic detection is used until it's back
+    selected_missing: bool,
+    history: TempHistory,
+    // Segments of the graph's range above it, kept in sync with `config.graph_range`
+    graph_ranges: segmented_button::SingleSelectModel,
+    // Last time the history was written to disk, only used with `persist_history`
+    history_saved_at: Option<Instant>,
+    // Set by the sample log writer once it's running
+    sample_log: Option<tokio::sync::mpsc::UnboundedSender<sample_log::Command>>,
+    // Logging stays off after an error until the log settings change
+    sample_log_error: Option<String>,
+    log_path_string: String,
+    log_max_size_string: String,
+    // Set by the MQTT client once it's running
+    mqtt: Option<mqtt::Publisher>,
+    // Publishing stays off after an error until the MQTT settings change
+    mqtt_error: Option<String>,
+    // Samples since the client started, every `mqtt_interval_multiplier`th is published
+    mqtt_samples: u32,
+    // Rolling CPU statistics of `stats_sensor`, cleared when another sensor is selected
+    stats: TempStats,
+    stats_sensor: Option<String>,
+    // `None` if the D-Bus service isn't running
+    dbus: Option<zbus::Connection>,
+    critical_alert: ThresholdAlert,
+    muted_alerts: MutedAlerts,
+    flash_alert: FlashAlert,
+    // The desktop's reduced motion setting, only watched while flashing is enabled
+    reduced_motion: bool,
+    critical_action_alert: SustainedAlert,
+    // Why the last critical action failed, cleared when it succeeds
+    critical_action_error: Option<String>,
+    // Why the last alert or test sound didn't play
+    sound_error: Option<String>,
+    // Why the system monitor couldn't be started, shown for a few seconds
+    monitor_error: Option<String>,
+    monitor_error_generation: u64,
+    // The readings were just copied, see `COPIED_DURATION`
+    copied: bool,
+    copied_generation: u64,
+    // Every sensor of the last reading, for copying
+    sensor_temps: Vec<(String, f32)>,
+    // Diagnostic list of every sensor, only read while it's expanded in the open popup
+    show_all_sensors: bool,
+    all_sensors: Vec<(String, String, Option<f32>)>,
+    // Shown in the diagnostics section, for bug reports
+    show_diagnostics: bool,
+    component_count: usize,
+    cpu_priority: Option<usize>,
+    read_duration: Option<time::Duration>,
+    // Of every read, and how late every tick arrived, to tell slow sensors from a busy applet
+    read_durations: RecentDurations,
+    tick_delays: RecentDurations,
+    // Only saved on submit or when the input loses focus, the alert could otherwise run a
+    // half-typed command
+    critical_action_string: String,
+    critical_action_delay_string: String,
     refresh_period: watch::Sender<u64>,
-    period_string: String,
+    // Latest document for the sensor socket, only updated while it's enabled
+    sensor_export: watch::Sender<String>,
+    // Only known while battery mode is enabled
+    on_battery: bool,
+    // Polling stops while this is `true`, see `update_paused`
+    paused: watch::Sender<bool>,
+    // Locked or sleeping session
+    session_paused: bool,
+    // Paused from the right-click menu, until resumed there or the applet restarts
+    updates_paused: bool,
+    // Which samples update the shown temperature
+    display_cadence: DisplayCadence,
+    quiet_hours_start_string: String,
+    quiet_hours_end_string: String,
+    // Below `hide_below_celsius`, the panel only shows the icon
+    text_hidden: bool,
+    // First entry is always the automatic detection, the rest are component labels
+    sensor_options: Vec<String>,
+    // What the dropdown shows for `sensor_options`, with the driver when it's known
+    sensor_option_names: Vec<String>,
     config: CPUTempAppletConfig,
+    config_writer: ConfigWriter,
+}
+
+// Entries of the right-click menu on the panel button
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MenuAction {
+    ToggleUnit,
+    ResetPeak,
+    OpenSettings,
+    PauseUpdates,
+}
+
+#[derive(Clone, Debug)]
+pub enum Message {
+    TogglePopup,     // Mandatory for open and close the applet
+    PopupClosed(Id), // Mandatory for the applet to know if it's been closed
+    ToggleContextMenu,
+    MenuAction(MenuAction),
+    TogglePause,
+    Unit(usize), // Our custom message to update the unit in the config
+    TogglePin,
+    Rectangle(RectangleUpdate<u32>),
+    PeriodString(PeriodField, String),
+    SubmitPeriod(PeriodField),
+    CommitPeriod(PeriodField, u64),
+    WarningThreshold(String),
+    CriticalThreshold(String),
+    // Overrides for the sensor that gives the CPU value
+    SensorWarningThreshold(String),
+    SensorCriticalThreshold(String),
+    HideBelow(String),
+    Offset(String),
+    SensorOffset(String),
+    CriticalNotification(bool),
+    FlashOnCritical(bool),
+    PersistHistory(bool),
+    LogToFile(bool),
+    LogPathString(String),
+    SubmitLogPath,
+    LogMaxSize(String),
+    SubmitLogMaxSize,
+    SampleLog(sample_log::Event),
+    AlertSound(String),
+    QuietHoursStart(String),
+    QuietHoursEnd(String),
+    TestSound,
+    SoundFinished(Result<(), String>),
+    CriticalAction(String),
+    SubmitCriticalAction,
+    CriticalActionDelay(String),
+    CriticalActionFinished(Result<(), String>),
+    LaunchMonitor,
+    MonitorCommand(String),
+    // Hides the launch error again, ignored if a newer error is shown
+    MonitorErrorExpired(u64),
+    CopyReadings,
+    OpenSettings,
+    // Reads the sensors outside of the timer, which keeps its own schedule
+    RefreshNow,
+    // Ignored if the readings were copied again since
+    CopiedExpired(u64),
+    ShowIcon(bool),
+    ThresholdIndication(usize),
+    PanelStyle(usize),
+    ShowBothUnits(bool),
+    ShowLoad(bool),
+    LoadWithoutSensors(bool),
+    ShowFan(bool),
+    ShowPower(bool),
+    ShowThrottling(bool),
+    ShowHotCore(bool),
+    GraphRange(segmented_button::Entity),
+    GraphFixedScale(bool),
+    PowerInPanel(bool),
+    MetricsEnabled(bool),
+    MqttEnabled(bool),
+    Mqtt(mqtt::Event),
+    SocketEnabled(bool),
+    MetricsPort(String),
+    SubmitMetricsPort,
+    FixedWidth(bool),
+    PanelFormat(String),
+    PanelPrefix(String),
+    PanelSuffix(String),
+    DisplayMode(usize),
+    DecimalPlaces(usize),
+    Smoothing(usize),
+    RoundingStep(usize),
+    CoreAggregation(usize),
+    SensorBackend(usize),
+    HottestCcd(bool),
+    PreferTctl(bool),
+    ShowDriveTemps(bool),
+    ToggleAllSensors,
+    ToggleDiagnostics,
+    CopyDiagnostics,
+    SelectSensor(usize),
+    // Adds the label to the blacklist, or removes a blacklist entry again
+    IgnoreSensor(String),
+    UnignoreSensor(String),
+    ResetPeak,
+    // Mouse wheel or touchpad over the panel item
+    PanelScroll(ScrollDelta),
+    // Tab and Shift+Tab in the popup
+    FocusNext,
+    FocusPrevious,
+    // When it was due, it can arrive late while the applet is busy
+    Tick(Instant),
+    TempUpdated(SensorReading),
+    DbusStarted(Option<zbus::Connection>),
+    AccessProbed(Option<AccessProblem>),
+    VirtualizationDetected(bool),
+    Paused(bool),
+    Resumed,
+    // A hwmon chip was plugged in or removed
+    SensorsChanged,
+    OnBattery(bool),
+    BatterySaver(bool),
+    ReducedMotion(bool),
+    DisableAnimations(bool),
+    BatteryMultiplier(usize),
+    ConfigChanged(CPUTempAppletConfig),
+    // The panel was resized or moved to another edge
+    PanelChanged(PanelSize, PanelAnchor),
+    // SIGTERM and the like, saves what needs saving and quits
+    Exit,
+    // Sent by `ConfigWriter::subscription` with the generation it was started for
+    WriteConfig(u64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum TempLevel {
+    Normal,
+    Warning,
+    Critical,
+}
+
+// Thresholds are in Celsius, so this has to be called with the unconverted value. The
+// comparison uses the values as shown in `unit` with `decimals` decimals.
+fn get_temp_level(
+    celsius: f32,
+    warning_celsius: f32,
+    critical_celsius: f32,
+    unit: TemperatureUnit,
+    decimals: usize,
+) -> TempLevel {
+    if units::reaches_threshold(celsius, critical_celsius, unit, decimals) {
+        TempLevel::Critical
+    } else if units::reaches_threshold(celsius, warning_celsius, unit, decimals) {
+        TempLevel::Warning
+    } else {
+        TempLevel::Normal
+    }
+}
+
+// Digits before the decimal point that are always reserved in fixed width mode
+fn reserved_integer_digits(unit: TemperatureUnit, critical_celsius: f32) -> usize {
+    match unit {
+        // 100°F is a normal load temperature
+        TemperatureUnit::Fahrenheit | TemperatureUnit::Kelvin => 3,
+        // Only CPUs that are allowed to run that hot reach 100°C
+        TemperatureUnit::Celsius if critical_celsius >= 100.0 => 3,
+        TemperatureUnit::Celsius => 2,
+    }
+}
+
+// One segment per `GraphRange`, with `active` selected
+fn graph_range_model(active: GraphRange) -> segmented_button::SingleSelectModel {
+    let mut model = segmented_button::SingleSelectModel::default();
+    for (range, label) in GraphRange::ALL.iter().zip(GRAPH_RANGE_OPTIONS.iter()) {
+        let entity = model.insert().text(label.clone()).data(*range).id();
+        if *range == active {
+            model.activate(entity);
+        }
+    }
+    model
+}
+
+// Left pads a formatted number with figure spaces up to the reserved width
+fn pad_number(number: String, integer_digits: usize, decimal_places: usize) -> String {
+    let width = if decimal_places > 0 {
+        integer_digits + 1 + decimal_places
+    } else {
+        integer_digits
+    };
+
+    let padding = width.saturating_sub(number.chars().count());
+    std::iter::repeat(FIGURE_SPACE)
+        .take(padding)
+        .chain(number.chars())
+        .collect()
+}
+
+// Which edge of the panel item the popup hangs from, the direction it grows in and the
+// gap between the two, so it always opens away from the screen edge the panel is on
+fn popup_placement(panel: PanelAnchor) -> (Anchor, Gravity, (i32, i32)) {
+    match panel {
+        PanelAnchor::Top => (Anchor::Bottom, Gravity::Bottom, (0, POPUP_GAP)),
+        PanelAnchor::Bottom => (Anchor::Top, Gravity::Top, (0, -POPUP_GAP)),
+        PanelAnchor::Left => (Anchor::Right, Gravity::Right, (POPUP_GAP, 0)),
+        PanelAnchor::Right => (Anchor::Left, Gravity::Left, (-POPUP_GAP, 0)),
+    }
+}
+
+// The panel item's rectangle within the applet surface. Until the rectangle tracker
+// reported one, the whole surface is used, so the popup is still placed next to the panel.
+fn popup_anchor_rect(
+    tracked: Rectangle,
+    suggested_size: (u16, u16),
+    padding: u16,
+) -> Rectangle<i32> {
+    let Rectangle {
+        x,
+        y,
+        width,
+        height,
+    } = tracked;
+    if width >= 1.0 && height >= 1.0 {
+        return Rectangle {
+            x: x as i32,
+            y: y as i32,
+            width: width as i32,
+            height: height as i32,
+        };
+    }
+
+    let (width, height) = suggested_size;
+    Rectangle {
+        x: 0,
+        y: 0,
+        width: (width + 2 * padding) as i32,
+        height: (height + 2 * padding) as i32,
+    }
 }
 
-#[derive(Clone, Debug)]
-pub enum Message {
-    TogglePopup,      // Mandatory for open and close the applet
-    PopupClosed(Id),  // Mandatory for the applet to know if it's been closed
-    Fahrenheit(bool), // Our custom message to update the isEnabled field on the model
-    Rectangle(RectangleUpdate<u32>),
-    PeriodString(String),
-    Tick,
-    ConfigChanged(CPUTempAppletConfig),
-}
+// The message shown under a period input
+fn period_hint(input: PeriodInput) -> Option<String> {
+    match input {
+        PeriodInput::Valid(_) => None,
+        PeriodInput::TooShort => Some(fl!(
+            "refresh-interval-too-short",
+            min = MIN_REFRESH_PERIOD_MS
+        )),
+        PeriodInput::TooLong => Some(fl!(
+            "refresh-interval-too-long",
+            max = MAX_REFRESH_PERIOD_MS
+        )),
+        PeriodInput::Invalid => Some(fl!("refresh-interval-invalid")),
+    }
+}
+
+// Keys handled while the popup is open. Escape closes it, Tab and Shift+Tab move the focus
+// through its controls in the order they're shown. Keys a focused control used are left alone,
+// e.g. the first Escape only leaves a text input.
+fn popup_key(event: Event, status: event::Status, _window: window::Id) -> Option<Message> {
+    let Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }) = event else {
+        return None;
+    };
+    if status == event::Status::Captured {
+        return None;
+    }
+
+    match key {
+        Key::Named(Named::Escape) => Some(Message::TogglePopup),
+        Key::Named(Named::Tab) if modifiers.shift() => Some(Message::FocusPrevious),
+        Key::Named(Named::Tab) => Some(Message::FocusNext),
+        _ => None,
+    }
+}
+
+// Heading that expands and collapses the section below it
+fn section_toggle<'a>(title: String, expanded: bool, on_press: Message) -> Element<'a, Message> {
+    button::custom(
+        row![
+            text::heading(title),
+            horizontal_space(),
+            icon::from_name(if expanded {
+                "go-up-symbolic"
+            } else {
+                "go-down-symbolic"
+            })
+            .size(16)
+            .icon(),
+        ]
+        .align_y(Alignment::Center),
+    )
+    .class(cosmic::theme::Button::Text)
+    .on_press(on_press)
+    .into()
+}
+
+// "34%", or "–%" until there were two samples
+fn format_load(load: Option<f32>) -> String {
+    match load {
+        Some(load) => format!("{load:.0}%"),
+        None => "–%".to_string(),
+    }
+}
+
+fn format_power(watts: f32) -> String {
+    format!("{watts:.0}W")
+}
+
+fn format_fan(fan: Option<&Fan>) -> String {
+    match fan {
+        Some(fan) => fl!("fan-speed", rpm = fan.rpm),
+        None => fl!("fan-speed", rpm = "–"),
+    }
+}
+
+// "Tctl 76.5°C, Tccd1 71.2°C, peak 92.1°C". Always Celsius with one decimal, so bug reports
+// can be compared regardless of the unit the reporter uses.
+fn format_readings(temps: &[(String, f32)], peak: Option<f32>) -> String {
+    let celsius = |temp: f32| units::format_temp(temp, TemperatureUnit::Celsius, 1);
+    let mut parts: Vec<String> = temps
+        .iter()
+        .map(|(label, temp)| format!("{label} {}", celsius(*temp)))
+        .collect();
+    if let Some(peak) = peak {
+        parts.push(format!("peak {}", celsius(peak)));
+    }
+
+    parts.join(", ")
+}
+
+fn describe_cpu_sensor(sensor: &CpuSensor) -> String {
+    match sensor {
+        CpuSensor::Component {
+            label,
+            chip: Some(chip),
+        } => fl!(
+            "source-component-chip",
+            label = strip_chip(label, chip),
+            chip = chip.as_str()
+        ),
+        CpuSensor::Component { label, chip: None } => {
+            fl!("source-component", label = label.as_str())
+        }
+        CpuSensor::Cores { aggregation, count } => {
+            let index = CoreAggregation::ALL
+                .iter()
+                .position(|other| other == aggregation)
+                .unwrap_or_default();
+            fl!(
+                "source-cores",
+                aggregation = CORE_AGGREGATION_OPTIONS[index].as_str(),
+                count = *count
+            )
+        }
+        CpuSensor::ThermalZone => fl!("source-thermal-zone"),
+    }
+}
+
+impl Window {
+    fn read_options(&self) -> ReadOptions {
+        ReadOptions {
+            selected_sensor: self.selected_sensor().cloned(),
+            core_aggregation: self.config.core_aggregation,
+            hottest_ccd: self.config.hottest_ccd,
+            prefer_tctl: self.config.prefer_tctl,
+            drive_temps: self.config.show_drive_temps && self.popup.is_some(),
+            hottest_any: self.config.display_mode == DisplayMode::HottestAny,
+            all_components: self.show_all_sensors && self.popup.is_some(),
+            components: self.config.socket_enabled,
+            fans: self.config.show_fan,
+            power: self.config.show_power,
+            throttle: self.config.show_throttling,
+            blacklist: self.config.sensor_blacklist.clone(),
+            max_plausible_celsius: Some(self.config.max_plausible_celsius),
+            offset_celsius: self.config.offset_celsius,
+            sensor_offsets: self.config.sensor_offsets.clone(),
+        }
+    }
+
+    fn request_reading(&mut self) -> Task<cosmic::app::Message<Message>> {
+        // Skip this one, the reading in flight will deliver a fresh enough value. Nothing
+        // reads the sensors while paused, the popup opening included.
+        if self.reading_in_flight || self.updates_paused {
+            return Task::none();
+        }
+
+        self.reading_in_flight = true;
+        Task::perform(
+            read_sensors(
+                self.sensors.clone(),
+                self.cpu_load.clone(),
+                self.rapl.clone(),
+                self.throttle_monitor.clone(),
+                self.read_options(),
+            ),
+            |reading| cosmic::app::Message::App(Message::TempUpdated(reading)),
+        )
+    }
+
+    // Creates and "opens" the popup
+    fn open_popup(&mut self) -> Task<cosmic::app::Message<Message>> {
+        let Some(main_window_id) = self.core.main_window_id() else {
+            return Task::none();
+        };
+
+        self.update_sensor_options();
+        let new_id = Id::unique();
+        self.popup.replace(new_id);
+
+        let mut popup_settings =
+            self.core
+                .applet
+                .get_popup_settings(main_window_id, new_id, None, None, None);
+        // Without the grab the compositor doesn't close it when the focus moves
+        popup_settings.grab = !self.popup_pinned;
+
+        let (anchor, gravity, offset) = popup_placement(self.core.applet.anchor);
+        popup_settings.positioner.anchor_rect = self.popup_anchor_rect();
+        popup_settings.positioner.anchor = anchor;
+        popup_settings.positioner.gravity = gravity;
+        popup_settings.positioner.offset = offset;
+
+        // Let the popup grow with its content. The compositor shrinks it when it
+        // would go past the screen edge, the content scrolls in that case.
+        popup_settings.positioner.size_limits = Limits::NONE
+            .min_width(POPUP_MIN_WIDTH)
+            .max_width(POPUP_MAX_WIDTH)
+            .min_height(POPUP_MIN_HEIGHT)
+            .max_height(POPUP_MAX_HEIGHT);
+
+        // Same for mouse and keyboard activation, both end up here
+        let focus = text_input::focus(PERIOD_INPUT_ID.clone());
+
+        // The shown values can be a whole refresh period old, and drives aren't
+        // read at all while the popup is closed, so don't wait for the next tick
+        Task::batch([get_popup(popup_settings), focus, self.refresh_now()])
+    }
+
+    // Closes the main popup, pinned or not
+    fn close_popup(&mut self) -> Task<cosmic::app::Message<Message>> {
+        let Some(popup_id) = self.popup.take() else {
+            return Task::none();
+        };
+        self.popup_pinned = false;
+        self.update_text_hidden();
+        destroy_popup(popup_id)
+    }
+
+    fn close_context_menu(&mut self) -> Task<cosmic::app::Message<Message>> {
+        self.context_menu
+            .take()
+            .map_or_else(Task::none, destroy_popup)
+    }
+
+    // Quick actions, placed like the main popup which it replaces
+    fn open_context_menu(&mut self) -> Task<cosmic::app::Message<Message>> {
+        let Some(main_window_id) = self.core.main_window_id() else {
+            return Task::none();
+        };
+
+        let close_popup = self.close_popup();
+        let new_id = Id::unique();
+        self.context_menu.replace(new_id);
+
+        let mut popup_settings =
+            self.core
+                .applet
+                .get_popup_settings(main_window_id, new_id, None, None, None);
+        let (anchor, gravity, offset) = popup_placement(self.core.applet.anchor);
+        popup_settings.positioner.anchor_rect = self.popup_anchor_rect();
+        popup_settings.positioner.anchor = anchor;
+        popup_settings.positioner.gravity = gravity;
+        popup_settings.positioner.offset = offset;
+
+        Task::batch([close_popup, get_popup(popup_settings)])
+    }
+
+    // The session and the right-click menu can both pause the readings
+    fn update_paused(&mut self) {
+        let paused = self.session_paused || self.updates_paused;
+        let was_paused = self.paused.send_replace(paused);
+        // Time spent paused isn't a stuck sensor
+        if was_paused && !paused {
+            self.last_reading_at = Some(Instant::now());
+            self.update_panel_text();
+        }
+    }
+
+    // Goes through the files, which can be slow for EC based sensors
+    fn probe_access(&self) -> Task<cosmic::app::Message<Message>> {
+        Task::perform(
+            async {
+                tokio::task::spawn_blocking(|| probe_access(Path::new(hwmon::HWMON_ROOT)))
+                    .await
+                    .unwrap_or_default()
+            },
+            |problem| cosmic::app::Message::App(Message::AccessProbed(problem)),
+        )
+    }
+
+    fn detect_virtualization(&self) -> Task<cosmic::app::Message<Message>> {
+        Task::perform(
+            async {
+                tokio::task::spawn_blocking(|| {
+                    virtualization::detect(
+                        Path::new(virtualization::DMI_PRODUCT_NAME),
+                        Path::new(virtualization::CPUINFO),
+                    )
+                })
+                .await
+                .unwrap_or_default()
+            },
+            |detected| cosmic::app::Message::App(Message::VirtualizationDetected(detected)),
+        )
+    }
+
+    // A VM without a single sensor, "--" is all there ever will be
+    fn no_sensors_in_vm(&self) -> bool {
+        self.virtual_machine && self.sensor_labels.is_empty() && self.state.temp.is_none()
+    }
+
+    fn load_instead_of_temp(&self) -> bool {
+        self.config.load_without_sensors && self.no_sensors_in_vm()
+    }
+
+    // Same as a tick, except that the reading is shown even if the display period would skip it
+    fn refresh_now(&mut self) -> Task<cosmic::app::Message<Message>> {
+        self.display_cadence.reset();
+        self.update_panel_text();
+        self.request_reading()
+    }
+
+    fn apply_reading(&mut self, reading: SensorReading) -> Task<cosmic::app::Message<Message>> {
+        // Was already in flight when the updates were paused
+        if self.updates_paused {
+            return Task::none();
+        }
+        // Right after a resume sensors can still report a value cached before the suspend,
+        // or a spike. Dropped whole, so it can't reach the peak, the history or an alert.
+        if self.resume_samples_to_skip > 0 && matches!(reading.cpu, TempReading::Value(_)) {
+            self.resume_samples_to_skip -= 1;
+            return Task::none();
+        }
+
+        // Only the shown values wait for the display period, everything else below gets
+        // every sample
+        let display = self
+            .display_cadence
+            .sample(self.config.sample_period_ms, self.config.display_period_ms);
+        let fresh = self
+            .state
+            .apply_reading(&reading.cpu, display, self.config.smoothing);
+        self.read_error = match &reading.cpu {
+            TempReading::ReadError(source) => Some(fl!("read-error", source = source)),
+            _ => None,
+        };
+        if display {
+            self.gpu_temp = reading.gpu.as_ref().map(|(_, temp)| *temp);
+            self.hottest_sensor = reading.hottest;
+            self.load = reading.load;
+            self.fans = reading.fans;
+            self.power = reading.power;
+        }
+        self.power_error = reading.power_error;
+        // Every sample, so the badge goes away as soon as the throttling stops
+        self.throttle = reading.throttle;
+        self.gpu_sensor = reading.gpu.map(|(label, _)| label);
+        if fresh.is_some() {
+            self.last_reading_at = Some(Instant::now());
+            self.cpu_raw = reading.cpu_raw;
+            let previous_label = self.cpu_label().map(str::to_string);
+            self.cpu_sensor = reading.cpu_sensor;
+            let label = self.cpu_label().map(str::to_string);
+            if label != previous_label {
+                self.state
+                    .update_sensor_threshold_strings(&self.config, label.as_deref());
+            }
+        }
+        self.ccd_temps = reading.ccds;
+        if self.config.metrics_enabled {
+            *self.metrics.lock().unwrap_or_else(PoisonError::into_inner) = Metrics {
+                cpu: fresh,
+                cores: reading.cores.clone(),
+            };
+        }
+        if display {
+            self.hot_core = hottest_core(&reading.cores).map(|(label, _)| label.clone());
+            self.core_temps = reading.cores;
+        }
+        self.drive_temps = reading.drives;
+        // An open sensor picker follows sensors that come and go
+        let labels_changed = self.sensor_labels != reading.labels;
+        self.sensor_labels = reading.labels;
+        self.sensor_chips = reading.chips;
+        self.cpu_alternative = reading.cpu_alternative;
+        self.selected_missing = reading.selected_missing;
+        if labels_changed && self.popup.is_some() {
+            self.update_sensor_options();
+        }
+        self.sensor_temps = reading.temps;
+        self.all_sensors = reading.all;
+        self.component_count = reading.component_count;
+        self.cpu_priority = reading.cpu_priority;
+        self.read_duration = Some(reading.read_duration);
+        if self.config.socket_enabled {
+            self.sensor_export
+                .send_replace(socket::document(&reading.components));
+        }
+        self.update_panel_text();
+
+        let dbus_task = match &self.dbus {
+            Some(connection) => Task::perform(
+                dbus::update(connection.clone(), fresh, self.selected_sensor().cloned()),
+                |_| cosmic::app::Message::None,
+            ),
+            None => Task::none(),
+        };
+
+        let Some(celsius) = fresh else {
+            return dbus_task;
+        };
+
+        self.history.push(celsius);
+        self.log_sample(celsius);
+        self.publish_mqtt(celsius);
+        let save_task = self.save_history_if_due();
+        if self.stats_sensor.as_ref() != self.selected_sensor() {
+            self.stats.clear();
+            self.stats_sensor = self.selected_sensor().cloned();
+        }
+        self.stats.push(celsius);
+
+        let mut tasks = vec![dbus_task, save_task];
+
+        // Rounded like the panel, so a value that's shown as critical is critical here too
+        let unit = self.config.unit;
+        let shown = units::shown_celsius(celsius, unit, self.decimal_places());
+        let critical = units::shown_celsius(self.critical_threshold(), unit, self.decimal_places());
+
+        // Notifications and sounds are held back during quiet hours, the panel still changes
+        let quiet = self.quiet_hours().is_some();
+        let muted_peak = self.muted_alerts.update(quiet, celsius);
+        let crossed = self.critical_alert.update(shown, critical);
+        if crossed && quiet {
+            self.muted_alerts.mute(celsius);
+        }
+        let alert = crossed && !quiet;
+        if alert && self.config.critical_notification {
+            let body = fl!(
+                "critical-notification-body",
+                temp = self.format_temp(Some(celsius))
+            );
+            tasks.push(Task::perform(
+                notifications::send_critical(fl!("critical-notification-summary"), body),
+                |_| cosmic::app::Message::None,
+            ));
+        }
+
+        if let Some(peak) = muted_peak.filter(|_| self.config.critical_notification) {
+            let body = fl!(
+                "quiet-hours-summary-body",
+                temp = self.format_temp(Some(peak))
+            );
+            tasks.push(Task::perform(
+                notifications::send_critical(fl!("quiet-hours-summary"), body),
+                |_| cosmic::app::Message::None,
+            ));
+        }
+
+        // Same crossing as the notification, so it plays once per incident
+        if let Some(sound) = self.config.alert_sound.clone().filter(|_| alert) {
+            tasks.push(Task::perform(sound::play(sound), |result| {
+                cosmic::app::Message::App(Message::SoundFinished(result))
+            }));
+        }
+
+        if self.config.flash_on_critical {
+            self.flash_alert.update(shown, critical);
+        }
+
+        let sustained = self.critical_action_alert.update(
+            shown,
+            critical,
+            time::Duration::from_secs(self.config.critical_action_delay_seconds),
+            std::time::Instant::now(),
+        );
+        if let Some(command) = self.config.critical_action.clone().filter(|_| sustained) {
+            tasks.push(Task::perform(critical_action::run(command), |result| {
+                cosmic::app::Message::App(Message::CriticalActionFinished(result))
+            }));
+        }
+
+        Task::batch(tasks)
+    }
+
+    fn to_display_unit(&self, celsius: f32) -> f32 {
+        units::to_unit(celsius, self.config.unit)
+    }
+
+    fn motion(&self) -> Motion {
+        Motion {
+            reduced_by_desktop: self.reduced_motion,
+            disabled_by_config: self.config.disable_animations,
+        }
+    }
+
+    fn unit_symbol(&self) -> &'static str {
+        units::unit_symbol(self.config.unit)
+    }
+
+    // Label the per-sensor thresholds of the CPU value are stored under, `None` when the
+    // value isn't from a single component
+    fn cpu_label(&self) -> Option<&str> {
+        match &self.cpu_sensor {
+            Some(CpuSensor::Component { label, .. }) => Some(label),
+            _ => None,
+        }
+    }
+
+    fn default_critical_threshold(&self) -> f32 {
+        self.state.default_critical_threshold(&self.config)
+    }
+
+    fn default_warning_threshold(&self) -> f32 {
+        self.state.default_warning_threshold(&self.config)
+    }
+
+    fn critical_threshold_of(&self, label: Option<&str>) -> f32 {
+        self.state.critical_threshold(&self.config, label)
+    }
+
+    fn warning_threshold_of(&self, label: Option<&str>) -> f32 {
+        self.state.warning_threshold(&self.config, label)
+    }
+
+    // What the alerts use
+    fn critical_threshold(&self) -> f32 {
+        self.critical_threshold_of(self.cpu_label())
+    }
+
+    fn warning_threshold(&self) -> f32 {
+        self.warning_threshold_of(self.cpu_label())
+    }
+
+    // Returns `true` if the input was saved
+    fn threshold_input(&mut self, field: ThresholdField, input: String) -> bool {
+        let label = self.cpu_label().map(str::to_string);
+        let saved = self
+            .state
+            .threshold_input(&mut self.config, field, input, label.as_deref());
+        if saved {
+            self.save_config();
+        }
+        saved
+    }
+
+    // Shown in the threshold inputs while they're empty
+    fn threshold_placeholder(&self, celsius: f32) -> String {
+        units::format_number(celsius, self.config.unit, 0).unwrap_or_default()
+    }
+
+    // The global offset, which the sensor's own replaces
+    fn offset_placeholder(&self) -> String {
+        units::format_number(self.config.offset_celsius, TemperatureUnit::Celsius, 1)
+            .unwrap_or_default()
+    }
+
+    // "87°C / max 95°C / crit 105°C", limits the driver doesn't report are left out
+    fn format_sensor_limits(&self) -> String {
+        let mut text = self.format_temp(self.state.temp);
+        if let Some(max) = self.state.sensor_max {
+            text.push_str(" / ");
+            text.push_str(&fl!("sensor-max", temp = self.format_temp(Some(max))));
+        }
+        if let Some(critical) = self.state.sensor_critical {
+            text.push_str(" / ");
+            text.push_str(&fl!(
+                "sensor-critical",
+                temp = self.format_temp(Some(critical))
+            ));
+        }
+        text
+    }
+
+    fn effective_refresh_period(&self) -> u64 {
+        let period = self.config.sample_period_ms;
+        if self.config.battery_saver && self.on_battery {
+            period.saturating_mul(self.config.battery_period_multiplier.max(1) as u64)
+        } else {
+            period
+        }
+    }
+
+    // Don't interrupt the tick subscription unless necessary
+    fn update_refresh_period(&mut self) {
+        let period = self.effective_refresh_period();
+        self.refresh_period.send_if_modified(|current| {
+            if *current == period {
+                false
+            } else {
+                *current = period;
+                true
+            }
+        });
+    }
+
+    fn decimal_places(&self) -> usize {
+        self.config.decimal_places.min(MAX_DECIMAL_PLACES) as usize
+    }
+
+    // Always prints the configured number of decimals (no trimming of trailing zeros), so
+    // the width only changes with the integer part
+    fn format_temp(&self, temp: Option<f32>) -> String {
+        match temp {
+            Some(celsius) => units::format_temp(celsius, self.config.unit, self.decimal_places()),
+            None => units::NO_VALUE.to_string(),
+        }
+    }
+
+    fn hottest_temp(&self) -> Option<f32> {
+        self.hottest_sensor.as_ref().map(|(_, temp)| *temp)
+    }
+
+    // Uses the configured panel format, padded in fixed width mode. With both units shown
+    // the secondary one follows the primary one, without decimals to keep the panel compact.
+    fn format_panel_temp(&self, temp: Option<f32>) -> String {
+        let Some(celsius) = temp else {
+            return units::NO_VALUE.to_string();
+        };
+
+        if !self.config.show_both_units {
+            return self.format_panel_value(celsius, self.config.unit, self.decimal_places());
+        }
+        format!(
+            "{} / {}",
+            self.format_panel_value(celsius, self.config.unit, 0),
+            self.format_panel_value(celsius, units::secondary_unit(self.config.unit), 0)
+        )
+    }
+
+    // A rounding step replaces the decimals
+    fn format_panel_value(&self, celsius: f32, unit: TemperatureUnit, decimals: usize) -> String {
+        let step = self.config.rounding_step.filter(|step| *step > 0);
+        let (number, decimals) = match step {
+            Some(step) => (units::format_number_in_steps(celsius, unit, step), 0),
+            None => (units::format_number(celsius, unit, decimals), decimals),
+        };
+        let Some(mut number) = number else {
+            return units::NO_VALUE.to_string();
+        };
+
+        if self.config.fixed_width {
+            number = pad_number(
+                number,
+                reserved_integer_digits(unit, self.critical_threshold()),
+                decimals,
+            );
+        }
+
+        panel_format::format(
+            &self.config.panel_format,
+            &[("temp", &number), ("unit", units::unit_symbol(unit))],
+        )
+    }
+
+    // The smoothed value, marked with "!" while the sensors fail to report. Thresholds and
+    // alerts keep using the raw value, so smoothing doesn't hide a spike.
+    fn format_cpu_temp(&self) -> String {
+        self.mark_stale(self.format_panel_temp(self.state.smoothed_temp))
+    }
+
+    fn mark_stale(&self, temp: String) -> String {
+        if self.read_error.is_some() && self.state.temp.is_some() {
+            format!("{}!", temp)
+        } else {
+            temp
+        }
+    }
+
+    // What the headroom is measured against. Unlike the alerts the sensor's own limit comes
+    // first, it's what the hardware throttles at.
+    fn headroom_critical(&self) -> Option<f32> {
+        self.state.headroom_critical(&self.config, self.cpu_label())
+    }
+
+    // Signed distance to the critical limit, negative while below it. Not run through the
+    // panel format, "{temp}{unit}" makes no sense for a difference. Without a known limit
+    // the absolute temperature is shown, the popup says why.
+    fn format_headroom(&self) -> String {
+        let Some(critical) = self.headroom_critical() else {
+            return self.format_cpu_temp();
+        };
+        let Some(celsius) = self.state.smoothed_temp else {
+            return units::NO_VALUE.to_string();
+        };
+
+        self.mark_stale(units::format_delta(
+            celsius - critical,
+            self.config.unit,
+            self.decimal_places(),
+        ))
+    }
+
+    // How long the CPU sensor hasn't given a value, `None` while that's still expected.
+    // Readings don't happen while paused, so that never counts.
+    fn stale_for(&self) -> Option<time::Duration> {
+        let elapsed = self.last_reading_at?.elapsed();
+        let limit =
+            time::Duration::from_millis(self.effective_refresh_period()) * STALE_REFRESH_PERIODS;
+        (elapsed > limit && !*self.paused.borrow()).then_some(elapsed)
+    }
+
+    // Raw values of what the panel shows, with the label of their sensor
+    fn shown_temps(&self) -> impl Iterator<Item = (Option<&str>, f32)> {
+        let mode = self.config.display_mode;
+        [
+            self.state
+                .temp
+                .filter(|_| mode.shows_cpu())
+                .map(|celsius| (self.cpu_label(), celsius)),
+            self.gpu_temp
+                .filter(|_| mode.shows_gpu())
+                .map(|celsius| (self.gpu_sensor.as_deref(), celsius)),
+            self.hottest_sensor
+                .as_ref()
+                .filter(|_| mode == DisplayMode::HottestAny)
+                .map(|(label, celsius)| (Some(label.as_str()), *celsius)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    // Never hides a missing or stale value, that's worth seeing. Left alone while the popup
+    // is open, it's anchored to the button and would end up next to it after a resize.
+    fn update_text_hidden(&mut self) {
+        if self.popup.is_some() {
+            return;
+        }
+
+        let hottest = self
+            .shown_temps()
+            .map(|(_, celsius)| celsius)
+            .reduce(f32::max);
+        self.text_hidden = match (self.config.hide_below_celsius, hottest) {
+            (Some(limit), Some(celsius)) if self.stale_for().is_none() => {
+                if self.text_hidden {
+                    celsius < limit + HIDE_HYSTERESIS_CELSIUS
+                } else {
+                    celsius < limit
+                }
+            }
+            _ => false,
+        };
+    }
+
+    // Decides both the text color and the icon, so they always agree
+    fn temp_level(&self) -> TempLevel {
+        // Not a temperature, but it shouldn't look like everything is fine either
+        if self.config.display_mode.shows_cpu() && self.stale_for().is_some() {
+            return TempLevel::Warning;
+        }
+
+        // A positive headroom is shown as critical, even with a higher configured threshold
+        let above_headroom = self.config.display_mode == DisplayMode::Headroom
+            && self
+                .state
+                .temp
+                .zip(self.headroom_critical())
+                .is_some_and(|(celsius, critical)| celsius > critical);
+        if above_headroom {
+            return TempLevel::Critical;
+        }
+
+        self.shown_temps()
+            .map(|(label, celsius)| {
+                get_temp_level(
+                    celsius,
+                    self.warning_threshold_of(label),
+                    self.critical_threshold_of(label),
+                    self.config.unit,
+                    self.decimal_places(),
+                )
+            })
+            .max()
+            .unwrap_or(TempLevel::Normal)
+    }
+
+    // `None` means the default theme color
+    fn temp_color(&self) -> Option<Color> {
+        if !self.config.threshold_indication.colors_text() {
+            return None;
+        }
+
+        let cosmic = self.core.system_theme().cosmic();
+        match self.temp_level() {
+            TempLevel::Normal => None,
+            TempLevel::Warning => Some(cosmic.warning_color().into()),
+            TempLevel::Critical => Some(cosmic.destructive_color().into()),
+        }
+    }
+
+    // Thin gauge from `CORE_BAR_MIN_CELSIUS` up to the critical threshold, colored like the
+    // panel text would be at that temperature
+    fn core_bar<'a>(&self, label: &'a str, celsius: f32, hottest: bool) -> Element<'a, Message> {
+        let critical = self.critical_threshold().max(CORE_BAR_MIN_CELSIUS + 1.0);
+        let level = get_temp_level(
+            celsius,
+            self.warning_threshold(),
+            self.critical_threshold(),
+            self.config.unit,
+            self.decimal_places(),
+        );
+        let class = match level {
+            TempLevel::Normal => cosmic::theme::ProgressBar::Primary,
+            TempLevel::Warning => {
+                cosmic::theme::ProgressBar::Custom(Box::new(|theme: &cosmic::Theme| {
+                    let cosmic = theme.cosmic();
+                    progress_bar::Style {
+                        background: Color::from(cosmic.background.divider).into(),
+                        bar: Color::from(cosmic.warning_color()).into(),
+                        border: cosmic::iced::Border {
+                            radius: cosmic.corner_radii.radius_xs.into(),
+                            ..Default::default()
+                        },
+                    }
+                }))
+            }
+            TempLevel::Critical => cosmic::theme::ProgressBar::Danger,
+        };
+
+        let mut label = text::caption(label).width(Length::Fixed(CORE_LABEL_WIDTH));
+        if hottest {
+            label = label.class(cosmic::theme::Text::Accent);
+        }
+
+        row![
+            label,
+            progress_bar(CORE_BAR_MIN_CELSIUS..=critical, celsius)
+                .height(Length::Fixed(CORE_BAR_HEIGHT))
+                .class(class),
+            text::caption(self.format_temp(Some(celsius))),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center)
+        .into()
+    }
+
+    // Formatted here instead of in `view`, which runs on every redraw. Called whenever
+    // something shown in the panel changes.
+    fn update_panel_text(&mut self) {
+        #[cfg(debug_assertions)]
+        PANEL_TEXT_FORMATS.fetch_add(1, Ordering::Relaxed);
+
+        self.update_text_hidden();
+
+        let load_instead = self.load_instead_of_temp();
+        let mut text = match self.config.display_mode {
+            // "23%", better than "--" that never goes away
+            _ if load_instead => format_load(self.load),
+            DisplayMode::Cpu => self.format_cpu_temp(),
+            DisplayMode::Gpu => self.format_panel_temp(self.gpu_temp),
+            DisplayMode::Both => format!(
+                "{} / {}",
+                self.format_cpu_temp(),
+                self.format_panel_temp(self.gpu_temp)
+            ),
+            DisplayMode::HottestAny => self.format_panel_temp(self.hottest_temp()),
+            DisplayMode::Headroom => self.format_headroom(),
+        };
+        // "62° 34%", the load explains a high temperature (or doesn't)
+        if self.config.show_load && !load_instead {
+            text.push(' ');
+            text.push_str(&format_load(self.load));
+        }
+        // "65° 48W", left out while there's no reading
+        if let Some(watts) = self.power.filter(|_| self.config.power_in_panel) {
+            text.push(' ');
+            text.push_str(&format_power(watts));
+        }
+        // "67° · 1450 rpm", whether the cooling keeps up
+        if self.config.show_fan {
+            text.push_str(" · ");
+            text.push_str(&format_fan(fans::cpu_fan(&self.fans)));
+        }
+        // "Core 11: 88°", only next to the CPU temperature the core is a part of
+        if let Some(core) = self
+            .hot_core
+            .as_deref()
+            .filter(|_| self.config.show_hot_core && self.config.display_mode == DisplayMode::Cpu)
+        {
+            text.insert_str(0, &format!("{core}: "));
+        }
+        // "92° THROTTLING", only while it is
+        if self.throttle.is_some_and(|throttle| throttle.active) {
+            text.push(' ');
+            text.push_str(&fl!("throttling-badge"));
+        }
+        // Part of the text, so the size of the panel item and the stacking account for it
+        text.insert_str(0, &self.config.panel_prefix);
+        text.push_str(&self.config.panel_suffix);
+        // "⏸ 63°", the last value before the updates were paused
+        if self.updates_paused {
+            text.insert_str(0, PAUSED_MARK);
+        }
+
+        // Most samples round to what's shown already, there's no need to stack the lines again.
+        // The panel is still drawn after the update, this only saves the formatting work.
+        if text == self.panel_text {
+            #[cfg(debug_assertions)]
+            PANEL_TEXT_UNCHANGED.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+
+        self.stacked_panel_text = panel_format::stack_lines(&text);
+        self.panel_text = text;
+    }
+
+    // "CPU: 63°C" for the icon-only panel, the same text the panel would show
+    fn panel_tooltip(&self) -> String {
+        let sensor = match self.config.display_mode {
+            DisplayMode::Cpu | DisplayMode::Headroom => fl!("display-cpu"),
+            DisplayMode::Gpu => fl!("display-gpu"),
+            // The text tells them apart already
+            DisplayMode::Both | DisplayMode::HottestAny => return self.panel_text.clone(),
+        };
+        fl!(
+            "panel-tooltip",
+            sensor = sensor,
+            temp = self.panel_text.as_str()
+        )
+    }
+
+    // What a vertical panel gives the applet, the text overflows when it's wider
+    fn vertical_breadth(&self) -> f32 {
+        let (icon_width, _) = self.core.applet.suggested_size(true);
+        (icon_width + 2 * self.core.applet.suggested_padding(true)) as f32
+    }
+
+    // Stacked once the single line was measured wider than the panel, until the text gets
+    // shorter than that or the panel size changes. Measuring again while stacked would
+    // always fit and flip back and forth.
+    fn stack_panel_text(&self) -> bool {
+        self.vertical_overflow.is_some_and(|(chars, breadth)| {
+            breadth == self.vertical_breadth() && self.panel_text.chars().count() >= chars
+        })
+    }
+
+    // Every config change goes through here, see `ConfigWriter`
+    fn save_config(&mut self) {
+        self.config_writer.save();
+    }
+
+    // (label, value) pairs for the diagnostics section, also what gets copied
+    fn diagnostics(&self) -> Vec<(String, String)> {
+        let backend = SensorBackend::ALL
+            .iter()
+            .position(|backend| *backend == self.config.sensor_backend)
+            .map_or("", |index| SENSOR_BACKEND_OPTIONS[index]);
+        let sensor = match (&self.cpu_sensor, self.cpu_priority) {
+            (Some(sensor), Some(priority)) => format!(
+                "{} ({})",
+                describe_cpu_sensor(sensor),
+                fl!("diagnostics-priority", priority = priority)
+            ),
+            (Some(sensor), None) => describe_cpu_sensor(sensor),
+            (None, _) => fl!("not-found"),
+        };
+        let read_duration = self
+            .read_duration
+            .map_or(units::NO_VALUE.to_string(), |duration| {
+                format!("{:.1} ms", duration.as_secs_f64() * 1000.0)
+            });
+        // "3.1 ms avg / 112 ms max", over the last minute
+        let summarize = |durations: &RecentDurations| {
+            durations
+                .summary()
+                .map_or(units::NO_VALUE.to_string(), |summary| {
+                    fl!(
+                        "diagnostics-timing-value",
+                        avg = timing::format_millis(summary.avg),
+                        max = timing::format_millis(summary.max)
+                    )
+                })
+        };
+        let last_error = [
+            &self.read_error,
+            self.config_writer.error(),
+            &self.sample_log_error,
+            &self.mqtt_error,
+            &self.monitor_error,
+            &self.critical_action_error,
+        ]
+        .into_iter()
+        .find_map(Option::clone)
+        .unwrap_or_else(|| fl!("diagnostics-none"));
+
+        let mut diagnostics = vec![
+            (
+                fl!("diagnostics-version"),
+                env!("CARGO_PKG_VERSION").to_string(),
+            ),
+            (fl!("sensor-backend"), backend.to_string()),
+            (
+                fl!("diagnostics-components"),
+                self.component_count.to_string(),
+            ),
+            (fl!("diagnostics-sensor"), sensor),
+            (fl!("diagnostics-read-time"), read_duration),
+            (
+                fl!("diagnostics-read-times"),
+                summarize(&self.read_durations),
+            ),
+            (fl!("diagnostics-tick-delay"), summarize(&self.tick_delays)),
+            (fl!("diagnostics-last-error"), last_error),
+        ];
+        // Both in Celsius, which is what the offset is typed in
+        if let (Some(raw), Some(temp)) = (self.cpu_raw, self.state.temp) {
+            let unit = TemperatureUnit::Celsius;
+            diagnostics.push((
+                fl!("diagnostics-calibration"),
+                fl!(
+                    "diagnostics-calibration-value",
+                    raw = units::format_temp(raw, unit, 1),
+                    adjusted = units::format_temp(temp, unit, 1)
+                ),
+            ));
+        }
+        // Why the power readout is missing, usually permissions
+        if let Some(error) = &self.power_error {
+            diagnostics.push((fl!("package-power"), error.clone()));
+        }
+        diagnostics
+    }
+
+    // The right-click menu, each entry closes it
+    fn view_context_menu(&self) -> Element<'_, Message> {
+        let unit = units::unit_symbol(units::secondary_unit(self.config.unit));
+        let pause = if self.updates_paused {
+            fl!("menu-resume-updates")
+        } else {
+            fl!("menu-pause-updates")
+        };
+        let entries = [
+            (fl!("menu-toggle-unit", unit = unit), MenuAction::ToggleUnit),
+            (fl!("menu-reset-peak"), MenuAction::ResetPeak),
+            (fl!("open-settings"), MenuAction::OpenSettings),
+            (pause, MenuAction::PauseUpdates),
+        ];
+        let menu = column(entries.into_iter().map(|(label, action)| {
+            cosmic::applet::menu_button(text::body(label))
+                .on_press(Message::MenuAction(action))
+                .into()
+        }))
+        .padding([8, 0]);
+
+        self.core.applet.popup_container(menu).into()
+    }
+
+    // Hands the sample to the log writer, which does the disk access
+    fn log_sample(&mut self, celsius: f32) {
+        if self.config.log_to_file.is_none() {
+            return;
+        }
+        let Some(sender) = &self.sample_log else {
+            return;
+        };
+
+        let sensor = match &self.cpu_sensor {
+            Some(CpuSensor::Component { label, .. }) => label.clone(),
+            Some(sensor) => describe_cpu_sensor(sensor),
+            None => "CPU".to_string(),
+        };
+        if sender
+            .send(sample_log::Command::Write(Sample::now(sensor, celsius)))
+            .is_err()
+        {
+            // The writer is gone, a new one announces itself
+            self.sample_log = None;
+        }
+    }
+
+    fn publish_mqtt(&mut self, celsius: f32) {
+        if !self.config.mqtt_enabled {
+            return;
+        }
+        let Some(publisher) = &self.mqtt else {
+            return;
+        };
+
+        let multiplier = self.config.mqtt_interval_multiplier.max(1);
+        let due = self.mqtt_samples % multiplier == 0;
+        self.mqtt_samples = self.mqtt_samples.wrapping_add(1);
+        if !due {
+            return;
+        }
+
+        let sensor = match &self.cpu_sensor {
+            Some(CpuSensor::Component { label, .. }) => label.clone(),
+            Some(sensor) => describe_cpu_sensor(sensor),
+            None => "CPU".to_string(),
+        };
+        if !publisher.publish(&sensor, celsius) {
+            // The client is gone, a new one announces itself
+            self.mqtt = None;
+        }
+    }
+
+    // Nothing is read while the power is off, so the next reading starts over. The energy
+    // counters would otherwise be averaged over the whole time it was off, and can wrap more
+    // than once in between.
+    fn reset_power(&mut self) {
+        self.power = None;
+        self.power_error = None;
+        *self.rapl.lock().unwrap_or_else(PoisonError::into_inner) = RaplMeter::default();
+    }
+
+    // What `instance_config_id` appended to the applet ID, `None` for the default instance
+    fn instance_suffix(&self) -> Option<&'static str> {
+        self.config_id
+            .strip_prefix(ID)
+            .and_then(|suffix| suffix.strip_prefix('.'))
+    }
+
+    // Named instances get their own socket
+    fn socket_path(&self) -> Option<PathBuf> {
+        socket::socket_path(self.instance_suffix())
+    }
+
+    // `Some` while the quiet hours are on
+    fn quiet_hours(&self) -> Option<QuietHours> {
+        QuietHours::new(self.config.quiet_hours_start, self.config.quiet_hours_end)
+            .filter(|quiet| quiet.contains(quiet_hours::minute_of_day_now()))
+    }
+
+    fn update_quiet_hours_strings(&mut self) {
+        let format = |minute: Option<u16>| minute.map(quiet_hours::format_time).unwrap_or_default();
+        self.quiet_hours_start_string = format(self.config.quiet_hours_start);
+        self.quiet_hours_end_string = format(self.config.quiet_hours_end);
+    }
+
+    fn update_log_strings(&mut self) {
+        self.log_path_string = self
+            .config
+            .log_to_file
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        self.log_max_size_string = self.config.log_max_size_mb.to_string();
+    }
+
+    fn save_history_if_due(&mut self) -> Task<cosmic::app::Message<Message>> {
+        let due = self.history_saved_at.map_or(true, |saved_at| {
+            saved_at.elapsed() >= persist::SAVE_INTERVAL
+        });
+        if !self.config.persist_history || !due {
+            return Task::none();
+        }
+        let Some(path) = persist::snapshot_path(self.config_id) else {
+            return Task::none();
+        };
+
+        self.history_saved_at = Some(Instant::now());
+        let snapshot = Snapshot::new(&self.history, self.state.peak);
+        Task::perform(
+            tokio::task::spawn_blocking(move || persist::save(&path, &snapshot)),
+            |result| {
+                match result {
+                    Ok(Err(err)) => tracing::error!(?err, "Error saving the history"),
+                    Err(err) => tracing::error!(?err, "Saving the history failed"),
+                    Ok(Ok(())) => {}
+                }
+                cosmic::app::Message::None
+            },
+        )
+    }
+
+    fn update_sensor_options(&mut self) {
+        let mut options = vec![fl!("automatic")];
+        options.extend(self.sensor_labels.iter().cloned());
+
+        // Keep showing the selection even if the sensor is currently missing
+        if let Some(selected) = self.selected_sensor() {
+            if !options.contains(selected) {
+                options.push(selected.clone());
+            }
+        }
+
+        self.sensor_option_names = std::iter::once(options[0].clone())
+            .chain(sensor_names(&options[1..], &self.sensor_chips))
+            .collect();
+        self.sensor_options = options;
+    }
+
+    // Moves the selection `step` entries through the automatic detection followed by every
+    // sensor, wrapping around at both ends. A missing selected sensor counts as automatic.
+    fn cycle_sensor(&mut self, step: i32) -> Task<cosmic::app::Message<Message>> {
+        let count = self.sensor_labels.len() + 1;
+        let current = self
+            .selected_sensor()
+            .and_then(|selected| {
+                self.sensor_labels
+                    .iter()
+                    .position(|label| label == selected)
+            })
+            .map_or(0, |index| index + 1);
+        let next = (current as i64 + step as i64).rem_euclid(count as i64) as usize;
+
+        self.config.selected_sensor = next
+            .checked_sub(1)
+            .map(|index| self.sensor_labels[index].clone());
+        // Picked in the panel, that replaces the one from the command line
+        self.sensor_override = None;
+        self.display_cadence.reset();
+        self.save_config();
+        if self.popup.is_some() {
+            self.update_sensor_options();
+        }
+
+        self.request_reading()
+    }
+
+    // A read that is still running finishes with the old provider, its result is fine to use
+    fn switch_backend(&mut self) -> Task<cosmic::app::Message<Message>> {
+        self.sensors = Arc::new(Mutex::new(new_provider(self.config.sensor_backend)));
+        self.display_cadence.reset();
+        self.request_reading()
+    }
+
+    fn popup_anchor_rect(&self) -> Rectangle<i32> {
+        popup_anchor_rect(
+            self.rectangle,
+            self.core.applet.suggested_size(true),
+            self.core.applet.suggested_padding(true),
+        )
+    }
 
-fn convert_to_fahrenheit(celsius: f32) -> f32 {
-    return (celsius * 1.8) + 32.0;
+    // `--sensor` wins over the config until another one is picked
+    fn selected_sensor(&self) -> Option<&String> {
+        self.sensor_override
+            .as_ref()
+            .or(self.config.selected_sensor.as_ref())
+    }
+
+    fn selected_sensor_index(&self) -> Option<usize> {
+        match self.selected_sensor() {
+            None => Some(0),
+            Some(label) => self
+                .sensor_options
+                .iter()
+                .skip(1)
+                .position(|option| option == label)
+                .map(|index| index + 1),
+        }
+    }
 }
 
 impl cosmic::Application for Window {
@@ -76,7 +1892,7 @@ impl cosmic::Application for Window {
      *  Tasks take place.
      */
     type Executor = cosmic::SingleThreadExecutor;
-    type Flags = (); // Honestly not sure what these are for.
+    type Flags = Flags; // The instance name and sensor passed from main, if any
     type Message = Message; // These are setting the application messages to our Message enum
     const APP_ID: &'static str = ID; // This is where we set our const above to the actual ID
 
@@ -96,190 +1912,1130 @@ impl cosmic::Application for Window {
 
     // Initialize the applet
     /*
-     *  The parameters are the Core and flags (again not sure what to do with these).
+     *  The parameters are the Core and flags, which carry the instance name from main.
      *  The function returns our model struct initialized and an Option<Task>, in this case
      *  there is no command so it returns a None value with the type of Task in its place.
      */
-    fn init(core: Core, _flags: Self::Flags) -> (Self, Task<cosmic::app::Message<Self::Message>>) {
+    fn init(core: Core, flags: Self::Flags) -> (Self, Task<cosmic::app::Message<Self::Message>>) {
+        // Leaked once, the config subscription needs a 'static ID
+        let config_id: &'static str =
+            Box::leak(instance_config_id(Self::APP_ID, flags.instance.as_deref()).into_boxed_str());
+        let config_helper =
+            match cosmic::cosmic_config::Config::new(config_id, CPUTempAppletConfig::VERSION) {
+                Ok(helper) => Some(helper),
+                Err(err) => {
+                    tracing::error!(?err, "Error opening config");
+                    None
+                }
+            };
+        if let Some(helper) = &config_helper {
+            CPUTempAppletConfig::migrate(config_id, helper);
+        }
+
+        // Loaded before the first reading, so it continues the saved graph
+        let snapshot = config_helper
+            .as_ref()
+            .and_then(|helper| helper.get::<bool>("persist_history").ok())
+            .filter(|persist| *persist)
+            .and_then(|_| persist::snapshot_path(config_id))
+            .and_then(|path| persist::load(&path));
+
         let (period, _) = watch::channel(1000);
+        // Scans the components on its first read, which happens in the background below
+        let sensors = new_provider(SensorBackend::Sysinfo);
 
-        let window = Window {
+        let mut window = Window {
             core, // Set the incoming core
+            config_id,
+            config_writer: ConfigWriter::new(config_id, config_helper),
+            instance: flags.instance,
+            sensor_override: flags.sensor,
             rectangle_tracker: None,
             rectangle: Rectangle::default(),
             refresh_period: period,
-            period_string: "1000".to_string(),
-            temp: get_temp(),
+            sensor_export: watch::channel(socket::document(&[])).0,
+            state: AppletState::new(&CPUTempAppletConfig::default()),
+            sensors: Arc::new(Mutex::new(sensors)),
+            sensor_options: vec![fl!("automatic")],
+            sensor_option_names: vec![fl!("automatic")],
             config: CPUTempAppletConfig::default(),
+            last_reading_at: Some(Instant::now()),
+            history: snapshot
+                .as_ref()
+                .map(|snapshot| TempHistory::restore(snapshot.samples()))
+                .unwrap_or_default(),
+            // Written on the first reading otherwise, which would only repeat what was loaded
+            history_saved_at: snapshot.is_some().then(Instant::now),
+            graph_ranges: graph_range_model(GraphRange::default()),
             ..Default::default() // Set everything else to the default values
         };
 
-        (window, Task::none())
+        window.state.peak = snapshot.as_ref().and_then(Snapshot::peak);
+        window.critical_action_string = window.config.critical_action.clone().unwrap_or_default();
+        window.critical_action_delay_string =
+            window.config.critical_action_delay_seconds.to_string();
+        window.metrics_port_string = window.config.metrics_port.to_string();
+        window.update_log_strings();
+        window.update_quiet_hours_strings();
+        // The panel shows "--" right away instead of waiting for the sensors, enumerating
+        // them can take a few hundred milliseconds with many hwmon devices
+        window.update_panel_text();
+        let task = window.request_reading();
+        let dbus_task = Task::perform(dbus::start(window.instance_suffix()), |connection| {
+            cosmic::app::Message::App(Message::DbusStarted(connection))
+        });
+
+        let probe_task = window.probe_access();
+        let virtualization_task = window.detect_virtualization();
+        (
+            window,
+            Task::batch([task, dbus_task, probe_task, virtualization_task]),
+        )
+    }
+
+    // Create what happens when the applet is closed
+    fn on_close_requested(&self, id: window::Id) -> Option<Message> {
+        // Pass the PopupClosed message to the update function
+        Some(Message::PopupClosed(id))
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        fn time_subscription(
+            mut period_watcher: watch::Receiver<u64>,
+            mut paused_watcher: watch::Receiver<bool>,
+        ) -> Subscription<Message> {
+            Subscription::run_with_id(
+                "time-sub",
+                stream::channel(1, |mut output| async move {
+                    // Mark this receiver's state as changed so that it always receives an initial
+                    // update during the loop below
+                    // This allows us to avoid duplicating code from the loop
+                    period_watcher.mark_changed();
+                    let mut timer = time::interval(time::Duration::from_millis(1000));
+                    timer.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+                    let mut paused = *paused_watcher.borrow();
+
+                    loop {
+                        tokio::select! {
+                            due = timer.tick(), if !paused => {
+                                let tick = Message::Tick(due.into_std());
+                                #[cfg(debug_assertions)]
+                                if let Err(err) = output.send(tick).await {
+                                    tracing::error!(?err, "Failed sending tick request to applet");
+                                }
+                                #[cfg(not(debug_assertions))]
+                                let _ = output.send(tick).await;
+                            },
+                            // Update timer if the user changes the refresh period
+                            Ok(()) = period_watcher.changed() => {
+                                let milliseconds = *period_watcher.borrow();
+                                let time_ms = time::Duration::from_millis(milliseconds);
+                                let start = time::Instant::now() + time_ms;
+                                timer = time::interval_at(start, time_ms);
+
+                                timer.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+                            }
+                            // Stop ticking while the session is locked, nobody can see the value
+                            Ok(()) = paused_watcher.changed() => {
+                                paused = *paused_watcher.borrow();
+                                // The shown value can be hours old on unlock, so read right away
+                                if !paused {
+                                    timer.reset_immediately();
+                                }
+                            }
+                        }
+                    }
+                }),
+            )
+        }
+
+        let period_rx = self.refresh_period.subscribe();
+        let paused_rx = self.paused.subscribe();
+
+        // Only talk to UPower when the power state is actually used
+        let power_subscription = if self.config.battery_saver {
+            Subscription::run_with_id("power-sub", stream::channel(1, power::watch))
+                .map(Message::OnBattery)
+        } else {
+            Subscription::none()
+        };
+
+        // The flash is the only animation so far
+        let motion_subscription = if self.config.flash_on_critical {
+            Subscription::run_with_id("motion-sub", stream::channel(1, motion::watch))
+                .map(Message::ReducedMotion)
+        } else {
+            Subscription::none()
+        };
+
+        // Dropping the subscription stops the server, a new port starts a new one
+        let metrics_subscription = if self.config.metrics_enabled {
+            let port = self.config.metrics_port;
+            let metrics = self.metrics.clone();
+            Subscription::run_with_id(
+                ("metrics-sub", port),
+                stream::channel(1, move |_| metrics::serve(port, metrics)),
+            )
+        } else {
+            Subscription::none()
+        };
+
+        let config_save_subscription = self.config_writer.subscription(Message::WriteConfig);
+
+        // Dropping the subscription stops the writer, another file or size starts a new one
+        let sample_log_subscription = match self
+            .config
+            .log_to_file
+            .as_deref()
+            .filter(|_| self.sample_log_error.is_none())
+            .and_then(sample_log::resolve_path)
+        {
+            Some(path) => {
+                let max_bytes = u64::from(self.config.log_max_size_mb) * 1024 * 1024;
+                Subscription::run_with_id(
+                    ("sample-log-sub", path.clone(), max_bytes),
+                    stream::channel(1, move |output| sample_log::run(path, max_bytes, output)),
+                )
+                .map(Message::SampleLog)
+            }
+            None => Subscription::none(),
+        };
+
+        // Dropping the subscription disconnects, other settings connect again
+        let mqtt_subscription = if self.config.mqtt_enabled && self.mqtt_error.is_none() {
+            let broker = self.config.mqtt_broker.clone();
+            let topic = self.config.mqtt_topic.clone();
+            let credentials_file = self.config.mqtt_credentials_file.clone();
+            Subscription::run_with_id(
+                (
+                    "mqtt-sub",
+                    broker.clone(),
+                    topic.clone(),
+                    credentials_file.clone(),
+                ),
+                stream::channel(1, move |output| {
+                    mqtt::run(broker, topic, credentials_file, output)
+                }),
+            )
+            .map(Message::Mqtt)
+        } else {
+            Subscription::none()
+        };
+
+        // Dropping the subscription stops the server and removes the socket
+        let socket_subscription = match self.socket_path().filter(|_| self.config.socket_enabled) {
+            Some(path) => {
+                let export = self.sensor_export.subscribe();
+                Subscription::run_with_id(
+                    ("socket-sub", path.clone()),
+                    stream::channel(1, move |_| socket::serve(path, export)),
+                )
+            }
+            None => Subscription::none(),
+        };
+
+        // Always running, the signal handlers stay installed once the subscription started.
+        // Saves the history, flushes the sample log and removes the socket first when those
+        // are enabled.
+        let exit_subscription =
+            Subscription::run_with_id("exit-sub", stream::channel(1, persist::watch_exit))
+                .map(|()| Message::Exit);
+
+        let keyboard_subscription = if self.popup.is_some() {
+            event::listen_with(popup_key)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch(vec![
+            power_subscription,
+            motion_subscription,
+            keyboard_subscription,
+            metrics_subscription,
+            sample_log_subscription,
+            mqtt_subscription,
+            socket_subscription,
+            exit_subscription,
+            config_save_subscription,
+            rectangle_tracker_subscription(0).map(|e| Message::Rectangle(e.1)),
+            time_subscription(period_rx, paused_rx),
+            Subscription::run_with_id("hwmon-sub", stream::channel(1, hwmon::watch_chips))
+                .map(|()| Message::SensorsChanged),
+            Subscription::run_with_id("session-sub", stream::channel(1, session::watch)).map(
+                |event| match event {
+                    SessionEvent::Paused(paused) => Message::Paused(paused),
+                    SessionEvent::Resumed => Message::Resumed,
+                },
+            ),
+            self.core.watch_config(self.config_id).map(|u| {
+                for err in u.errors {
+                    tracing::error!(?err, "Error watching config");
+                }
+                Message::ConfigChanged(u.config)
+            }),
+            // The applet context only reads the panel size on start
+            cosmic::cosmic_config::config_subscription::<_, CosmicPanelConfig>(
+                "panel-config-sub",
+                format!("{PANEL_CONFIG_PREFIX}.{}", self.core.applet.panel_type).into(),
+                CosmicPanelConfig::VERSION,
+            )
+            .map(|u| Message::PanelChanged(u.config.size, u.config.anchor)),
+        ])
     }
 
-    // Create what happens when the applet is closed
-    fn on_close_requested(&self, id: window::Id) -> Option<Message> {
-        // Pass the PopupClosed message to the update function
-        Some(Message::PopupClosed(id))
-    }
+    // Here is the update function, it's the one that handles all of the messages that
+    // are passed within the applet.
+    fn update(&mut self, message: Self::Message) -> Task<cosmic::app::Message<Self::Message>> {
+        // match on what message was sent
+        match message {
+            // Handle the TogglePopup message
+            Message::TogglePopup => {
+                // Close the popup, the panel button closes a pinned one as well
+                if self.popup.is_some() {
+                    return self.close_popup();
+                } else {
+                    return Task::batch([self.close_context_menu(), self.open_popup()]);
+                }
+            }
+            Message::ToggleContextMenu => {
+                if self.context_menu.is_some() {
+                    return self.close_context_menu();
+                } else {
+                    return self.open_context_menu();
+                }
+            }
+            // Not saved, a restart always monitors again
+            Message::TogglePause => {
+                self.updates_paused = !self.updates_paused;
+                if self.updates_paused {
+                    // Nothing is measured while paused, nothing to alert about either
+                    self.flash_alert.reset();
+                    self.critical_action_alert = SustainedAlert::default();
+                }
+                self.update_paused();
+                self.update_panel_text();
+                if !self.updates_paused {
+                    return self.refresh_now();
+                }
+            }
+            Message::MenuAction(action) => {
+                let close = self.close_context_menu();
+                let task = match action {
+                    MenuAction::ToggleUnit => {
+                        let unit = units::secondary_unit(self.config.unit);
+                        match TemperatureUnit::ALL.iter().position(|other| *other == unit) {
+                            Some(index) => self.update(Message::Unit(index)),
+                            None => Task::none(),
+                        }
+                    }
+                    MenuAction::ResetPeak => self.update(Message::ResetPeak),
+                    MenuAction::OpenSettings => self.update(Message::OpenSettings),
+                    MenuAction::PauseUpdates => self.update(Message::TogglePause),
+                };
+                return Task::batch([close, task]);
+            }
+            // Unset the popup field after it's been closed
+            Message::PopupClosed(popup_id) => {
+                if self.popup.as_ref() == Some(&popup_id) {
+                    self.popup = None;
+                    // Closed by the compositor while pinned, the focus moved elsewhere. Our
+                    // own closes take the ID first and don't get here.
+                    let reopened_recently = self
+                        .popup_reopened_at
+                        .is_some_and(|at| at.elapsed() < PINNED_REOPEN_GUARD);
+                    if self.popup_pinned && !reopened_recently {
+                        self.popup_reopened_at = Some(Instant::now());
+                        return self.open_popup();
+                    }
+                    self.popup_pinned = false;
+                    self.update_text_hidden();
+                } else if self.context_menu.as_ref() == Some(&popup_id) {
+                    self.context_menu = None;
+                }
+            }
+            // Unpinning closes the popup, like clicking the panel button would
+            Message::TogglePin => {
+                if self.popup_pinned {
+                    return self.update(Message::TogglePopup);
+                }
+                self.popup_pinned = true;
+            }
+            Message::Unit(index) => {
+                let label = self.cpu_label().map(str::to_string);
+                let changed = TemperatureUnit::ALL.get(index).is_some_and(|unit| {
+                    self.state
+                        .set_unit(&mut self.config, *unit, label.as_deref())
+                });
+                if changed {
+                    self.update_panel_text();
+                    self.save_config();
+                }
+            }
+            Message::Rectangle(u) => match u {
+                RectangleUpdate::Rectangle(r) => {
+                    self.rectangle = r.1;
+
+                    let vertical = matches!(
+                        self.core.applet.anchor,
+                        PanelAnchor::Left | PanelAnchor::Right
+                    );
+                    if vertical
+                        && !self.stack_panel_text()
+                        && r.1.width > self.vertical_breadth() + 0.5
+                    {
+                        self.vertical_overflow =
+                            Some((self.panel_text.chars().count(), self.vertical_breadth()));
+                    }
+                }
+                RectangleUpdate::Init(tracker) => {
+                    self.rectangle_tracker = Some(tracker);
+                }
+            },
+            Message::Tick(due) => {
+                self.tick_delays.push(due.elapsed());
+                // Marks the value as stale when readings stopped arriving
+                self.update_panel_text();
+                return self.request_reading();
+            }
+            Message::TempUpdated(reading) => {
+                self.reading_in_flight = false;
+                self.read_durations.push(reading.read_duration);
+                tracing::debug!(
+                    read = ?reading.read_duration,
+                    read_max = ?self.read_durations.summary().map(|summary| summary.max),
+                    tick_delay_max = ?self.tick_delays.summary().map(|summary| summary.max),
+                    "Sensors read"
+                );
+                return self.apply_reading(reading);
+            }
+            Message::DbusStarted(connection) => {
+                self.dbus = connection;
+            }
+            Message::Paused(paused) => {
+                self.session_paused = paused;
+                self.update_paused();
+            }
+            Message::Resumed => {
+                // The old value is shown until there is a trusted one, the pause already
+                // keeps the time asleep from counting as stale
+                self.resume_samples_to_skip = RESUME_SKIPPED_SAMPLES;
+                self.state.restart_smoothing = true;
+                self.display_cadence.reset();
+                self.sensors
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .rescan_on_next_read();
+            }
+            Message::SensorsChanged => {
+                self.sensors
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .rescan_on_next_read();
+                return self.request_reading();
+            }
+            Message::OnBattery(on_battery) => {
+                let plugged_in = self.on_battery && !on_battery;
+                self.on_battery = on_battery;
+                self.update_refresh_period();
+                // The last reading can be a whole battery period old
+                if plugged_in {
+                    return self.request_reading();
+                }
+            }
+            Message::BatterySaver(enabled) => {
+                self.config.battery_saver = enabled;
+                // Not tracked anymore, the next watch starts with the current state
+                if !enabled {
+                    self.on_battery = false;
+                }
+                self.save_config();
+                self.update_refresh_period();
+            }
+            Message::BatteryMultiplier(index) => {
+                if let Some(multiplier) = BATTERY_MULTIPLIERS.get(index) {
+                    self.config.battery_period_multiplier = *multiplier;
+                    self.save_config();
+                    self.update_refresh_period();
+                }
+            }
+            Message::WarningThreshold(input) => {
+                // Empty goes back to the sensor's limit
+                self.threshold_input(ThresholdField::Warning, input);
+            }
+            Message::CriticalThreshold(input) => {
+                self.threshold_input(ThresholdField::Critical, input);
+            }
+            Message::SensorWarningThreshold(input) => {
+                // Empty goes back to the global threshold
+                self.threshold_input(ThresholdField::SensorWarning, input);
+            }
+            Message::SensorCriticalThreshold(input) => {
+                self.threshold_input(ThresholdField::SensorCritical, input);
+            }
+            Message::HideBelow(input) => {
+                // Empty always shows the text
+                if self.threshold_input(ThresholdField::HideBelow, input) {
+                    self.update_panel_text();
+                }
+            }
+            // Applied from the next reading on
+            Message::Offset(input) => {
+                self.threshold_input(ThresholdField::Offset, input);
+            }
+            Message::SensorOffset(input) => {
+                // Empty goes back to the global offset
+                self.threshold_input(ThresholdField::SensorOffset, input);
+            }
+            Message::CriticalNotification(enabled) => {
+                self.config.critical_notification = enabled;
+                self.save_config();
+            }
+            Message::FlashOnCritical(enabled) => {
+                self.config.flash_on_critical = enabled;
+                if !enabled {
+                    self.flash_alert.reset();
+                }
+                self.save_config();
+            }
+            Message::ReducedMotion(reduced) => {
+                self.reduced_motion = reduced;
+            }
+            Message::DisableAnimations(disabled) => {
+                self.config.disable_animations = disabled;
+                self.save_config();
+            }
+            Message::PersistHistory(enabled) => {
+                self.config.persist_history = enabled;
+                self.save_config();
+            }
+            Message::LogToFile(enabled) => {
+                self.config.log_to_file =
+                    enabled.then(|| PathBuf::from(format!("{}.csv", self.config_id)));
+                self.sample_log_error = None;
+                self.update_log_strings();
+                self.save_config();
+            }
+            Message::LogPathString(input) => {
+                self.log_path_string = input;
+            }
+            // Only on submit, every keystroke would start a new file
+            Message::SubmitLogPath => {
+                let path = self.log_path_string.trim();
+                if !path.is_empty() {
+                    self.config.log_to_file = Some(PathBuf::from(path));
+                    self.sample_log_error = None;
+                    self.save_config();
+                }
+            }
+            Message::LogMaxSize(input) => {
+                self.log_max_size_string = input;
+            }
+            // Only on submit, every keystroke would restart the writer with a limit that can
+            // rotate the log away
+            Message::SubmitLogMaxSize => {
+                if let Ok(megabytes) = self.log_max_size_string.trim().parse::<u32>() {
+                    if megabytes > 0 && megabytes != self.config.log_max_size_mb {
+                        self.config.log_max_size_mb = megabytes;
+                        self.sample_log_error = None;
+                        self.save_config();
+                    }
+                }
+            }
+            Message::SampleLog(sample_log::Event::Ready(sender)) => {
+                self.sample_log = Some(sender);
+            }
+            Message::SampleLog(sample_log::Event::Failed(error)) => {
+                self.sample_log = None;
+                self.sample_log_error = Some(error);
+            }
+            Message::WriteConfig(generation) => {
+                self.config_writer
+                    .write_if_current(&self.config, generation);
+            }
+            Message::Exit => {
+                self.config_writer.flush(&self.config);
+                // The process ends right after, so this can't wait for the blocking pool
+                let snapshot_path =
+                    persist::snapshot_path(self.config_id).filter(|_| self.config.persist_history);
+                if let Some(path) = snapshot_path {
+                    if let Err(err) =
+                        persist::save(&path, &Snapshot::new(&self.history, self.state.peak))
+                    {
+                        tracing::error!(?err, "Error saving the history");
+                    }
+                }
+
+                // `exit` skips the server's cleanup
+                if let Some(path) = self.socket_path().filter(|_| self.config.socket_enabled) {
+                    let _ = std::fs::remove_file(path);
+                }
+
+                // Waits a moment for the writer to get the last samples to disk
+                if let Some(sender) = self.sample_log.take() {
+                    let (done, flushed) = tokio::sync::oneshot::channel();
+                    if sender.send(sample_log::Command::Flush(done)).is_ok() {
+                        return Task::perform(
+                            time::timeout(SAMPLE_LOG_FLUSH_TIMEOUT, flushed),
+                            |_| std::process::exit(0),
+                        );
+                    }
+                }
+                std::process::exit(0);
+            }
+            Message::AlertSound(sound) => {
+                // Empty disables the sound
+                self.config.alert_sound = Some(sound).filter(|sound| !sound.trim().is_empty());
+                self.save_config();
+            }
+            // Empty turns the quiet hours off, anything else that isn't a time is ignored
+            Message::QuietHoursStart(input) => {
+                match input.trim() {
+                    "" => self.config.quiet_hours_start = None,
+                    value => {
+                        if let Some(minute) = quiet_hours::parse_time(value) {
+                            self.config.quiet_hours_start = Some(minute);
+                        }
+                    }
+                }
+                self.quiet_hours_start_string = input;
+                self.save_config();
+            }
+            Message::QuietHoursEnd(input) => {
+                match input.trim() {
+                    "" => self.config.quiet_hours_end = None,
+                    value => {
+                        if let Some(minute) = quiet_hours::parse_time(value) {
+                            self.config.quiet_hours_end = Some(minute);
+                        }
+                    }
+                }
+                self.quiet_hours_end_string = input;
+                self.save_config();
+            }
+            Message::TestSound => {
+                if let Some(sound) = self.config.alert_sound.clone() {
+                    return Task::perform(sound::play(sound), |result| {
+                        cosmic::app::Message::App(Message::SoundFinished(result))
+                    });
+                }
+            }
+            Message::SoundFinished(result) => {
+                self.sound_error = result.err().map(|error| fl!("sound-failed", error = error));
+            }
+            Message::CriticalAction(input) => {
+                self.critical_action_string = input;
+            }
+            Message::SubmitCriticalAction => {
+                // Empty disables the action
+                let command = Some(self.critical_action_string.trim().to_string())
+                    .filter(|command| !command.is_empty());
+                if self.config.critical_action != command {
+                    self.config.critical_action = command;
+                    self.save_config();
+                }
+            }
+            Message::CriticalActionDelay(input) => {
+                if let Ok(seconds) = input.trim().parse::<u64>() {
+                    self.config.critical_action_delay_seconds = seconds;
+                    self.save_config();
+                }
+
+                self.critical_action_delay_string = input;
+            }
+            Message::MetricsEnabled(enabled) => {
+                self.config.metrics_enabled = enabled;
+                self.save_config();
+            }
+            Message::MetricsPort(input) => {
+                self.metrics_port_string = input;
+            }
+            // Only on submit, every keystroke would bind another port
+            Message::SubmitMetricsPort => {
+                // Port 0 would bind to a random port nobody knows about
+                if let Ok(port) = self.metrics_port_string.trim().parse::<u16>() {
+                    if port != 0 && port != self.config.metrics_port {
+                        self.config.metrics_port = port;
+                        self.save_config();
+                    }
+                }
+            }
+            Message::MqttEnabled(enabled) => {
+                self.config.mqtt_enabled = enabled;
+                self.mqtt_error = None;
+                if !enabled {
+                    self.mqtt = None;
+                }
+                self.save_config();
+            }
+            Message::SocketEnabled(enabled) => {
+                self.config.socket_enabled = enabled;
+                self.save_config();
+                // Fresh components for the first client
+                if enabled {
+                    return self.request_reading();
+                }
+            }
+            Message::Mqtt(mqtt::Event::Ready(publisher)) => {
+                self.mqtt = Some(publisher);
+                self.mqtt_samples = 0;
+            }
+            Message::Mqtt(mqtt::Event::Failed(error)) => {
+                self.mqtt = None;
+                self.mqtt_error = Some(error);
+            }
+            Message::CriticalActionFinished(result) => {
+                self.critical_action_error = result
+                    .err()
+                    .map(|error| fl!("critical-action-failed", error = error));
+            }
+            Message::LaunchMonitor => {
+                let command = self
+                    .config
+                    .monitor_command
+                    .clone()
+                    .or_else(|| monitor::find_default().map(str::to_string));
+                let result = match command {
+                    Some(command) => monitor::launch(&command),
+                    None => {
+                        tracing::error!("No system monitor installed");
+                        Err(fl!("no-system-monitor"))
+                    }
+                };
+
+                if let Err(error) = result {
+                    self.monitor_error = Some(fl!("monitor-launch-failed", error = error));
+                    self.monitor_error_generation += 1;
+                    let generation = self.monitor_error_generation;
+                    return Task::perform(time::sleep(MONITOR_ERROR_DURATION), move |_| {
+                        cosmic::app::Message::App(Message::MonitorErrorExpired(generation))
+                    });
+                }
+            }
+            Message::MonitorCommand(command) => {
+                // Empty goes back to the default monitor
+                self.config.monitor_command =
+                    Some(command).filter(|command| !command.trim().is_empty());
+                self.save_config();
+            }
+            Message::RefreshNow => {
+                return Task::batch([
+                    self.refresh_now(),
+                    self.probe_access(),
+                    self.detect_virtualization(),
+                ]);
+            }
+            Message::AccessProbed(problem) => {
+                if let Some(problem) = &problem {
+                    tracing::warn!(path = %problem.path.display(), error = %problem.error, "Sensors can't be read");
+                }
+                self.access_problem = problem;
+            }
+            Message::VirtualizationDetected(detected) => {
+                self.virtual_machine = detected;
+                self.update_panel_text();
+            }
+            Message::OpenSettings => {
+                if let Err(err) = settings_app::launch(self.instance.as_deref()) {
+                    tracing::error!(?err, "Failed to open the settings window");
+                }
+            }
+            Message::CopyReadings => {
+                self.copied = true;
+                self.copied_generation += 1;
+                let generation = self.copied_generation;
+                return Task::batch([
+                    cosmic::iced::clipboard::write(format_readings(
+                        &self.sensor_temps,
+                        self.state.peak,
+                    )),
+                    Task::perform(time::sleep(COPIED_DURATION), move |_| {
+                        cosmic::app::Message::App(Message::CopiedExpired(generation))
+                    }),
+                ]);
+            }
+            Message::CopiedExpired(generation) => {
+                if generation == self.copied_generation {
+                    self.copied = false;
+                }
+            }
+            Message::MonitorErrorExpired(generation) => {
+                if generation == self.monitor_error_generation {
+                    self.monitor_error = None;
+                }
+            }
+            Message::ShowLoad(enabled) => {
+                self.config.show_load = enabled;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::LoadWithoutSensors(enabled) => {
+                self.config.load_without_sensors = enabled;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::ShowFan(enabled) => {
+                self.config.show_fan = enabled;
+                // The first reading fills them in
+                self.fans.clear();
+                self.update_panel_text();
+                self.save_config();
+                if enabled {
+                    return self.request_reading();
+                }
+            }
+            Message::ShowThrottling(enabled) => {
+                self.config.show_throttling = enabled;
+                // The count starts over when it's turned on again
+                self.throttle = None;
+                *self
+                    .throttle_monitor
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner) = ThrottleMonitor::default();
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::ShowHotCore(enabled) => {
+                self.config.show_hot_core = enabled;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::GraphRange(entity) => {
+                if let Some(range) = self.graph_ranges.data::<GraphRange>(entity).copied() {
+                    self.graph_ranges.activate(entity);
+                    self.config.graph_range = range;
+                    self.save_config();
+                }
+            }
+            Message::GraphFixedScale(enabled) => {
+                self.config.graph_fixed_scale = enabled;
+                self.save_config();
+            }
+            Message::ShowPower(enabled) => {
+                self.config.show_power = enabled;
+                self.reset_power();
+                self.update_panel_text();
+                self.save_config();
+                if enabled {
+                    return self.request_reading();
+                }
+            }
+            Message::PowerInPanel(enabled) => {
+                self.config.power_in_panel = enabled;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::ShowBothUnits(enabled) => {
+                self.config.show_both_units = enabled;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::ShowIcon(enabled) => {
+                self.config.show_icon = enabled;
+                self.save_config();
+            }
+            Message::ThresholdIndication(index) => {
+                if let Some(indication) = ThresholdIndication::ALL.get(index) {
+                    self.config.threshold_indication = *indication;
+                    self.save_config();
+                }
+            }
+            Message::PanelStyle(index) => {
+                if let Some(style) = PanelStyle::ALL.get(index) {
+                    self.config.panel_style = *style;
+                    // Measured for the text, which isn't there anymore or wasn't before
+                    self.vertical_overflow = None;
+                    self.save_config();
+                }
+            }
+            Message::FixedWidth(enabled) => {
+                self.config.fixed_width = enabled;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::PanelFormat(format) => {
+                self.config.panel_format = format;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::PanelPrefix(prefix) => {
+                self.config.panel_prefix = prefix;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::PanelSuffix(suffix) => {
+                self.config.panel_suffix = suffix;
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::DisplayMode(index) => {
+                if let Some(mode) = DisplayMode::ALL.get(index) {
+                    self.config.display_mode = *mode;
+                    self.update_panel_text();
+                    self.save_config();
+
+                    // The hottest sensor is only looked for in its own mode
+                    if *mode == DisplayMode::HottestAny {
+                        return self.request_reading();
+                    }
+                }
+            }
+            Message::DecimalPlaces(index) => {
+                self.config.decimal_places = (index as u8).min(MAX_DECIMAL_PLACES);
+                self.update_panel_text();
+                self.save_config();
+            }
+            Message::FocusNext => {
+                return focus_next();
+            }
+            Message::FocusPrevious => {
+                return focus_previous();
+            }
+            Message::PanelScroll(delta) => {
+                // Touchpads send many small pixel deltas, those are added up into steps
+                let step = match delta {
+                    // `signum` would turn horizontal scrolling (y = 0) into a step
+                    ScrollDelta::Lines { y, .. } if y > 0.0 => -1,
+                    ScrollDelta::Lines { y, .. } if y < 0.0 => 1,
+                    ScrollDelta::Lines { .. } => 0,
+                    ScrollDelta::Pixels { y, .. } => {
+                        self.scroll_pixels += y;
+                        let steps = (self.scroll_pixels / SCROLL_STEP_PIXELS).trunc();
+                        self.scroll_pixels -= steps * SCROLL_STEP_PIXELS;
+                        -steps as i32
+                    }
+                };
 
-    fn subscription(&self) -> Subscription<Message> {
-        fn time_subscription(mut period_watcher: watch::Receiver<u64>) -> Subscription<Message> {
-            Subscription::run_with_id(
-                "time-sub",
-                stream::channel(1, |mut output| async move {
-                    // Mark this receiver's state as changed so that it always receives an initial
-                    // update during the loop below
-                    // This allows us to avoid duplicating code from the loop
-                    period_watcher.mark_changed();
-                    let mut timer = time::interval(time::Duration::from_millis(1000));
-                    timer.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
+                if step != 0 {
+                    return self.cycle_sensor(step);
+                }
+            }
+            Message::ResetPeak => {
+                self.state.reset_peak();
+            }
+            Message::SensorBackend(index) => {
+                if let Some(backend) = SensorBackend::ALL.get(index) {
+                    self.config.sensor_backend = *backend;
+                    self.save_config();
 
-                    loop {
-                        tokio::select! {
-                            _ = timer.tick() => {
-                                #[cfg(debug_assertions)]
-                                if let Err(err) = output.send(Message::Tick).await {
-                                    tracing::error!(?err, "Failed sending tick request to applet");
-                                }
-                                #[cfg(not(debug_assertions))]
-                                let _ = output.send(Message::Tick).await;
-                            },
-                            // Update timer if the user changes the refresh period
-                            Ok(()) = period_watcher.changed() => {
-                                let milliseconds = *period_watcher.borrow();
-                                let time_ms = time::Duration::from_millis(milliseconds);
-                                let start = time::Instant::now() + time_ms;
-                                timer = time::interval_at(start, time_ms);
+                    return self.switch_backend();
+                }
+            }
+            Message::CoreAggregation(index) => {
+                if let Some(aggregation) = CoreAggregation::ALL.get(index) {
+                    self.config.core_aggregation = *aggregation;
+                    self.save_config();
 
-                                timer.set_missed_tick_behavior(time::MissedTickBehavior::Skip);
-                            }
-                        }
-                    }
-                }),
-            )
-        }
+                    return self.request_reading();
+                }
+            }
+            Message::HottestCcd(enabled) => {
+                self.config.hottest_ccd = enabled;
+                self.save_config();
 
-        let period_rx = self.refresh_period.subscribe();
+                return self.request_reading();
+            }
+            Message::PreferTctl(enabled) => {
+                self.config.prefer_tctl = enabled;
+                self.save_config();
 
-        Subscription::batch(vec![
-            rectangle_tracker_subscription(0).map(|e| Message::Rectangle(e.1)),
-            time_subscription(period_rx),
-            self.core.watch_config(Self::APP_ID).map(|u| {
-                for err in u.errors {
-                    tracing::error!(?err, "Error watching config");
+                return self.request_reading();
+            }
+            Message::Smoothing(index) => {
+                if let Some(smoothing) = SMOOTHING_FACTORS.get(index) {
+                    self.config.smoothing = *smoothing;
+                    self.save_config();
                 }
-                Message::ConfigChanged(u.config)
-            }),
-        ])
-    }
+            }
+            Message::RoundingStep(index) => {
+                if let Some(step) = ROUNDING_STEPS.get(index) {
+                    self.config.rounding_step = *step;
+                    self.update_panel_text();
+                    self.save_config();
+                }
+            }
+            Message::ShowDriveTemps(enabled) => {
+                self.config.show_drive_temps = enabled;
+                self.save_config();
 
-    // Here is the update function, it's the one that handles all of the messages that
-    // are passed within the applet.
-    fn update(&mut self, message: Self::Message) -> Task<cosmic::app::Message<Self::Message>> {
-        // match on what message was sent
-        match message {
-            // Handle the TogglePopup message
-            Message::TogglePopup => {
-                // Close the popup
-                if let Some(popup_id) = self.popup.take() {
-                    return destroy_popup(popup_id);
-                } else if let Some(main_window_id) = self.core.main_window_id() {
-                    // Create and "open" the popup
-                    let new_id = Id::unique();
-                    self.popup.replace(new_id);
-
-                    let mut popup_settings = self.core.applet.get_popup_settings(
-                        main_window_id,
-                        new_id,
-                        None,
-                        None,
-                        None,
-                    );
+                if !enabled {
+                    self.drive_temps.clear();
+                    return Task::none();
+                }
+                return self.request_reading();
+            }
+            Message::ToggleAllSensors => {
+                self.show_all_sensors = !self.show_all_sensors;
+                if !self.show_all_sensors {
+                    self.all_sensors.clear();
+                    return Task::none();
+                }
+                return self.request_reading();
+            }
+            Message::ToggleDiagnostics => {
+                self.show_diagnostics = !self.show_diagnostics;
+            }
+            Message::CopyDiagnostics => {
+                let text = self
+                    .diagnostics()
+                    .into_iter()
+                    .map(|(label, value)| format!("{label}: {value}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                return cosmic::iced::clipboard::write(text);
+            }
+            Message::SelectSensor(index) => {
+                self.config.selected_sensor = if index == 0 {
+                    None
+                } else {
+                    self.sensor_options.get(index).cloned()
+                };
+                self.sensor_override = None;
 
-                    let Rectangle {
-                        x,
-                        y,
-                        width,
-                        height,
-                    } = self.rectangle;
+                self.save_config();
 
-                    popup_settings.positioner.anchor_rect = Rectangle::<i32> {
-                        x: x.max(1.) as i32,
-                        y: y.max(1.) as i32,
-                        width: width.max(1.) as i32,
-                        height: height.max(1.) as i32,
-                    };
+                return self.request_reading();
+            }
+            Message::IgnoreSensor(label) => {
+                if !self.config.sensor_blacklist.contains(&label) {
+                    self.config.sensor_blacklist.push(label.clone());
+                }
+                if self.config.selected_sensor.as_ref() == Some(&label) {
+                    self.config.selected_sensor = None;
+                }
+                if self.sensor_override.as_ref() == Some(&label) {
+                    self.sensor_override = None;
+                }
+                self.save_config();
 
-                    popup_settings.positioner.size = Some((300, 500));
+                // Out of the list right away, the next reading doesn't report it anymore
+                self.sensor_labels.retain(|other| *other != label);
+                self.update_sensor_options();
+                self.display_cadence.reset();
+                return self.request_reading();
+            }
+            Message::UnignoreSensor(pattern) => {
+                self.config
+                    .sensor_blacklist
+                    .retain(|other| *other != pattern);
+                self.save_config();
 
-                    return get_popup(popup_settings);
-                }
+                self.display_cadence.reset();
+                return self.request_reading();
             }
-            // Unset the popup field after it's been closed
-            Message::PopupClosed(popup_id) => {
-                if self.popup.as_ref() == Some(&popup_id) {
-                    self.popup = None;
+            Message::PeriodString(field, input) => {
+                // Saving is debounced, otherwise typing "15000" would write the config and
+                // restart the timer for 1, 15, 150, 1500 and 15000
+                let generation = self.state.period_input(field, input);
+                return Task::perform(time::sleep(PERIOD_COMMIT_DELAY), move |_| {
+                    cosmic::app::Message::App(Message::CommitPeriod(field, generation))
+                });
+            }
+            Message::SubmitPeriod(field) => {
+                if self.state.submit_period(&mut self.config, field) {
+                    self.save_config();
                 }
             }
-            Message::Fahrenheit(fahrenheit) => {
-                self.config.fahrenheit = fahrenheit;
-                if let Ok(helper) =
-                    cosmic::cosmic_config::Config::new(Self::APP_ID, CPUTempAppletConfig::VERSION)
+            Message::CommitPeriod(field, generation) => {
+                if self
+                    .state
+                    .commit_period_if_current(&mut self.config, field, generation)
                 {
-                    if let Err(err) = self.config.write_entry(&helper) {
-                        tracing::error!(?err, "Error writing config");
-                    }
+                    self.save_config();
                 }
             }
-            Message::Rectangle(u) => match u {
-                RectangleUpdate::Rectangle(r) => {
-                    self.rectangle = r.1;
-                }
-                RectangleUpdate::Init(tracker) => {
-                    self.rectangle_tracker = Some(tracker);
+            Message::PanelChanged(size, anchor) => {
+                // Other panel settings come through here as well
+                let moved = self.core.applet.anchor != anchor
+                    || matches!(
+                        &self.core.applet.size,
+                        cosmic::applet::Size::PanelSize(current) if *current != size
+                    );
+                // A size from the environment wins, like in the applet context
+                if matches!(self.core.applet.size, cosmic::applet::Size::PanelSize(_)) {
+                    self.core.applet.size = cosmic::applet::Size::PanelSize(size);
                 }
-            },
-            Message::Tick => {
-                self.temp = get_temp();
-            }
-            Message::PeriodString(input) => {
-                match input.parse::<u64>() {
-                    Ok(valid_int) => {
-                        if valid_int >= 500 {
-                            self.config.refresh_period_milliseconds = valid_int.clone();
-                            if let Ok(helper) = cosmic::cosmic_config::Config::new(
-                                Self::APP_ID,
-                                CPUTempAppletConfig::VERSION,
-                            ) {
-                                if let Err(err) = self.config.write_entry(&helper) {
-                                    tracing::error!(?err, "Error writing config");
-                                }
-                            }
-                        } else {
-                            // TODO: Error handling
-                        }
-                    }
-                    Err(_) => {
-                        // TODO parse handling
-                    }
+                self.core.applet.anchor = anchor;
+                // Measured for the old size, `view` picks up the new paddings by itself
+                self.vertical_overflow = None;
+                self.update_panel_text();
+                // Placed for the old panel, pinned or not
+                if moved {
+                    return Task::batch([self.close_popup(), self.close_context_menu()]);
                 }
-
-                self.period_string = input;
             }
             Message::ConfigChanged(c) => {
-                // Don't interrupt the tick subscription unless necessary
-                self.refresh_period
-                    .send_if_modified(|refresh_period_milliseconds| {
-                        if *refresh_period_milliseconds == c.refresh_period_milliseconds {
-                            false
-                        } else {
-                            *refresh_period_milliseconds = c.refresh_period_milliseconds;
-                            self.period_string = c.refresh_period_milliseconds.to_string();
-                            true
-                        }
-                    });
+                let label = self.cpu_label().map(str::to_string);
+                self.state.config_changed(
+                    &self.config,
+                    &c,
+                    !self.config_writer.is_dirty(),
+                    label.as_deref(),
+                );
+                // A change that's still waiting to be written is newer, and would overwrite
+                // this one anyway
+                if self.config_writer.is_dirty() {
+                    return Task::none();
+                }
+                let sensor_changed = self.config.selected_sensor != c.selected_sensor;
+                let backend_changed = self.config.sensor_backend != c.sensor_backend;
+                let aggregation_changed = self.config.core_aggregation != c.core_aggregation
+                    || self.config.hottest_ccd != c.hottest_ccd
+                    || self.config.prefer_tctl != c.prefer_tctl;
+                if self.config.critical_action != c.critical_action {
+                    self.critical_action_string = c.critical_action.clone().unwrap_or_default();
+                }
+                if self.config.critical_action_delay_seconds != c.critical_action_delay_seconds {
+                    self.critical_action_delay_string = c.critical_action_delay_seconds.to_string();
+                }
+                if !c.flash_on_critical {
+                    self.flash_alert.reset();
+                }
+                if self.config.metrics_port != c.metrics_port {
+                    self.metrics_port_string = c.metrics_port.to_string();
+                }
+                let log_changed = self.config.log_to_file != c.log_to_file
+                    || self.config.log_max_size_mb != c.log_max_size_mb;
+                // Measured for the text, the tracker reports the new size by itself
+                if self.config.panel_style != c.panel_style {
+                    self.vertical_overflow = None;
+                }
+                if self.config.graph_range != c.graph_range {
+                    self.graph_ranges = graph_range_model(c.graph_range);
+                }
+                if self.config.show_power != c.show_power {
+                    self.reset_power();
+                }
+                let quiet_hours_changed = self.config.quiet_hours_start != c.quiet_hours_start
+                    || self.config.quiet_hours_end != c.quiet_hours_end;
+                let mqtt_changed = self.config.mqtt_enabled != c.mqtt_enabled
+                    || self.config.mqtt_broker != c.mqtt_broker
+                    || self.config.mqtt_topic != c.mqtt_topic
+                    || self.config.mqtt_credentials_file != c.mqtt_credentials_file;
                 self.config = c;
+                if log_changed {
+                    self.sample_log_error = None;
+                    self.update_log_strings();
+                }
+                if quiet_hours_changed {
+                    self.update_quiet_hours_strings();
+                }
+                // The old client is torn down with its subscription
+                if mqtt_changed {
+                    self.mqtt = None;
+                    self.mqtt_error = None;
+                }
+                self.update_refresh_period();
+                self.update_panel_text();
+                if sensor_changed && self.popup.is_some() {
+                    self.update_sensor_options();
+                }
+                if backend_changed {
+                    return self.switch_backend();
+                }
+                if sensor_changed || aggregation_changed {
+                    self.display_cadence.reset();
+                    return self.request_reading();
+                }
             }
         }
 
@@ -297,24 +3053,72 @@ impl cosmic::Application for Window {
             PanelAnchor::Top | PanelAnchor::Bottom
         );
 
-        let mut temp: String = "--".to_string();
-        if let Some(temp_value) = self.temp {
-            if self.config.fahrenheit {
-                temp = format!("{:.0}", convert_to_fahrenheit(temp_value));
-            } else {
-                temp = format!("{:.0}", temp_value);
+        #[cfg(debug_assertions)]
+        {
+            let views = PANEL_VIEWS.fetch_add(1, Ordering::Relaxed) + 1;
+            if views % 1000 == 0 {
+                let formats = PANEL_TEXT_FORMATS.load(Ordering::Relaxed);
+                let unchanged = PANEL_TEXT_UNCHANGED.load(Ordering::Relaxed);
+                tracing::debug!(
+                    views,
+                    formats,
+                    changed = formats - unchanged,
+                    unchanged,
+                    "Panel text formats"
+                );
             }
-            temp.push_str("°");
         }
 
+        let panel_text = if !horizontal && self.stack_panel_text() {
+            self.stacked_panel_text.as_str()
+        } else {
+            self.panel_text.as_str()
+        };
+        let mut temp_text = self
+            .core
+            .applet
+            .text(panel_text)
+            .align_x(cosmic::iced::alignment::Horizontal::Center);
+        // Digits of the default font aren't all the same width, the padding alone isn't enough
+        if self.config.fixed_width {
+            temp_text = temp_text.font(cosmic::font::mono());
+        }
+        if let Some(color) = self.temp_color() {
+            temp_text = temp_text.class(cosmic::theme::Text::Color(color));
+        }
+
+        // Sized like any other applet icon, so it lines up with the rest of the panel
+        let (icon_width, icon_height) = self.core.applet.suggested_size(true);
+        // Always the same size, so swapping icons doesn't move anything in the panel
+        let icon_only = self.config.panel_style == PanelStyle::IconOnly;
+        // Without the text the icon is all that tells how hot it is
+        let changes_icon = self.config.threshold_indication.changes_icon() || icon_only;
+        let show_text = !self.text_hidden && !icon_only;
+        // Something has to stay in the panel to open the popup with while the text is hidden
+        let thermometer = (self.config.show_icon || changes_icon || !show_text).then(|| {
+            let level = if changes_icon {
+                self.temp_level()
+            } else {
+                TempLevel::Normal
+            };
+            icon::icon(LEVEL_ICONS[level as usize].clone())
+                .size(icon_width.min(icon_height))
+                .width(Length::Fixed(icon_width as f32))
+                .height(Length::Fixed(icon_height as f32))
+        });
+
+        // The icon is part of the button, so the rectangle tracker reports the combined size
+        let spacing = self.core.applet.suggested_padding(true);
         let button = button::custom(if horizontal {
             Element::from(
                 row!(
-                    self.core.applet.text(temp),
+                    row![]
+                        .push_maybe(thermometer)
+                        .push_maybe(show_text.then_some(temp_text))
+                        .spacing(spacing)
+                        .align_y(Alignment::Center),
                     container(vertical_space().height(Length::Fixed(
-                        (self.core.applet.suggested_size(true).1
-                            + 2 * self.core.applet.suggested_padding(true))
-                            as f32
+                        (icon_height + 2 * self.core.applet.suggested_padding(true)) as f32
                     )))
                 )
                 .align_y(Alignment::Center),
@@ -322,11 +3126,13 @@ impl cosmic::Application for Window {
         } else {
             Element::from(
                 column!(
-                    self.core.applet.text(temp),
+                    column![]
+                        .push_maybe(thermometer)
+                        .push_maybe(show_text.then_some(temp_text))
+                        .spacing(spacing)
+                        .align_x(Alignment::Center),
                     container(horizontal_space().width(Length::Fixed(
-                        (self.core.applet.suggested_size(true).0
-                            + 2 * self.core.applet.suggested_padding(true))
-                            as f32
+                        (icon_width + 2 * self.core.applet.suggested_padding(true)) as f32
                     )))
                 )
                 .align_x(Alignment::Center),
@@ -338,7 +3144,33 @@ impl cosmic::Application for Window {
             [self.core.applet.suggested_padding(true), 0]
         })
         .on_press_down(Message::TogglePopup)
-        .class(cosmic::theme::Button::AppletIcon);
+        .class(
+            // Checks the option as well, so turning it off stops the flashing right away
+            if self.config.flash_on_critical
+                && self.flash_alert.highlighted(self.motion().animate())
+            {
+                cosmic::theme::Button::Destructive
+            } else {
+                cosmic::theme::Button::AppletIcon
+            },
+        );
+        // Scrolling flips through the sensors, like volume applets do for outputs
+        let button = mouse_area(button)
+            .on_scroll(Message::PanelScroll)
+            .on_middle_press(Message::LaunchMonitor)
+            .on_right_press(Message::ToggleContextMenu);
+        // Inside the tracker, so the popup is still placed against the whole panel item
+        let button = if icon_only {
+            let position = match self.core.applet.anchor {
+                PanelAnchor::Top => tooltip::Position::Bottom,
+                PanelAnchor::Bottom => tooltip::Position::Top,
+                PanelAnchor::Left => tooltip::Position::Right,
+                PanelAnchor::Right => tooltip::Position::Left,
+            };
+            Element::from(tooltip(button, text(self.panel_tooltip()), position))
+        } else {
+            Element::from(button)
+        };
 
         autosize::autosize(
             if let Some(tracker) = self.rectangle_tracker.as_ref() {
@@ -352,25 +3184,902 @@ impl cosmic::Application for Window {
     }
 
     // The actual GUI window for the applet. It's a popup.
-    fn view_window(&self, _id: Id) -> Element<Self::Message> {
+    fn view_window(&self, id: Id) -> Element<Self::Message> {
+        if self.context_menu == Some(id) {
+            return self.view_context_menu();
+        }
+
+        let period_error = self.state.period_error.and_then(period_hint).map(|error| {
+            let color = self.core.system_theme().cosmic().destructive_color();
+            text::caption(error).class(cosmic::theme::Text::Color(color.into()))
+        });
+        let thresholds_error = self
+            .state
+            .thresholds_out_of_order(&self.config, self.cpu_label())
+            .then(|| {
+                let color = self.core.system_theme().cosmic().destructive_color();
+                text::caption(fl!("thresholds-out-of-order"))
+                    .class(cosmic::theme::Text::Color(color.into()))
+            });
+        let display_period_error = self
+            .state
+            .display_period_error
+            .and_then(period_hint)
+            .or_else(|| {
+                self.state
+                    .display_period_too_short(&self.config)
+                    .then(|| fl!("display-interval-too-short"))
+            })
+            .map(|error| {
+                let color = self.core.system_theme().cosmic().destructive_color();
+                text::caption(error).class(cosmic::theme::Text::Color(color.into()))
+            });
+        // "--" alone looks like the applet is broken, say why when it's a setup problem
+        let access_problem = self
+            .access_problem
+            .as_ref()
+            .filter(|_| self.state.temp.is_none() || self.read_error.is_some())
+            .map(|problem| {
+                let color = self.core.system_theme().cosmic().destructive_color();
+                text::caption(fl!(
+                    "sensor-access-denied",
+                    error = problem.error.as_str(),
+                    path = problem.path.display().to_string()
+                ))
+                .class(cosmic::theme::Text::Color(color.into()))
+            });
+        // Offered only where it applies, it changes nothing anywhere else
+        let no_sensors_in_vm = self.no_sensors_in_vm().then(|| {
+            column![
+                text::caption(fl!("no-sensors-in-vm")),
+                settings::item(
+                    fl!("load-without-sensors"),
+                    toggler(self.config.load_without_sensors)
+                        .on_toggle(Message::LoadWithoutSensors),
+                ),
+            ]
+        });
+        let read_error = self.read_error.as_deref().map(|error| {
+            let color = self.core.system_theme().cosmic().warning_color();
+            settings::item(
+                fl!("sensor-error"),
+                text::caption(error).class(cosmic::theme::Text::Color(color.into())),
+            )
+        });
+        // A single failure can be a hiccup, a read-only home fails every time
+        let config_write_error = self
+            .config_writer
+            .error()
+            .as_deref()
+            .filter(|_| self.config_writer.failures() >= CONFIG_WRITE_FAILURES_SHOWN)
+            .map(|error| {
+                let color = self.core.system_theme().cosmic().destructive_color();
+                settings::item(
+                    fl!("config-write-failed", count = self.config_writer.failures()),
+                    text::caption(error).class(cosmic::theme::Text::Color(color.into())),
+                )
+            });
+        let stale = self.stale_for().map(|elapsed| {
+            let color = self.core.system_theme().cosmic().warning_color();
+            let seconds = elapsed.as_secs();
+            let message = match &self.cpu_sensor {
+                Some(sensor) => fl!(
+                    "no-reading-for-sensor",
+                    seconds = seconds,
+                    sensor = describe_cpu_sensor(sensor)
+                ),
+                None => fl!("no-reading-for", seconds = seconds),
+            };
+            text::caption(message).class(cosmic::theme::Text::Color(color.into()))
+        });
+
         // A text box to show if we've enabled or disabled anything in the model
-        let content_list = column![
+        // Tells which component the value comes from, for when it looks off
+        let source = match (&self.cpu_sensor, &self.read_error) {
+            (Some(sensor), _) => Some(fl!("source", source = describe_cpu_sensor(sensor))),
+            (None, None) => Some(fl!("no-cpu-sensor")),
+            // The read error explains it already
+            (None, Some(_)) => None,
+        };
+        let title = column![text::heading(fl!("cpu-temperature"))]
+            .push_maybe(source.map(text::caption))
+            .push_maybe(
+                self.cpu_alternative
+                    .as_deref()
+                    .map(|sensor| text::caption(fl!("cpu-alternative", sensor = sensor))),
+            )
+            .push_maybe(
+                self.selected_sensor()
+                    .map(String::as_str)
+                    .filter(|_| self.selected_missing)
+                    .map(|sensor| text::caption(fl!("selected-sensor-missing", sensor = sensor))),
+            )
+            .spacing(4);
+        let header = row![
+            title,
+            horizontal_space(),
+            button::icon(icon::from_name(PIN_ICON))
+                .tooltip(if self.popup_pinned {
+                    fl!("unpin-popup")
+                } else {
+                    fl!("pin-popup")
+                })
+                .selected(self.popup_pinned)
+                .on_press(Message::TogglePin),
+            button::icon(icon::from_name(REFRESH_ICON))
+                .tooltip(fl!("refresh-now"))
+                .on_press(Message::RefreshNow),
+        ]
+        .align_y(Alignment::Center);
+
+        let mut content_list = column![
+            header,
             settings::item(
-                "Fahrenheit",
-                toggler(self.config.fahrenheit).on_toggle(Message::Fahrenheit),
+                fl!("unit"),
+                dropdown(
+                    UNIT_OPTIONS.as_slice(),
+                    TemperatureUnit::ALL
+                        .iter()
+                        .position(|unit| *unit == self.config.unit),
+                    Message::Unit,
+                ),
             ),
             settings::item(
-                "Refresh Interval (ms)",
-                text_input("1000", self.period_string.clone()).on_input(Message::PeriodString),
-            )
+                fl!("refresh-interval"),
+                text_input("1000", self.state.period_edit.text())
+                    .id(PERIOD_INPUT_ID.clone())
+                    .on_input(|input| Message::PeriodString(PeriodField::Sample, input))
+                    .on_submit(Message::SubmitPeriod(PeriodField::Sample)),
+            ),
         ]
-        .padding(self.core.applet.suggested_padding(true))
-        .spacing(8);
+        .push_maybe(period_error)
+        .push(settings::item(
+            fl!("display-interval"),
+            text_input("1000", self.state.display_period_edit.text())
+                .on_input(|input| Message::PeriodString(PeriodField::Display, input))
+                .on_submit(Message::SubmitPeriod(PeriodField::Display)),
+        ))
+        .push_maybe(display_period_error)
+        .push(settings::item(
+            fl!("slower-refresh-on-battery"),
+            toggler(self.config.battery_saver).on_toggle(Message::BatterySaver),
+        ))
+        .push_maybe(self.config.battery_saver.then(|| {
+            settings::item(
+                fl!("battery-refresh-multiplier"),
+                dropdown(
+                    BATTERY_MULTIPLIER_OPTIONS,
+                    BATTERY_MULTIPLIERS.iter().position(|multiplier| {
+                        *multiplier == self.config.battery_period_multiplier
+                    }),
+                    Message::BatteryMultiplier,
+                ),
+            )
+        }))
+        .push(settings::item(
+            fl!("warning-threshold", unit = self.unit_symbol()),
+            text_input(
+                self.threshold_placeholder(self.default_warning_threshold()),
+                self.state.warning_string.as_str(),
+            )
+            .on_input(Message::WarningThreshold),
+        ))
+        .push(settings::item(
+            fl!("critical-threshold", unit = self.unit_symbol()),
+            text_input(
+                self.threshold_placeholder(self.default_critical_threshold()),
+                self.state.critical_string.as_str(),
+            )
+            .on_input(Message::CriticalThreshold),
+        ))
+        .push_maybe(match &self.cpu_sensor {
+            Some(CpuSensor::Component { label, chip }) => Some(text::caption_heading(fl!(
+                "sensor-thresholds",
+                sensor = sensor_name(label, chip.as_deref())
+            ))),
+            _ => None,
+        })
+        .push_maybe(self.cpu_label().map(|_| {
+            settings::item(
+                fl!("warning-threshold", unit = self.unit_symbol()),
+                text_input(
+                    self.threshold_placeholder(self.default_warning_threshold()),
+                    self.state.sensor_warning_string.as_str(),
+                )
+                .on_input(Message::SensorWarningThreshold),
+            )
+        }))
+        .push_maybe(self.cpu_label().map(|_| {
+            settings::item(
+                fl!("critical-threshold", unit = self.unit_symbol()),
+                text_input(
+                    self.threshold_placeholder(self.default_critical_threshold()),
+                    self.state.sensor_critical_string.as_str(),
+                )
+                .on_input(Message::SensorCriticalThreshold),
+            )
+        }))
+        // Under both pairs, it's about either of them
+        .push_maybe(thresholds_error)
+        .push_maybe(self.cpu_label().map(|_| {
+            settings::item(
+                fl!("temperature-offset"),
+                text_input(
+                    self.offset_placeholder(),
+                    self.state.sensor_offset_string.as_str(),
+                )
+                .on_input(Message::SensorOffset),
+            )
+        }))
+        .push(settings::item(
+            fl!("hide-below", unit = self.unit_symbol()),
+            text_input("", self.state.hide_below_string.as_str()).on_input(Message::HideBelow),
+        ))
+        .push(settings::item(
+            fl!("temperature-offset"),
+            text_input("0", self.state.offset_string.as_str()).on_input(Message::Offset),
+        ))
+        .push(settings::item(
+            fl!("notify-critical"),
+            toggler(self.config.critical_notification).on_toggle(Message::CriticalNotification),
+        ))
+        .push(settings::item(
+            fl!("flash-on-critical"),
+            toggler(self.config.flash_on_critical).on_toggle(Message::FlashOnCritical),
+        ))
+        .push(settings::item(
+            fl!("disable-animations"),
+            toggler(self.config.disable_animations).on_toggle(Message::DisableAnimations),
+        ))
+        .push(settings::item(
+            fl!("alert-sound"),
+            row![
+                text_input(
+                    "dialog-warning",
+                    self.config.alert_sound.as_deref().unwrap_or_default(),
+                )
+                .on_input(Message::AlertSound),
+                button::standard(fl!("test-sound")).on_press_maybe(
+                    self.config
+                        .alert_sound
+                        .is_some()
+                        .then_some(Message::TestSound)
+                ),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ))
+        .push_maybe(self.sound_error.as_deref().map(|error| {
+            let color = self.core.system_theme().cosmic().destructive_color();
+            text::caption(error).class(cosmic::theme::Text::Color(color.into()))
+        }))
+        .push(settings::item(
+            fl!("quiet-hours"),
+            row![
+                text_input("22:00", self.quiet_hours_start_string.as_str())
+                    .on_input(Message::QuietHoursStart),
+                text("–"),
+                text_input("07:00", self.quiet_hours_end_string.as_str())
+                    .on_input(Message::QuietHoursEnd),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ))
+        .push_maybe(self.quiet_hours().map(|quiet| {
+            text::caption(fl!(
+                "alerts-muted-until",
+                time = quiet_hours::format_time(quiet.end())
+            ))
+        }))
+        .push(settings::item(
+            fl!("persist-history"),
+            toggler(self.config.persist_history).on_toggle(Message::PersistHistory),
+        ))
+        .push(settings::item(
+            fl!("log-to-file"),
+            toggler(self.config.log_to_file.is_some()).on_toggle(Message::LogToFile),
+        ))
+        .push_maybe(self.config.log_to_file.is_some().then(|| {
+            settings::item(
+                fl!("log-file"),
+                text_input("readings.csv", self.log_path_string.as_str())
+                    .on_input(Message::LogPathString)
+                    .on_submit(Message::SubmitLogPath),
+            )
+        }))
+        .push_maybe(self.config.log_to_file.is_some().then(|| {
+            settings::item(
+                fl!("log-max-size"),
+                text_input("10", self.log_max_size_string.as_str())
+                    .on_input(Message::LogMaxSize)
+                    .on_submit(Message::SubmitLogMaxSize),
+            )
+        }))
+        .push_maybe(self.sample_log_error.as_deref().map(|error| {
+            let color = self.core.system_theme().cosmic().warning_color();
+            settings::item(
+                fl!("log-failed"),
+                text::caption(error).class(cosmic::theme::Text::Color(color.into())),
+            )
+        }))
+        .push(settings::item(
+            fl!("critical-action"),
+            text_input("systemctl suspend", self.critical_action_string.as_str())
+                .on_input(Message::CriticalAction)
+                .on_submit(Message::SubmitCriticalAction)
+                .on_unfocus(Message::SubmitCriticalAction),
+        ))
+        .push(settings::item(
+            fl!("critical-action-delay"),
+            text_input("30", self.critical_action_delay_string.as_str())
+                .on_input(Message::CriticalActionDelay),
+        ))
+        .push_maybe(self.critical_action_error.as_deref().map(|error| {
+            let color = self.core.system_theme().cosmic().destructive_color();
+            text::caption(error).class(cosmic::theme::Text::Color(color.into()))
+        }))
+        .push(settings::item(
+            fl!("metrics-enabled"),
+            toggler(self.config.metrics_enabled).on_toggle(Message::MetricsEnabled),
+        ))
+        .push_maybe(self.config.metrics_enabled.then(|| {
+            settings::item(
+                fl!("metrics-port"),
+                text_input("9101", self.metrics_port_string.as_str())
+                    .on_input(Message::MetricsPort)
+                    .on_submit(Message::SubmitMetricsPort),
+            )
+        }))
+        .push(settings::item(
+            fl!("mqtt-enabled"),
+            toggler(self.config.mqtt_enabled).on_toggle(Message::MqttEnabled),
+        ))
+        .push_maybe(self.config.mqtt_enabled.then(|| {
+            text::caption(fl!(
+                "mqtt-target",
+                topic = self.config.mqtt_topic.as_str(),
+                broker = self.config.mqtt_broker.as_str()
+            ))
+        }))
+        .push(settings::item(
+            fl!("socket-enabled"),
+            toggler(self.config.socket_enabled).on_toggle(Message::SocketEnabled),
+        ))
+        .push_maybe(
+            self.socket_path()
+                .filter(|_| self.config.socket_enabled)
+                .map(|path| text::caption(path.display().to_string())),
+        )
+        .push_maybe(self.mqtt_error.as_deref().map(|error| {
+            let color = self.core.system_theme().cosmic().warning_color();
+            settings::item(
+                fl!("mqtt-failed"),
+                text::caption(error).class(cosmic::theme::Text::Color(color.into())),
+            )
+        }))
+        .push(settings::item(
+            fl!("monitor-command"),
+            text_input(
+                "cosmic-monitor",
+                self.config.monitor_command.as_deref().unwrap_or_default(),
+            )
+            .on_input(Message::MonitorCommand),
+        ))
+        .push_maybe(self.monitor_error.as_deref().map(|error| {
+            let color = self.core.system_theme().cosmic().destructive_color();
+            text::caption(error).class(cosmic::theme::Text::Color(color.into()))
+        }))
+        .push(settings::item(
+            fl!("show-load"),
+            toggler(self.config.show_load).on_toggle(Message::ShowLoad),
+        ))
+        .push(settings::item(
+            fl!("show-fan"),
+            toggler(self.config.show_fan).on_toggle(Message::ShowFan),
+        ))
+        .push(settings::item(
+            fl!("show-power"),
+            toggler(self.config.show_power).on_toggle(Message::ShowPower),
+        ))
+        .push(settings::item(
+            fl!("show-throttling"),
+            toggler(self.config.show_throttling).on_toggle(Message::ShowThrottling),
+        ))
+        .push(settings::item(
+            fl!("show-hot-core"),
+            toggler(self.config.show_hot_core).on_toggle(Message::ShowHotCore),
+        ))
+        .push_maybe(self.config.show_power.then(|| {
+            settings::item(
+                fl!("power-in-panel"),
+                toggler(self.config.power_in_panel).on_toggle(Message::PowerInPanel),
+            )
+        }))
+        .push(settings::item(
+            fl!("show-both-units"),
+            toggler(self.config.show_both_units).on_toggle(Message::ShowBothUnits),
+        ))
+        .push(settings::item(
+            fl!("show-icon"),
+            toggler(self.config.show_icon).on_toggle(Message::ShowIcon),
+        ))
+        .push(settings::item(
+            fl!("panel-style"),
+            dropdown(
+                PANEL_STYLE_OPTIONS.as_slice(),
+                PanelStyle::ALL
+                    .iter()
+                    .position(|style| *style == self.config.panel_style),
+                Message::PanelStyle,
+            ),
+        ))
+        .push(settings::item(
+            fl!("threshold-indication"),
+            dropdown(
+                THRESHOLD_INDICATION_OPTIONS.as_slice(),
+                ThresholdIndication::ALL
+                    .iter()
+                    .position(|indication| *indication == self.config.threshold_indication),
+                Message::ThresholdIndication,
+            ),
+        ))
+        .push(settings::item(
+            fl!("sensor"),
+            dropdown(
+                self.sensor_option_names.as_slice(),
+                self.selected_sensor_index(),
+                Message::SelectSensor,
+            ),
+        ))
+        .push(text::caption(fl!("scroll-to-switch")))
+        .extend(
+            self.sensor_options
+                .iter()
+                .zip(self.sensor_option_names.iter())
+                .enumerate()
+                .map(|(index, (label, name))| {
+                    let mut name = text::body(name.as_str());
+                    if Some(index) == self.selected_sensor_index() {
+                        name = name.class(cosmic::theme::Text::Accent);
+                    }
+                    // The automatic detection isn't a sensor
+                    if index == 0 {
+                        return Element::from(name);
+                    }
+
+                    // Right-clicking the row does the same as the button
+                    mouse_area(
+                        row![
+                            name,
+                            horizontal_space(),
+                            button::text(fl!("ignore-sensor"))
+                                .on_press(Message::IgnoreSensor(label.clone())),
+                        ]
+                        .align_y(Alignment::Center),
+                    )
+                    .on_right_press(Message::IgnoreSensor(label.clone()))
+                    .into()
+                }),
+        )
+        .push_maybe(
+            (!self.config.sensor_blacklist.is_empty())
+                .then(|| text::caption(fl!("ignored-sensors"))),
+        )
+        .extend(self.config.sensor_blacklist.iter().map(|pattern| {
+            Element::from(
+                row![
+                    text::body(pattern.as_str()),
+                    horizontal_space(),
+                    button::text(fl!("unignore-sensor"))
+                        .on_press(Message::UnignoreSensor(pattern.clone())),
+                ]
+                .align_y(Alignment::Center),
+            )
+        }))
+        .push(settings::item(
+            fl!("sensor-backend"),
+            dropdown(
+                SENSOR_BACKEND_OPTIONS,
+                SensorBackend::ALL
+                    .iter()
+                    .position(|backend| *backend == self.config.sensor_backend),
+                Message::SensorBackend,
+            ),
+        ))
+        .push(settings::item(
+            fl!("combine-cores"),
+            dropdown(
+                CORE_AGGREGATION_OPTIONS.as_slice(),
+                CoreAggregation::ALL
+                    .iter()
+                    .position(|aggregation| *aggregation == self.config.core_aggregation),
+                Message::CoreAggregation,
+            ),
+        ))
+        .push(settings::item(
+            fl!("prefer-tctl"),
+            toggler(self.config.prefer_tctl).on_toggle(Message::PreferTctl),
+        ))
+        .push_maybe((!self.ccd_temps.is_empty()).then(|| {
+            settings::item(
+                fl!("hottest-ccd"),
+                toggler(self.config.hottest_ccd).on_toggle(Message::HottestCcd),
+            )
+        }))
+        .extend(self.ccd_temps.iter().map(|(label, temp)| {
+            Element::from(settings::item(
+                label.as_str(),
+                text(self.format_temp(Some(*temp))),
+            ))
+        }))
+        .push(settings::item(
+            fl!("display"),
+            dropdown(
+                DISPLAY_MODE_OPTIONS.as_slice(),
+                DisplayMode::ALL
+                    .iter()
+                    .position(|mode| *mode == self.config.display_mode),
+                Message::DisplayMode,
+            ),
+        ))
+        .push(settings::item(
+            fl!("decimal-places"),
+            dropdown(
+                DECIMAL_PLACES_OPTIONS,
+                Some(self.decimal_places()),
+                Message::DecimalPlaces,
+            ),
+        ))
+        .push(settings::item(
+            fl!("rounding-step"),
+            dropdown(
+                ROUNDING_STEP_OPTIONS.as_slice(),
+                ROUNDING_STEPS
+                    .iter()
+                    .position(|step| *step == self.config.rounding_step),
+                Message::RoundingStep,
+            ),
+        ))
+        .push(settings::item(
+            fl!("smoothing"),
+            dropdown(
+                SMOOTHING_OPTIONS.as_slice(),
+                SMOOTHING_FACTORS
+                    .iter()
+                    .position(|factor| *factor == self.config.smoothing),
+                Message::Smoothing,
+            ),
+        ))
+        .push_maybe(self.config.smoothing.is_some().then(|| {
+            settings::item(
+                fl!("smoothed-raw"),
+                text(format!(
+                    "{} / {}",
+                    self.format_temp(self.state.smoothed_temp),
+                    self.format_temp(self.state.temp)
+                )),
+            )
+        }))
+        .push(settings::item(
+            fl!("fixed-width"),
+            toggler(self.config.fixed_width).on_toggle(Message::FixedWidth),
+        ))
+        .push(settings::item(
+            fl!("panel-format"),
+            text_input(
+                panel_format::DEFAULT_PANEL_FORMAT,
+                self.config.panel_format.as_str(),
+            )
+            .on_input(Message::PanelFormat),
+        ))
+        .push(settings::item(
+            fl!("panel-prefix"),
+            text_input("", self.config.panel_prefix.as_str()).on_input(Message::PanelPrefix),
+        ))
+        .push(settings::item(
+            fl!("panel-suffix"),
+            text_input("", self.config.panel_suffix.as_str()).on_input(Message::PanelSuffix),
+        ))
+        // Uses a sample value until there is a reading, so the format can be tried out
+        .push(text::caption(fl!(
+            "panel-format-preview",
+            text = self.format_panel_temp(self.state.temp.or(Some(45.0)))
+        )))
+        .push_maybe(access_problem)
+        .push_maybe(no_sensors_in_vm)
+        .push_maybe(read_error)
+        .push_maybe(stale)
+        .push_maybe(config_write_error)
+        .push(settings::item(
+            fl!("cpu"),
+            text(self.format_sensor_limits()),
+        ))
+        .push_maybe(self.config.show_both_units.then(|| {
+            let secondary = units::secondary_unit(self.config.unit);
+            settings::item(
+                fl!("both-units"),
+                row![
+                    text(self.format_temp(self.state.temp)),
+                    text(
+                        self.state
+                            .temp
+                            .map_or(units::NO_VALUE.to_string(), |celsius| {
+                                units::format_temp(celsius, secondary, self.decimal_places())
+                            })
+                    ),
+                ]
+                .spacing(16),
+            )
+        }))
+        .push(settings::item(
+            fl!("cpu-load"),
+            text(format_load(self.load)),
+        ))
+        .push_maybe(
+            (self.config.show_fan && self.fans.is_empty())
+                .then(|| settings::item(fl!("fans"), text(fl!("not-found")))),
+        )
+        .push_maybe(
+            self.power
+                .map(|watts| settings::item(fl!("package-power"), text(format_power(watts)))),
+        )
+        .push_maybe(self.throttle.map(|throttle| {
+            let status = if throttle.active {
+                fl!("throttling-active", count = throttle.events)
+            } else {
+                fl!("throttling-inactive", count = throttle.events)
+            };
+            settings::item(fl!("throttling"), text(status))
+        }))
+        .extend(self.fans.iter().map(|fan| {
+            Element::from(settings::item(
+                fan.label.as_str(),
+                text(fl!("fan-speed", rpm = fan.rpm)),
+            ))
+        }))
+        .push(settings::item(
+            fl!("gpu-sensor"),
+            text(self.gpu_sensor.clone().unwrap_or_else(|| fl!("not-found"))),
+        ))
+        .push_maybe(
+            (self.config.display_mode == DisplayMode::HottestAny).then(|| {
+                let sensor = match &self.hottest_sensor {
+                    Some((label, temp)) => format!("{label} ({})", self.format_temp(Some(*temp))),
+                    None => fl!("not-found"),
+                };
+                settings::item(fl!("hottest-sensor"), text(sensor))
+            }),
+        )
+        .push_maybe(
+            (self.config.display_mode == DisplayMode::Headroom
+                && self.headroom_critical().is_none())
+            .then(|| text::caption(fl!("headroom-no-critical"))),
+        )
+        .push(settings::item(
+            fl!("show-drive-temps"),
+            toggler(self.config.show_drive_temps).on_toggle(Message::ShowDriveTemps),
+        ))
+        .push_maybe(
+            (self.config.show_drive_temps && !self.drive_temps.is_empty())
+                .then(|| text::heading(fl!("drives"))),
+        )
+        .extend(self.drive_temps.iter().map(|(label, temp)| {
+            Element::from(settings::item(
+                label.as_str(),
+                text(self.format_temp(Some(*temp))),
+            ))
+        }))
+        .push(settings::item(
+            fl!("peak", temp = self.format_temp(self.state.peak)),
+            button::standard(fl!("reset")).on_press(Message::ResetPeak),
+        ))
+        .push(
+            button::standard(if self.copied {
+                fl!("copied")
+            } else {
+                fl!("copy-readings")
+            })
+            .on_press(Message::CopyReadings),
+        )
+        .push(button::standard(fl!("open-settings")).on_press(Message::OpenSettings))
+        .push(
+            button::standard(if self.updates_paused {
+                fl!("menu-resume-updates")
+            } else {
+                fl!("menu-pause-updates")
+            })
+            .on_press(Message::TogglePause),
+        )
+        .extend(STATS_WINDOWS.iter().map(|window| {
+            let minutes = window.as_secs() / 60;
+            let values = match self.stats.summary(*window) {
+                Some(summary) => fl!(
+                    "stats-values",
+                    min = self.format_temp(Some(summary.min)),
+                    avg = self.format_temp(Some(summary.avg)),
+                    max = self.format_temp(Some(summary.max))
+                ),
+                None => units::NO_VALUE.to_string(),
+            };
+            Element::from(settings::item(
+                fl!("stats-last-minutes", minutes = minutes),
+                text(values),
+            ))
+        }));
+
+        content_list = content_list.push(section_toggle(
+            fl!("all-sensors"),
+            self.show_all_sensors,
+            Message::ToggleAllSensors,
+        ));
+        // Grouped like `sensors` prints them, the list is already sorted by chip
+        let mut previous_chip = None;
+        for (chip, label, temp) in self.all_sensors.iter() {
+            if previous_chip != Some(chip) {
+                content_list = content_list.push(text::caption_heading(chip.as_str()));
+                previous_chip = Some(chip);
+            }
+            // sysinfo repeats the chip in front of every label
+            let name = label
+                .strip_prefix(chip.as_str())
+                .map(str::trim_start)
+                .filter(|name| !name.is_empty())
+                .unwrap_or(label);
+            content_list = content_list.push(settings::item(name, text(self.format_temp(*temp))));
+        }
 
-        // Set the widget content list as the popup_container for the applet
+        content_list = content_list.push(section_toggle(
+            fl!("diagnostics"),
+            self.show_diagnostics,
+            Message::ToggleDiagnostics,
+        ));
+        if self.show_diagnostics {
+            content_list = content_list
+                .extend(self.diagnostics().into_iter().map(|(label, value)| {
+                    Element::from(settings::item(label, text::caption(value)))
+                }))
+                .push(button::standard(fl!("copy-diagnostics")).on_press(Message::CopyDiagnostics));
+        }
+
+        content_list = content_list.push(
+            segmented_control::horizontal(&self.graph_ranges).on_activate(Message::GraphRange),
+        );
+        let scale = self
+            .config
+            .graph_fixed_scale
+            .then_some((self.config.graph_min_celsius, self.config.graph_max_celsius));
+        if let Some(graph) = HistoryGraph::new(
+            &self.history,
+            self.config.graph_range.duration(),
+            scale,
+            (self.warning_threshold(), self.critical_threshold()),
+            |celsius| self.to_display_unit(celsius),
+        ) {
+            let labels = column![
+                text::caption(units::format_value(
+                    graph.max,
+                    self.config.unit,
+                    self.decimal_places()
+                )),
+                vertical_space(),
+                text::caption(units::format_value(
+                    graph.min,
+                    self.config.unit,
+                    self.decimal_places()
+                )),
+            ]
+            .height(Length::Fixed(GRAPH_HEIGHT));
+
+            content_list = content_list.push(
+                row![
+                    labels,
+                    canvas(graph)
+                        .width(Length::Fill)
+                        .height(Length::Fixed(GRAPH_HEIGHT)),
+                ]
+                .spacing(8),
+            );
+        }
+        content_list = content_list.push(settings::item(
+            fl!("graph-fixed-scale"),
+            toggler(self.config.graph_fixed_scale).on_toggle(Message::GraphFixedScale),
+        ));
+
+        if !self.core_temps.is_empty() {
+            let hottest = hottest_core(&self.core_temps);
+            content_list = content_list
+                .push(text::caption_heading(fl!("cores")))
+                .push_maybe(hottest.map(|(label, celsius)| {
+                    text::caption(fl!(
+                        "hottest-core",
+                        core = label.as_str(),
+                        temp = self.format_temp(Some(*celsius))
+                    ))
+                }))
+                .extend(self.core_temps.iter().map(|(label, celsius)| {
+                    let is_hottest = hottest.is_some_and(|(hot, _)| hot == label);
+                    self.core_bar(label, *celsius, is_hottest)
+                }));
+        }
+
+        let content_list = content_list
+            .padding(self.core.applet.suggested_padding(true))
+            .spacing(8);
+
+        // Set the widget content list as the popup_container for the applet. The scrollable
+        // keeps everything reachable when the popup is shorter than its content.
         self.core
             .applet
-            .popup_container(container(content_list))
+            .popup_container(container(scrollable(content_list)))
             .into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn popup_opens_below_a_top_panel() {
+        assert_eq!(
+            popup_placement(PanelAnchor::Top),
+            (Anchor::Bottom, Gravity::Bottom, (0, POPUP_GAP))
+        );
+    }
+
+    #[test]
+    fn popup_opens_above_a_bottom_panel() {
+        assert_eq!(
+            popup_placement(PanelAnchor::Bottom),
+            (Anchor::Top, Gravity::Top, (0, -POPUP_GAP))
+        );
+    }
+
+    #[test]
+    fn popup_opens_right_of_a_left_panel() {
+        assert_eq!(
+            popup_placement(PanelAnchor::Left),
+            (Anchor::Right, Gravity::Right, (POPUP_GAP, 0))
+        );
+    }
+
+    #[test]
+    fn popup_opens_left_of_a_right_panel() {
+        assert_eq!(
+            popup_placement(PanelAnchor::Right),
+            (Anchor::Left, Gravity::Left, (-POPUP_GAP, 0))
+        );
+    }
+
+    #[test]
+    fn popup_hangs_from_the_tracked_rectangle() {
+        let tracked = Rectangle {
+            x: 12.0,
+            y: 4.5,
+            width: 60.0,
+            height: 32.0,
+        };
+        assert_eq!(
+            popup_anchor_rect(tracked, (16, 16), 8),
+            Rectangle {
+                x: 12,
+                y: 4,
+                width: 60,
+                height: 32
+            }
+        );
+    }
+
+    #[test]
+    fn popup_hangs_from_the_surface_until_the_rectangle_is_known() {
+        assert_eq!(
+            popup_anchor_rect(Rectangle::default(), (16, 16), 8),
+            Rectangle {
+                x: 0,
+                y: 0,
+                width: 32,
+                height: 32
+            }
+        );
+    }
+}